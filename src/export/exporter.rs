@@ -0,0 +1,89 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use image::RgbaImage;
+
+use crate::file::FileFilter;
+
+use super::spritesheet::SpritesheetExporter;
+
+/// One rendered frame, with the outfit and character effects already applied
+#[derive(Debug, Clone)]
+pub struct RenderedFrame {
+    pub image: RgbaImage,
+    pub duration_ms: u32,
+}
+
+/// An animation rendered to images, which is all an exporter gets to see
+#[derive(Debug, Clone)]
+pub struct RenderedAnimation {
+    pub character: String,
+    pub animation: String,
+    pub outfit: Option<String>,
+    pub fps: u32,
+    /// Width and height of every frame (the character's canvas size)
+    pub frame_size: (u32, u32),
+    pub frames: Vec<RenderedFrame>,
+}
+
+/// A file format animations can be exported to. Exporters run on the export worker
+/// thread, once per animation (and outfit) being exported.
+pub trait Exporter: Send + Sync + fmt::Debug {
+    /// Name shown in the Export menu
+    fn name(&self) -> &'static str;
+
+    /// File types offered when exporting a single animation; the first extension is
+    /// also used for the files of Export All
+    fn filters(&self) -> &'static [FileFilter];
+
+    /// File name suggested when exporting a single animation
+    fn default_file_name(&self) -> &'static str;
+
+    /// Write the animation to `path` plus any files that go with it, returning every
+    /// path written
+    fn export(&self, animation: &RenderedAnimation, path: &Path) -> Result<Vec<String>, String>;
+
+    fn extension(&self) -> &'static str {
+        self.filters()
+            .first()
+            .and_then(|filter| filter.extensions.first())
+            .copied()
+            .unwrap_or("")
+    }
+}
+
+/// Every export format available, in Export menu order
+#[derive(Debug, Clone)]
+pub struct ExporterRegistry {
+    exporters: Vec<Arc<dyn Exporter>>,
+}
+
+impl Default for ExporterRegistry {
+    /// The formats built into the app
+    fn default() -> Self {
+        let mut registry = Self {
+            exporters: Vec::new(),
+        };
+        registry.register(Arc::new(SpritesheetExporter));
+        registry
+    }
+}
+
+impl ExporterRegistry {
+    /// Add a format, replacing any exporter with the same name
+    pub fn register(&mut self, exporter: Arc<dyn Exporter>) {
+        match self
+            .exporters
+            .iter_mut()
+            .find(|e| e.name() == exporter.name())
+        {
+            Some(existing) => *existing = exporter,
+            None => self.exporters.push(exporter),
+        }
+    }
+
+    pub fn all(&self) -> &[Arc<dyn Exporter>] {
+        &self.exporters
+    }
+}
@@ -0,0 +1,426 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use crate::model::{Character, Project};
+use crate::state::AppState;
+
+use super::exporter::Exporter;
+use super::render::render_animation;
+
+/// One animation to export, optionally in an outfit, written to `path`
+#[derive(Debug, Clone)]
+pub struct SheetSpec {
+    pub character: String,
+    pub animation: String,
+    pub outfit: Option<String>,
+    pub frame_count: usize,
+    pub path: String,
+}
+
+impl SheetSpec {
+    pub fn label(&self) -> String {
+        match self.outfit {
+            Some(ref outfit) => format!("{} ({})", self.animation, outfit),
+            None => self.animation.clone(),
+        }
+    }
+}
+
+/// Where one spritesheet of an export job is at
+#[derive(Debug, Clone, PartialEq)]
+pub enum SheetStatus {
+    Waiting,
+    Rendering,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+/// Progress of one spritesheet, shown as a row in the export progress window
+#[derive(Debug, Clone)]
+pub struct SheetProgress {
+    pub label: String,
+    pub frames_done: usize,
+    pub frame_count: usize,
+    pub status: SheetStatus,
+}
+
+/// State shared between the UI and the export worker
+#[derive(Debug)]
+pub struct ExportProgress {
+    sheets: Mutex<Vec<SheetProgress>>,
+    cancelled: AtomicBool,
+}
+
+impl ExportProgress {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Count one more rendered frame of a sheet
+    pub fn frame_done(&self, sheet: usize) {
+        if let Ok(mut sheets) = self.sheets.lock() {
+            if let Some(progress) = sheets.get_mut(sheet) {
+                progress.frames_done += 1;
+            }
+        }
+    }
+
+    fn set_status(&self, sheet: usize, status: SheetStatus) {
+        if let Ok(mut sheets) = self.sheets.lock() {
+            if let Some(progress) = sheets.get_mut(sheet) {
+                progress.status = status;
+            }
+        }
+    }
+}
+
+/// What a finished export job wrote, and what it didn't
+#[derive(Debug, Default)]
+pub struct ExportOutcome {
+    /// Files written for each sheet
+    pub written: Vec<Vec<String>>,
+    /// (sheet label, error) of each sheet that failed
+    pub failed: Vec<(String, String)>,
+    pub cancelled: bool,
+}
+
+/// An export running on a worker thread, rendering the frames of each sheet in parallel
+/// and handing them to the exporter
+#[derive(Debug)]
+pub struct ExportJob {
+    /// Where the sheets go, for status messages
+    pub destination: String,
+    progress: Arc<ExportProgress>,
+    handle: Option<JoinHandle<ExportOutcome>>,
+}
+
+impl ExportJob {
+    /// Start exporting the sheets from a snapshot of the project, so editing can go on meanwhile
+    pub fn start(
+        project: Project,
+        exporter: Arc<dyn Exporter>,
+        sheets: Vec<SheetSpec>,
+        destination: impl Into<String>,
+    ) -> Self {
+        let progress = Arc::new(ExportProgress {
+            sheets: Mutex::new(
+                sheets
+                    .iter()
+                    .map(|spec| SheetProgress {
+                        label: spec.label(),
+                        frames_done: 0,
+                        frame_count: spec.frame_count,
+                        status: SheetStatus::Waiting,
+                    })
+                    .collect(),
+            ),
+            cancelled: AtomicBool::new(false),
+        });
+
+        let worker_progress = progress.clone();
+        let handle = std::thread::spawn(move || {
+            let progress = worker_progress;
+            let mut outcome = ExportOutcome::default();
+            for (index, spec) in sheets.iter().enumerate() {
+                if progress.is_cancelled() {
+                    progress.set_status(index, SheetStatus::Cancelled);
+                    continue;
+                }
+                progress.set_status(index, SheetStatus::Rendering);
+                let written = render_animation(&project, spec, &progress, index).and_then(|r| {
+                    r.map(|animation| exporter.export(&animation, Path::new(&spec.path)))
+                        .transpose()
+                });
+                match written {
+                    Ok(Some(paths)) => {
+                        progress.set_status(index, SheetStatus::Done);
+                        outcome.written.push(paths);
+                    }
+                    Ok(None) => progress.set_status(index, SheetStatus::Cancelled),
+                    Err(e) => {
+                        progress.set_status(index, SheetStatus::Failed(e.clone()));
+                        outcome.failed.push((spec.label(), e));
+                    }
+                }
+            }
+            outcome.cancelled = progress.is_cancelled();
+            outcome
+        });
+
+        Self {
+            destination: destination.into(),
+            progress,
+            handle: Some(handle),
+        }
+    }
+
+    /// Ask the worker to stop; sheets already written are kept
+    pub fn cancel(&self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.progress.is_cancelled()
+    }
+
+    /// Snapshot of every sheet's progress
+    pub fn sheets(&self) -> Vec<SheetProgress> {
+        self.progress
+            .sheets
+            .lock()
+            .map(|sheets| sheets.clone())
+            .unwrap_or_default()
+    }
+
+    /// The outcome once the worker has finished, None while it is still running
+    pub fn try_finish(&mut self) -> Option<ExportOutcome> {
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        let outcome = self
+            .handle
+            .take()?
+            .join()
+            .unwrap_or_else(|_| ExportOutcome {
+                failed: vec![("export".to_string(), "Export worker crashed".to_string())],
+                ..Default::default()
+            });
+        Some(outcome)
+    }
+}
+
+/// Start exporting the current animation, in the previewed outfit
+pub fn export_current_animation(
+    state: &AppState,
+    exporter: Arc<dyn Exporter>,
+    output_path: &str,
+) -> Result<ExportJob, String> {
+    let project = state.project.as_ref().ok_or("No project loaded")?;
+    let char_name = state
+        .active_character
+        .as_ref()
+        .ok_or("No character selected")?;
+    let character = project
+        .get_character(char_name)
+        .ok_or("Character not found")?;
+    let animation = character
+        .animations
+        .get(state.current_animation)
+        .ok_or("Animation not found")?;
+
+    if animation.frames.is_empty() {
+        return Err("Animation has no frames".to_string());
+    }
+
+    // Ensure output path has the exporter's extension
+    let extension = exporter.extension();
+    let path = if output_path
+        .to_lowercase()
+        .ends_with(&format!(".{}", extension))
+    {
+        output_path.to_string()
+    } else {
+        format!("{}.{}", output_path, extension)
+    };
+
+    let sheet = SheetSpec {
+        character: char_name.clone(),
+        animation: animation.name.clone(),
+        outfit: state.preview_outfit.clone(),
+        frame_count: animation.frames.len(),
+        path: path.clone(),
+    };
+    Ok(ExportJob::start(
+        project.clone(),
+        exporter,
+        vec![sheet],
+        path,
+    ))
+}
+
+/// Start exporting all animations of the current character, once per export outfit
+pub fn export_all_animations(
+    state: &AppState,
+    exporter: Arc<dyn Exporter>,
+    output_dir: &str,
+) -> Result<ExportJob, String> {
+    let project = state.project.as_ref().ok_or("No project loaded")?;
+    let char_name = state
+        .active_character
+        .as_ref()
+        .ok_or("No character selected")?;
+    let character = project
+        .get_character(char_name)
+        .ok_or("Character not found")?;
+
+    let outfits = export_outfits(character)?;
+
+    // Create output directory if needed
+    fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+    let extension = exporter.extension();
+    let mut sheets = Vec::new();
+    for outfit in outfits {
+        for animation in character.animations.iter().filter(|a| !a.frames.is_empty()) {
+            let path = match outfit {
+                Some(outfit) => format!(
+                    "{}/{}_{}_{}.{}",
+                    output_dir,
+                    char_name,
+                    safe_file_name(&animation.name),
+                    safe_file_name(outfit),
+                    extension
+                ),
+                None => format!(
+                    "{}/{}_{}.{}",
+                    output_dir,
+                    char_name,
+                    safe_file_name(&animation.name),
+                    extension
+                ),
+            };
+            sheets.push(SheetSpec {
+                character: char_name.clone(),
+                animation: animation.name.clone(),
+                outfit: outfit.map(str::to_string),
+                frame_count: animation.frames.len(),
+                path,
+            });
+        }
+    }
+
+    if sheets.is_empty() {
+        return Err("No animations with frames to export".to_string());
+    }
+    Ok(ExportJob::start(
+        project.clone(),
+        exporter,
+        sheets,
+        output_dir,
+    ))
+}
+
+/// One set of sheets per outfit chosen for export, or the plain look when none are.
+/// Chosen outfits that no longer exist are an error rather than silently left out.
+fn export_outfits(character: &Character) -> Result<Vec<Option<&str>>, String> {
+    if character.export_outfits.is_empty() {
+        return Ok(vec![None]);
+    }
+    let missing: Vec<&str> = character
+        .export_outfits
+        .iter()
+        .filter(|name| character.get_outfit(name).is_none())
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "{} is set to export outfit(s) that no longer exist: {}",
+            character.name,
+            missing.join(", ")
+        ));
+    }
+    Ok(character
+        .export_outfits
+        .iter()
+        .map(|name| Some(name.as_str()))
+        .collect())
+}
+
+/// Replace characters that don't belong in a file name
+fn safe_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::spritesheet::SpritesheetExporter;
+    use crate::imaging::encode_png;
+    use crate::model::{Angle, Animation, Character, Frame, Part, PlacedPart};
+
+    #[test]
+    fn test_export_job_renders_frames_in_order() {
+        let mut project = Project::new("Export");
+        let dot = image::RgbaImage::from_pixel(1, 1, image::Rgba([255, 0, 0, 255]));
+        let hash = project.images.insert_png(&encode_png(&dot).unwrap());
+
+        let mut character = Character::new(1, "Hero");
+        character.canvas_size = (8, 8);
+        let mut part = Part::new(1, "dot");
+        part.states[0]
+            .rotations
+            .get_mut(&Angle::ZERO)
+            .unwrap()
+            .image = Some(hash);
+        character.add_part(part);
+        // Ten frames with the dot moving one pixel right each frame
+        let mut walk = Animation::new("walk");
+        walk.frames = (0..10)
+            .map(|i| {
+                let mut frame = Frame::new(100);
+                let mut placed = PlacedPart::new(i + 1, 1, 1, 1);
+                placed.position = (i as f32 % 8.0, 0.0);
+                frame.placed_parts.push(placed);
+                frame
+            })
+            .collect();
+        character.add_animation(walk);
+        project.add_character(character);
+
+        let dir = std::env::temp_dir().join(format!("pss_export_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let png_path = dir.join("walk.png").to_string_lossy().to_string();
+        let sheet = SheetSpec {
+            character: "Hero".to_string(),
+            animation: "walk".to_string(),
+            outfit: None,
+            frame_count: 10,
+            path: png_path.clone(),
+        };
+
+        let exporter = Arc::new(SpritesheetExporter);
+        let mut job = ExportJob::start(project, exporter, vec![sheet], dir.to_string_lossy());
+        let outcome = loop {
+            if let Some(outcome) = job.try_finish() {
+                break outcome;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        };
+        assert!(outcome.failed.is_empty(), "{:?}", outcome.failed);
+        assert_eq!(job.sheets()[0].frames_done, 10);
+        assert_eq!(job.sheets()[0].status, SheetStatus::Done);
+
+        // Ten frames make a 4 x 3 grid; frame 5 sits in row 1, column 1
+        let sheet = image::open(&png_path).unwrap().to_rgba8();
+        assert_eq!(sheet.dimensions(), (32, 24));
+        assert_eq!(sheet.get_pixel(8 + 5, 8)[3], 255);
+        assert_eq!(sheet.get_pixel(8 + 4, 8)[3], 0);
+        let metadata = std::fs::read_to_string(dir.join("walk.json")).unwrap();
+        assert!(metadata.contains("\"duration_ms\": 100"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_export_outfits_reports_missing() {
+        let mut character = Character::new(1, "Hero");
+        assert_eq!(export_outfits(&character), Ok(vec![None]));
+        character.export_outfits = vec!["winter".to_string(), "summer".to_string()];
+        assert_eq!(
+            export_outfits(&character),
+            Err("Hero is set to export outfit(s) that no longer exist: winter, summer".to_string())
+        );
+    }
+}
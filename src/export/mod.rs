@@ -1,3 +1,8 @@
+mod exporter;
+mod job;
+mod render;
 mod spritesheet;
 
-pub use spritesheet::{export_all_animations, export_current_animation, render_frame_to_image};
+pub use exporter::{Exporter, ExporterRegistry};
+pub use job::{export_all_animations, export_current_animation, ExportJob, SheetStatus};
+pub use render::{composite_frame, render_frame_to_image};
@@ -0,0 +1,150 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::imaging::{apply_effects, blend_pixel};
+use crate::model::{Animation, Character, Project};
+
+use super::exporter::{RenderedAnimation, RenderedFrame};
+use super::job::{ExportProgress, SheetSpec};
+
+/// Render a single frame of a character's animation to an RGBA image buffer, optionally
+/// wearing an outfit, with the character's outline and shadow applied
+pub fn render_frame_to_image(
+    project: &Project,
+    character: &Character,
+    animation: &Animation,
+    frame_idx: usize,
+    outfit: Option<&str>,
+) -> Result<image::RgbaImage, String> {
+    let mut frame = composite_frame(project, animation, frame_idx, character.canvas_size, outfit)?;
+    apply_effects(&mut frame, &character.effects);
+    Ok(frame)
+}
+
+/// Composite the parts of a frame, before any character effects
+pub fn composite_frame(
+    project: &Project,
+    animation: &Animation,
+    frame_idx: usize,
+    canvas_size: (u32, u32),
+    outfit: Option<&str>,
+) -> Result<image::RgbaImage, String> {
+    if animation.frames.get(frame_idx).is_none() {
+        return Err(format!("Frame {} not found", frame_idx));
+    }
+
+    let (canvas_w, canvas_h) = canvas_size;
+    let mut canvas = image::RgbaImage::new(canvas_w, canvas_h);
+
+    // Draw each sprite (in order - later parts on top), with nested animations
+    // resolved to the sub-frame playing at this frame's time
+    for sprite in project.frame_sprites(animation, frame_idx, outfit) {
+        // Decoded once per image; export copies of the project share the canvas's cache
+        let image = project.sprite_pixels(&sprite).transpose()?;
+
+        if let Some(part_img) = image {
+            // Composite onto canvas at the specified position
+            let x = sprite.position.0.round() as i32;
+            let y = sprite.position.1.round() as i32;
+
+            for (px, py, pixel) in part_img.enumerate_pixels() {
+                let dest_x = x + px as i32;
+                let dest_y = y + py as i32;
+
+                // Bounds check
+                if dest_x >= 0
+                    && dest_x < canvas_w as i32
+                    && dest_y >= 0
+                    && dest_y < canvas_h as i32
+                {
+                    let dest_x = dest_x as u32;
+                    let dest_y = dest_y as u32;
+
+                    // Alpha blending
+                    blend_pixel(canvas.get_pixel_mut(dest_x, dest_y), pixel);
+                }
+            }
+        }
+    }
+
+    Ok(canvas)
+}
+
+/// Render every frame of a sheet in parallel, for handing to an exporter.
+/// Returns None if the export was cancelled first.
+pub(super) fn render_animation(
+    project: &Project,
+    spec: &SheetSpec,
+    progress: &ExportProgress,
+    sheet_index: usize,
+) -> Result<Option<RenderedAnimation>, String> {
+    let character = project
+        .get_character(&spec.character)
+        .ok_or_else(|| format!("Character '{}' not found", spec.character))?;
+    let animation = character
+        .get_animation(&spec.animation)
+        .ok_or_else(|| format!("Animation '{}' not found", spec.animation))?;
+    let frame_count = animation.frames.len();
+    if frame_count == 0 {
+        return Err("Animation has no frames".to_string());
+    }
+
+    // Workers take frames in turn until none are left
+    let outfit = spec.outfit.as_deref();
+    let next_frame = AtomicUsize::new(0);
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .min(frame_count);
+    let mut rendered: Vec<(usize, Result<image::RgbaImage, String>)> =
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut frames = Vec::new();
+                        while !progress.is_cancelled() {
+                            let i = next_frame.fetch_add(1, Ordering::Relaxed);
+                            if i >= frame_count {
+                                break;
+                            }
+                            let frame =
+                                render_frame_to_image(project, character, animation, i, outfit);
+                            frames.push((i, frame));
+                            progress.frame_done(sheet_index);
+                        }
+                        frames
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap_or_default())
+                .collect()
+        });
+    if progress.is_cancelled() {
+        return Ok(None);
+    }
+    if rendered.len() != frame_count {
+        return Err("Rendering a frame failed".to_string());
+    }
+    rendered.sort_by_key(|(i, _)| *i);
+
+    let frames = rendered
+        .into_iter()
+        .zip(&animation.frames)
+        .map(|((_, image), frame)| {
+            Ok(RenderedFrame {
+                image: image?,
+                duration_ms: frame.duration_ms,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(Some(RenderedAnimation {
+        character: character.name.clone(),
+        animation: animation.name.clone(),
+        outfit: spec.outfit.clone(),
+        fps: animation.fps,
+        frame_size: character.canvas_size,
+        frames,
+    }))
+}
@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::bundle::BUNDLE_EXTENSION;
+use crate::state::BrowsePurpose;
+
+/// What the browser picks
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BrowseMode {
+    Open,
+    Save,
+    Folder,
+}
+
+/// Named group of file extensions shown in the filter dropdown
+#[derive(Debug, Clone, Copy)]
+pub struct FileFilter {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+}
+
+impl FileFilter {
+    pub fn matches(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                self.extensions
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(ext))
+            })
+    }
+}
+
+/// One row in the directory listing
+#[derive(Debug, Clone)]
+pub struct BrowserEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub is_dir: bool,
+}
+
+impl BrowserEntry {
+    /// Bundle projects are directories but are picked like files
+    pub fn is_bundle(&self) -> bool {
+        self.is_dir && has_bundle_extension(&self.path)
+    }
+}
+
+fn has_bundle_extension(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(BUNDLE_EXTENSION))
+}
+
+/// In-app file browser state, shared by every open/save/import/export action
+#[derive(Debug, Clone)]
+pub struct FileBrowser {
+    pub purpose: BrowsePurpose,
+    pub dir: PathBuf,
+    pub dir_input: String, // Editable copy of `dir` for the path field
+    pub entries: Vec<BrowserEntry>,
+    pub filter_index: usize,
+    pub file_name: String, // Selected file (open) or name to save as
+    pub confirm_overwrite: Option<PathBuf>,
+    pub error: Option<String>,
+    pub failed_thumbnails: HashSet<PathBuf>, // Images that could not be decoded, not retried
+}
+
+impl FileBrowser {
+    pub fn new(purpose: BrowsePurpose, start_dir: PathBuf) -> Self {
+        let mut browser = Self {
+            file_name: purpose.default_file_name().to_string(),
+            purpose,
+            dir: PathBuf::new(),
+            dir_input: String::new(),
+            entries: Vec::new(),
+            filter_index: 0,
+            confirm_overwrite: None,
+            error: None,
+            failed_thumbnails: HashSet::new(),
+        };
+        browser.navigate(start_dir);
+        browser
+    }
+
+    pub fn mode(&self) -> BrowseMode {
+        self.purpose.mode()
+    }
+
+    pub fn filter(&self) -> Option<&'static FileFilter> {
+        self.purpose.filters().get(self.filter_index)
+    }
+
+    /// Change directory and re-read its listing (stays put if the directory can't be read)
+    pub fn navigate(&mut self, dir: PathBuf) {
+        match read_entries(&dir) {
+            Ok(entries) => {
+                self.dir = dir;
+                self.entries = entries;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+        self.dir_input = self.dir.to_string_lossy().to_string();
+    }
+
+    pub fn refresh(&mut self) {
+        self.navigate(self.dir.clone());
+    }
+
+    pub fn go_up(&mut self) {
+        if let Some(parent) = self.dir.parent() {
+            self.navigate(parent.to_path_buf());
+        }
+    }
+
+    /// Entries to list: directories, plus files matching the current filter
+    pub fn visible_entries(&self) -> impl Iterator<Item = &BrowserEntry> {
+        let filter = self.filter();
+        let folders_only = self.mode() == BrowseMode::Folder;
+        self.entries.iter().filter(move |entry| {
+            if entry.is_dir && !entry.is_bundle() {
+                return true;
+            }
+            !folders_only && filter.is_none_or(|f| f.matches(&entry.path))
+        })
+    }
+
+    /// Path the browser would return right now, if any
+    pub fn target_path(&self) -> Option<PathBuf> {
+        match self.mode() {
+            BrowseMode::Folder => Some(if self.file_name.is_empty() {
+                self.dir.clone()
+            } else {
+                self.dir.join(&self.file_name)
+            }),
+            BrowseMode::Open => {
+                (!self.file_name.is_empty()).then(|| self.dir.join(&self.file_name))
+            }
+            BrowseMode::Save => {
+                if self.file_name.trim().is_empty() {
+                    return None;
+                }
+                let path = self.dir.join(self.file_name.trim());
+                // Add the filter's extension when the name has none it accepts
+                // (a name that is an existing folder is left alone so it can be entered)
+                let is_folder = path.is_dir() && !has_bundle_extension(&path);
+                match self.filter() {
+                    Some(filter) if !is_folder && !filter.matches(&path) => {
+                        let mut name = path.into_os_string();
+                        name.push(".");
+                        name.push(filter.extensions[0]);
+                        Some(PathBuf::from(name))
+                    }
+                    _ => Some(path),
+                }
+            }
+        }
+    }
+
+    /// Accept the current selection, returning the chosen path. Returns None while the
+    /// browser should stay open: it entered a folder, needs overwrite confirmation
+    /// (confirm again to replace), or the path is not usable.
+    pub fn confirm(&mut self) -> Option<PathBuf> {
+        let path = self.target_path()?;
+        let is_folder = path.is_dir() && !has_bundle_extension(&path);
+        match self.mode() {
+            BrowseMode::Folder if is_folder => Some(path),
+            BrowseMode::Folder => {
+                self.error = Some(format!("{} is not a folder", path.display()));
+                None
+            }
+            _ if is_folder => {
+                self.file_name = self.purpose.default_file_name().to_string();
+                self.navigate(path);
+                None
+            }
+            BrowseMode::Open if path.exists() => Some(path),
+            BrowseMode::Open => {
+                self.error = Some(format!("{} not found", path.display()));
+                None
+            }
+            BrowseMode::Save => {
+                if path.exists() && self.confirm_overwrite.as_ref() != Some(&path) {
+                    self.confirm_overwrite = Some(path);
+                    None
+                } else {
+                    Some(path)
+                }
+            }
+        }
+    }
+}
+
+fn read_entries(dir: &Path) -> Result<Vec<BrowserEntry>, String> {
+    let mut entries: Vec<BrowserEntry> = fs::read_dir(dir)
+        .map_err(|e| format!("Cannot open {}: {}", dir.display(), e))?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                return None;
+            }
+            let path = entry.path();
+            Some(BrowserEntry {
+                is_dir: path.is_dir(),
+                path,
+                name,
+            })
+        })
+        .collect();
+
+    // Folders first, then files, each sorted by name
+    entries.sort_by(|a, b| {
+        (!a.is_dir || a.is_bundle(), a.name.to_lowercase())
+            .cmp(&(!b.is_dir || b.is_bundle(), b.name.to_lowercase()))
+    });
+    Ok(entries)
+}
+
+/// The user's home directory, used when there is no better starting point
+pub fn home_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::ExporterRegistry;
+
+    #[test]
+    fn test_save_target_adds_extension() {
+        let mut browser = FileBrowser::new(
+            BrowsePurpose::SaveProjectAs { then: None },
+            std::env::temp_dir(),
+        );
+        browser.file_name = "hero".to_string();
+        assert_eq!(
+            browser.target_path(),
+            Some(std::env::temp_dir().join("hero.pssb"))
+        );
+
+        browser.filter_index = 1;
+        browser.file_name = "hero.pss".to_string();
+        assert_eq!(
+            browser.target_path(),
+            Some(std::env::temp_dir().join("hero.pss"))
+        );
+    }
+
+    #[test]
+    fn test_confirm_enters_folders_and_asks_before_overwrite() {
+        let dir = std::env::temp_dir().join(format!("pss_browser_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sprites")).unwrap();
+        fs::write(dir.join("sheet.png"), b"").unwrap();
+
+        let exporter = ExporterRegistry::default().all()[0].clone();
+        let mut browser =
+            FileBrowser::new(BrowsePurpose::ExportAnimation { exporter }, dir.clone());
+        browser.file_name = "sprites".to_string();
+        assert_eq!(browser.confirm(), None);
+        assert_eq!(browser.dir, dir.join("sprites"));
+
+        browser.navigate(dir.clone());
+        browser.file_name = "sheet.png".to_string();
+        assert_eq!(browser.confirm(), None);
+        assert_eq!(browser.confirm_overwrite, Some(dir.join("sheet.png")));
+        assert_eq!(browser.confirm(), Some(dir.join("sheet.png")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
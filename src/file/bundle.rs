@@ -0,0 +1,288 @@
+use base64::Engine;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::model::{content_hash, Project};
+
+/// Extension used for bundle project directories
+pub const BUNDLE_EXTENSION: &str = "pssb";
+
+/// Project manifest inside a bundle directory
+const MANIFEST_NAME: &str = "project.json";
+
+/// Subdirectory of a bundle holding content-addressed PNG files
+const IMAGES_DIR: &str = "images";
+
+/// Resolve the bundle directory for a path, accepting the directory itself or its project.json.
+/// Other directories only count if they already hold a manifest, since saving a bundle
+/// prunes its images folder.
+pub fn bundle_root(path: &Path) -> Option<PathBuf> {
+    let has_bundle_extension = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(BUNDLE_EXTENSION));
+    if has_bundle_extension || path.join(MANIFEST_NAME).is_file() {
+        Some(path.to_path_buf())
+    } else if path.file_name().is_some_and(|name| name == MANIFEST_NAME) {
+        path.parent().map(Path::to_path_buf)
+    } else {
+        None
+    }
+}
+
+/// Check if a path refers to a bundle project rather than a single-file .pss
+pub fn is_bundle_path(path: &Path) -> bool {
+    bundle_root(path).is_some()
+}
+
+/// Bundle location used when converting a single-file project (next to the original)
+pub fn bundle_path_for(legacy_path: &Path) -> PathBuf {
+    legacy_path.with_extension(BUNDLE_EXTENSION)
+}
+
+/// Read a project in either format, restoring embedded image data for bundles. Also
+/// returns problems that didn't stop it from opening, like unreadable bundle images.
+pub fn read_project(path: &Path) -> Result<(Project, Vec<String>), String> {
+    match bundle_root(path) {
+        Some(root) => load_bundle(&root),
+        None => Ok((read_project_file(path)?, Vec::new())),
+    }
+}
+
+/// Read only the project structure, without loading bundle image files (for previews)
+pub fn read_project_manifest(path: &Path) -> Result<Project, String> {
+    match bundle_root(path) {
+        Some(root) => read_project_file(&root.join(MANIFEST_NAME)),
+        None => read_project_file(path),
+    }
+}
+
+fn read_project_file(path: &Path) -> Result<Project, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Read error: {}", e))?;
+    Project::from_json(&json).map_err(|e| format!("Parse error: {}", e))
+}
+
+/// Save a project as a bundle: project.json plus one PNG file per image in the store
+pub fn save_bundle(project: &Project, root: &Path) -> Result<(), String> {
+    let images_dir = root.join(IMAGES_DIR);
+    fs::create_dir_all(&images_dir).map_err(|e| format!("Write error: {}", e))?;
+
+    // Write referenced images as files named by their content hash
+    let used = project.used_image_hashes();
+    for hash in &used {
+        let Some(base64_data) = project.images.get(hash) else {
+            continue;
+        };
+        // Files from earlier saves are kept only if their content still matches the name
+        let image_path = images_dir.join(format!("{}.png", hash));
+        if fs::read(&image_path).is_ok_and(|bytes| content_hash(&bytes) == *hash) {
+            continue;
+        }
+        let png_bytes = base64::engine::general_purpose::STANDARD
+            .decode(base64_data)
+            .map_err(|e| format!("Failed to decode base64: {}", e))?;
+        write_via_temp(&image_path, &png_bytes)?;
+    }
+
+    // The manifest references images by hash only
+    let mut manifest = project.clone();
+    manifest.images.clear();
+
+    // Write the manifest through a temp file so an interrupted save never truncates it
+    let json = manifest
+        .to_json()
+        .map_err(|e| format!("Serialize error: {}", e))?;
+    write_via_temp(&root.join(MANIFEST_NAME), json.as_bytes())?;
+
+    // Drop image files that no rotation references anymore
+    if let Ok(entries) = fs::read_dir(&images_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_unused = path.extension().is_some_and(|ext| ext == "png")
+                && path
+                    .file_stem()
+                    .is_some_and(|stem| !used.contains(stem.to_string_lossy().as_ref()));
+            if is_unused {
+                let _ = fs::remove_file(&path);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a file through a temp file next to it, so an interrupted write never leaves
+/// it truncated
+fn write_via_temp(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    fs::write(&temp_path, contents).map_err(|e| format!("Write error: {}", e))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("Write error: {}", e))
+}
+
+/// Load a bundle project, filling the image store from its image files. Missing or
+/// corrupt images are left out of the store (Validate Project lists the rotations using
+/// them) and returned as problems instead of failing the load.
+pub fn load_bundle(root: &Path) -> Result<(Project, Vec<String>), String> {
+    let mut project = read_project_file(&root.join(MANIFEST_NAME))?;
+    let images_dir = root.join(IMAGES_DIR);
+
+    let mut problems = Vec::new();
+    for hash in project.used_image_hashes() {
+        if project.images.contains(&hash) {
+            continue;
+        }
+        let image_path = images_dir.join(format!("{}.png", hash));
+        match fs::read(&image_path) {
+            Ok(png_bytes) if content_hash(&png_bytes) == hash => {
+                project.images.insert_png(&png_bytes);
+            }
+            Ok(_) => problems.push(format!("Corrupt image {}", image_path.display())),
+            Err(e) => problems.push(format!("Missing image {}: {}", image_path.display(), e)),
+        }
+    }
+
+    Ok((project, problems))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::imaging::encode_png;
+    use crate::model::{Angle, Character, Part};
+
+    fn test_png(color: [u8; 4]) -> Vec<u8> {
+        encode_png(&image::RgbaImage::from_pixel(2, 2, image::Rgba(color))).unwrap()
+    }
+
+    fn temp_bundle_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "pss_{}_{}.{}",
+            name,
+            std::process::id(),
+            BUNDLE_EXTENSION
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let mut project = Project::new("Bundle");
+        let red = project.images.insert_png(&test_png([255, 0, 0, 255]));
+        let blue = project.images.insert_png(&test_png([0, 0, 255, 255]));
+        let mut character = Character::new(1, "Hero");
+        let mut part = Part::new(1, "shield");
+        let state = &mut part.states[0];
+        state.rotations.get_mut(&Angle::ZERO).unwrap().image = Some(red.clone());
+        state
+            .rotations
+            .get_mut(&Angle::from_degrees(180.0))
+            .unwrap()
+            .image = Some(red.clone());
+        state
+            .rotations
+            .get_mut(&Angle::from_degrees(90.0))
+            .unwrap()
+            .image = Some(blue.clone());
+        character.add_part(part);
+        project.add_character(character);
+
+        let dir = temp_bundle_dir("round_trip");
+        save_bundle(&project, &dir).unwrap();
+
+        // One file per distinct image, no image data in the manifest
+        assert_eq!(fs::read_dir(dir.join(IMAGES_DIR)).unwrap().count(), 2);
+        let manifest = fs::read_to_string(dir.join(MANIFEST_NAME)).unwrap();
+        assert!(!manifest.contains(project.images.get(&red).unwrap()));
+
+        let (loaded, problems) = read_project(&dir.join(MANIFEST_NAME)).unwrap();
+        assert!(problems.is_empty());
+        let state = &loaded.characters[0].parts[0].states[0];
+        assert_eq!(loaded.images.len(), 2);
+        assert_eq!(
+            loaded.rotation_image(&state.rotations[&Angle::from_degrees(180.0)]),
+            project.images.get(&red)
+        );
+        assert_eq!(
+            loaded.rotation_image(&state.rotations[&Angle::from_degrees(90.0)]),
+            project.images.get(&blue)
+        );
+        assert!(state.rotations[&Angle::from_degrees(45.0)].image.is_none());
+
+        // Unreferenced images are removed on the next save
+        project.characters[0].parts[0].states[0]
+            .rotations
+            .get_mut(&Angle::from_degrees(90.0))
+            .unwrap()
+            .image = None;
+        save_bundle(&project, &dir).unwrap();
+        assert_eq!(fs::read_dir(dir.join(IMAGES_DIR)).unwrap().count(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_bundle_damaged_images() {
+        let mut project = Project::new("Damaged");
+        let red = project.images.insert_png(&test_png([255, 0, 0, 255]));
+        let mut character = Character::new(1, "Hero");
+        let mut part = Part::new(1, "shield");
+        part.states[0]
+            .rotations
+            .get_mut(&Angle::ZERO)
+            .unwrap()
+            .image = Some(red.clone());
+        character.add_part(part);
+        project.add_character(character);
+
+        let dir = temp_bundle_dir("damaged");
+        save_bundle(&project, &dir).unwrap();
+        let image_path = dir.join(IMAGES_DIR).join(format!("{}.png", red));
+
+        // A damaged file opens with a problem instead of failing, without the image
+        fs::write(&image_path, b"not a png").unwrap();
+        let (loaded, problems) = read_project(&dir).unwrap();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("Corrupt image"));
+        assert!(!loaded.images.contains(&red));
+
+        // Saving rewrites a file whose content no longer matches its name
+        save_bundle(&project, &dir).unwrap();
+        let (loaded, problems) = read_project(&dir).unwrap();
+        assert!(problems.is_empty());
+        assert!(loaded.images.contains(&red));
+        let temp_path = image_path.with_extension("png.tmp");
+        assert!(!temp_path.exists());
+
+        fs::remove_file(&image_path).unwrap();
+        let (_, problems) = read_project(&dir).unwrap();
+        assert!(problems[0].starts_with("Missing image"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_bundle_root_resolution() {
+        assert_eq!(
+            bundle_root(Path::new("/tmp/hero.pssb/project.json")),
+            Some(PathBuf::from("/tmp/hero.pssb"))
+        );
+        assert_eq!(
+            bundle_root(Path::new("/tmp/hero.pssb")),
+            Some(PathBuf::from("/tmp/hero.pssb"))
+        );
+        assert_eq!(bundle_root(Path::new("/tmp/hero.pss")), None);
+        // A plain folder is not a bundle until it has a manifest
+        let folder = std::env::temp_dir().join(format!("pss_folder_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        assert_eq!(bundle_root(&folder), None);
+        fs::write(folder.join(MANIFEST_NAME), "{}").unwrap();
+        assert_eq!(bundle_root(&folder), Some(folder.clone()));
+        let _ = fs::remove_dir_all(&folder);
+        assert_eq!(
+            bundle_path_for(Path::new("/tmp/hero.pss")),
+            PathBuf::from("/tmp/hero.pssb")
+        );
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::imaging::import_image_as_base64;
+use crate::model::{Angle, Part, Project, RotationMode, State};
+
+/// Default filename pattern for folder imports
+pub const DEFAULT_IMPORT_PATTERN: &str = "{part}_{state}_{angle}";
+
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "bmp"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Part,
+    State,
+    Angle,
+}
+
+/// Filename pattern such as `{part}_{state}_{angle}`, matched against file stems.
+/// `{part}` and `{angle}` are required; without `{state}` every file goes to "default".
+#[derive(Debug, Clone)]
+pub struct NamingPattern {
+    tokens: Vec<Token>,
+}
+
+impl NamingPattern {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let mut tokens = Vec::new();
+        let mut rest = pattern;
+        while !rest.is_empty() {
+            if let Some(after_brace) = rest.strip_prefix('{') {
+                let end = after_brace
+                    .find('}')
+                    .ok_or_else(|| format!("Unclosed placeholder in \"{}\"", pattern))?;
+                let token = match &after_brace[..end] {
+                    "part" => Token::Part,
+                    "state" => Token::State,
+                    "angle" => Token::Angle,
+                    other => return Err(format!("Unknown placeholder {{{}}}", other)),
+                };
+                if tokens
+                    .last()
+                    .is_some_and(|t| !matches!(t, Token::Literal(_)))
+                {
+                    return Err("Placeholders must be separated by text".to_string());
+                }
+                if tokens.contains(&token) {
+                    return Err(format!(
+                        "Placeholder {{{}}} used twice",
+                        &after_brace[..end]
+                    ));
+                }
+                tokens.push(token);
+                rest = &after_brace[end + 1..];
+            } else {
+                let end = rest.find('{').unwrap_or(rest.len());
+                tokens.push(Token::Literal(rest[..end].to_string()));
+                rest = &rest[end..];
+            }
+        }
+
+        if !tokens.contains(&Token::Part) || !tokens.contains(&Token::Angle) {
+            return Err("Pattern needs both {part} and {angle}".to_string());
+        }
+        Ok(Self { tokens })
+    }
+
+    /// Match a file stem, returning (part, state, angle)
+    pub fn match_name(&self, name: &str) -> Option<(String, String, Angle)> {
+        let mut captures = Vec::new();
+        if !match_tokens(&self.tokens, name, &mut captures) {
+            return None;
+        }
+
+        let mut part = None;
+        let mut state = "default".to_string();
+        let mut angle = None;
+        for (token, value) in captures {
+            match token {
+                Token::Part => part = Some(value),
+                Token::State => state = value,
+                Token::Angle => angle = file_angle(&value),
+                Token::Literal(_) => {}
+            }
+        }
+        Some((part?, state, angle?))
+    }
+}
+
+/// Angle written in a file name ("45", "22.5"). Whole numbers that are a 22.5° step
+/// rounded down (22, 67...) mean that step, as files were named for older versions.
+fn file_angle(text: &str) -> Option<Angle> {
+    match text.parse::<u16>() {
+        Ok(degrees) if degrees < 360 => Some(Angle::from_legacy_degrees(degrees)),
+        Ok(_) => None,
+        Err(_) => Angle::parse(text),
+    }
+}
+
+/// Backtracking matcher. Placeholders take the longest text that still lets the rest
+/// match, so `left_arm_idle_45` splits into part "left_arm", state "idle".
+fn match_tokens(tokens: &[Token], text: &str, captures: &mut Vec<(Token, String)>) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return text.is_empty();
+    };
+    if let Token::Literal(literal) = token {
+        return text
+            .strip_prefix(literal.as_str())
+            .is_some_and(|remaining| match_tokens(rest, remaining, captures));
+    }
+
+    for end in (1..=text.len()).rev().filter(|&i| text.is_char_boundary(i)) {
+        let value = &text[..end];
+        if *token == Token::Angle && !value.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            continue;
+        }
+        captures.push((token.clone(), value.to_string()));
+        if match_tokens(rest, &text[end..], captures) {
+            return true;
+        }
+        captures.pop();
+    }
+    false
+}
+
+/// One image file matched by the pattern
+#[derive(Debug, Clone)]
+pub struct ImportEntry {
+    pub path: PathBuf,
+    pub part: String,
+    pub state: String,
+    pub angle: Angle,
+    /// Whether the rotation already has an image that will be replaced
+    pub overwrites: bool,
+}
+
+/// Everything a folder import will create or overwrite for one character, for previewing
+#[derive(Debug, Clone, Default)]
+pub struct FolderImportPlan {
+    pub entries: Vec<ImportEntry>,
+    /// (part, state) -> rotation mode chosen from the angles found
+    pub states: BTreeMap<(String, String), RotationMode>,
+    pub new_parts: Vec<String>,
+    pub new_states: Vec<(String, String)>,
+    /// Files that were skipped, with the reason
+    pub skipped: Vec<String>,
+}
+
+impl FolderImportPlan {
+    pub fn overwrite_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.overwrites).count()
+    }
+}
+
+/// Pick the evenly spaced mode with the fewest angles that covers every angle,
+/// or a custom mode with exactly these angles if none does
+fn rotation_mode_for(angles: &[Angle]) -> RotationMode {
+    RotationMode::PRESETS
+        .into_iter()
+        .find(|mode| angles.iter().all(|a| mode.angles().contains(a)))
+        .unwrap_or_else(|| RotationMode::custom(angles.to_vec()))
+}
+
+/// Scan a folder and work out what importing it into a character would do.
+/// Parts the character links from the shared library are filled in place.
+pub fn plan_folder_import(
+    folder: &Path,
+    pattern: &NamingPattern,
+    project: &Project,
+    character_name: &str,
+) -> Result<FolderImportPlan, String> {
+    let mut files: Vec<PathBuf> = fs::read_dir(folder)
+        .map_err(|e| format!("Read error: {}", e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        })
+        .collect();
+    files.sort();
+
+    let mut plan = FolderImportPlan::default();
+    let mut angles_by_state: BTreeMap<(String, String), Vec<Angle>> = BTreeMap::new();
+    for path in files {
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let file_name = path
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match pattern.match_name(&stem) {
+            Some((part, state, angle)) => {
+                angles_by_state
+                    .entry((part.clone(), state.clone()))
+                    .or_default()
+                    .push(angle);
+                plan.entries.push(ImportEntry {
+                    path,
+                    part,
+                    state,
+                    angle,
+                    overwrites: false,
+                });
+            }
+            None => plan
+                .skipped
+                .push(format!("{} (name does not match pattern)", file_name)),
+        }
+    }
+
+    for ((part_name, state_name), mut angles) in angles_by_state {
+        let existing_state = project
+            .get_part(character_name, &part_name)
+            .and_then(|p| p.states.iter().find(|s| s.name == state_name));
+        if let Some(state) = existing_state {
+            angles.extend(state.rotations.keys());
+        }
+
+        let mode = rotation_mode_for(&angles);
+        if project.get_part(character_name, &part_name).is_none()
+            && !plan.new_parts.contains(&part_name)
+        {
+            plan.new_parts.push(part_name.clone());
+        }
+        if existing_state.is_none() {
+            plan.new_states
+                .push((part_name.clone(), state_name.clone()));
+        }
+        plan.states.insert((part_name, state_name), mode);
+    }
+
+    for entry in &mut plan.entries {
+        entry.overwrites = project
+            .get_part(character_name, &entry.part)
+            .and_then(|p| p.states.iter().find(|s| s.name == entry.state))
+            .and_then(|s| s.rotations.get(&entry.angle))
+            .is_some_and(|r| r.image.is_some());
+    }
+
+    Ok(plan)
+}
+
+/// Apply a folder import plan to a character, returning how many rotations were filled
+pub fn apply_folder_import(
+    project: &mut Project,
+    character_name: &str,
+    plan: &FolderImportPlan,
+) -> Result<usize, String> {
+    // Import every file up front so a bad file leaves the project untouched
+    let mut images = Vec::new();
+    for entry in &plan.entries {
+        let base64_data = import_image_as_base64(&entry.path.to_string_lossy())
+            .map_err(|e| format!("{}: {}", entry.path.display(), e))?;
+        images.push(project.images.insert_base64(&base64_data)?);
+    }
+
+    if project.get_character(character_name).is_none() {
+        return Err(format!("Character '{}' not found", character_name));
+    }
+
+    // Create parts and states, switching existing states to the mode the new angles need
+    for ((part_name, state_name), mode) in &plan.states {
+        if project.get_part(character_name, part_name).is_none() {
+            let mut part = Part::new(project.new_part_id(), part_name.as_str());
+            part.states.clear();
+            if let Some(character) = project.get_character_mut(character_name) {
+                character.add_part(part);
+            }
+        }
+        let Some(part) = project.get_part_mut(character_name, part_name) else {
+            continue;
+        };
+        match part.states.iter_mut().find(|s| s.name == *state_name) {
+            Some(state) => {
+                state.set_rotation_mode(mode.clone());
+            }
+            None => {
+                part.add_state(State::new(state_name.as_str(), mode.clone()));
+            }
+        }
+    }
+
+    let mut filled = 0;
+    for (entry, hash) in plan.entries.iter().zip(images) {
+        let rotation = project
+            .get_part_mut(character_name, &entry.part)
+            .and_then(|p| p.states.iter_mut().find(|s| s.name == entry.state))
+            .and_then(|s| s.rotations.get_mut(&entry.angle));
+        if let Some(rotation) = rotation {
+            rotation.image = Some(hash);
+            rotation.source_path = Some(entry.path.to_string_lossy().to_string());
+            rotation.generated = None;
+            filled += 1;
+        }
+    }
+
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_matching() {
+        let pattern = NamingPattern::parse(DEFAULT_IMPORT_PATTERN).unwrap();
+        assert_eq!(
+            pattern.match_name("head_idle_45"),
+            Some((
+                "head".to_string(),
+                "idle".to_string(),
+                Angle::from_degrees(45.0)
+            ))
+        );
+        assert_eq!(
+            pattern.match_name("left_arm_swing_270"),
+            Some((
+                "left_arm".to_string(),
+                "swing".to_string(),
+                Angle::from_degrees(270.0)
+            ))
+        );
+        assert_eq!(
+            pattern
+                .match_name("cape_idle_67")
+                .map(|(_, _, angle)| angle.to_string()),
+            Some("67.5".to_string())
+        );
+        assert_eq!(
+            pattern
+                .match_name("cape_idle_11.25")
+                .map(|(_, _, angle)| angle.to_string()),
+            Some("11.25".to_string())
+        );
+        assert_eq!(pattern.match_name("head_idle_400"), None);
+        assert_eq!(pattern.match_name("head_idle_front"), None);
+        assert_eq!(pattern.match_name("head"), None);
+
+        let no_state = NamingPattern::parse("{part}-{angle}").unwrap();
+        assert_eq!(
+            no_state.match_name("sword-90"),
+            Some((
+                "sword".to_string(),
+                "default".to_string(),
+                Angle::from_degrees(90.0)
+            ))
+        );
+
+        assert!(NamingPattern::parse("{part}{angle}").is_err());
+        assert!(NamingPattern::parse("{part}_{state}").is_err());
+        assert!(NamingPattern::parse("{part}_{size}_{angle}").is_err());
+    }
+
+    #[test]
+    fn test_rotation_mode_from_angles() {
+        let angles = |degrees: &[f32]| -> Vec<Angle> {
+            degrees.iter().map(|&d| Angle::from_degrees(d)).collect()
+        };
+        assert_eq!(
+            rotation_mode_for(&angles(&[0.0, 90.0])),
+            RotationMode::Deg90
+        );
+        assert_eq!(
+            rotation_mode_for(&angles(&[0.0, 45.0, 90.0])),
+            RotationMode::Deg45
+        );
+        assert_eq!(
+            rotation_mode_for(&angles(&[0.0, 22.5, 45.0])),
+            RotationMode::Deg22_5
+        );
+        assert_eq!(
+            rotation_mode_for(&angles(&[30.0, 0.0])),
+            RotationMode::Custom(angles(&[0.0, 30.0]))
+        );
+    }
+}
@@ -1,10 +1,15 @@
-mod dialogs;
+mod browser;
+mod bundle;
+mod folder_import;
+mod recovery;
 
-pub use dialogs::{
-    pick_export_file, pick_export_folder, pick_file, pick_image_file, pick_save_file,
+pub use browser::{home_dir, BrowseMode, FileBrowser, FileFilter};
+pub use bundle::{
+    bundle_path_for, bundle_root, is_bundle_path, read_project, read_project_manifest,
+    save_bundle,
 };
-
-/// Alias for pick_file for semantic clarity when opening
-pub fn pick_open_file() -> Option<std::path::PathBuf> {
-    pick_file()
-}
+pub use folder_import::{
+    apply_folder_import, plan_folder_import, FolderImportPlan, NamingPattern,
+    DEFAULT_IMPORT_PATTERN,
+};
+pub use recovery::{clear_recovery, read_recovery, write_recovery, Recovery, RecoveryLock};
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, TryLockError};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::model::{content_hash, Project};
+use crate::state::AppConfig;
+
+/// Autosaved copy of the in-memory project, used to recover work after a crash
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Recovery {
+    /// Where the project was saved, or None for a project that was never saved
+    pub project_path: Option<String>,
+    /// Seconds since the Unix epoch when the recovery file was written
+    pub saved_at: u64,
+    /// Project JSON (kept as a string so it loads through the migration pipeline)
+    pub project_json: String,
+    /// Lock on the file this was read from, held while the restore prompt is open
+    #[serde(skip)]
+    pub lock: Option<RecoveryLock>,
+}
+
+impl Recovery {
+    pub fn project(&self) -> Result<Project, String> {
+        Project::from_json(&self.project_json).map_err(|e| format!("Parse error: {}", e))
+    }
+}
+
+/// Exclusive lock on a recovery file's `.lock` companion. The instance autosaving to a
+/// file holds it, so other instances neither offer that file for restore nor delete it;
+/// the OS releases it when the instance exits or crashes.
+#[derive(Debug)]
+pub struct RecoveryLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl RecoveryLock {
+    /// Lock a recovery file, or None if another running instance holds it
+    fn acquire(path: &Path) -> Result<Option<Self>, String> {
+        let file =
+            File::create(path.with_extension("lock")).map_err(|e| format!("Lock error: {}", e))?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self {
+                path: path.to_path_buf(),
+                file,
+            })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(format!("Lock error: {}", e)),
+        }
+    }
+
+    /// Delete the recovery file and its lock
+    fn remove(self) {
+        let _ = fs::remove_file(&self.path);
+        drop(self.file);
+        let _ = fs::remove_file(self.path.with_extension("lock"));
+    }
+}
+
+/// Folder of recovery files, next to config.json
+fn recovery_dir() -> Option<PathBuf> {
+    AppConfig::config_path().map(|path| path.with_file_name("recovery"))
+}
+
+/// Recovery file for a project: named by a hash of its path, or by this process for a
+/// project that was never saved, so separate instances and projects don't share one
+pub fn recovery_path(project_path: Option<&Path>) -> Option<PathBuf> {
+    let name = match project_path {
+        Some(path) => content_hash(path.to_string_lossy().as_bytes())[..16].to_string(),
+        None => format!("unsaved-{}", std::process::id()),
+    };
+    recovery_dir().map(|dir| dir.join(name).with_extension("json"))
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Write the project to its recovery file, taking the file's lock into `held`. A file
+/// held before for another path (the project was saved elsewhere) is removed.
+pub fn write_recovery(
+    project: &Project,
+    project_path: Option<&Path>,
+    held: &mut Option<RecoveryLock>,
+) -> Result<(), String> {
+    let path = recovery_path(project_path).ok_or("No config directory")?;
+    let recovery = Recovery {
+        project_path: project_path.map(|p| p.to_string_lossy().to_string()),
+        saved_at: unix_time(SystemTime::now()),
+        project_json: project
+            .to_json()
+            .map_err(|e| format!("Serialize error: {}", e))?,
+        lock: None,
+    };
+    let json = serde_json::to_string(&recovery).map_err(|e| format!("Serialize error: {}", e))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Write error: {}", e))?;
+    }
+    if held.as_ref().is_none_or(|lock| lock.path != path) {
+        let lock =
+            RecoveryLock::acquire(&path)?.ok_or("Another instance is autosaving this project")?;
+        if let Some(previous) = held.replace(lock) {
+            previous.remove();
+        }
+    }
+    // Write through a temp file so a crash mid-write never leaves a truncated recovery file
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json).map_err(|e| format!("Write error: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("Write error: {}", e))
+}
+
+/// Find the newest recovery file that no running instance holds and that has work newer
+/// than the project it was autosaved from. It stays locked until restored or discarded.
+pub fn read_recovery() -> Option<Recovery> {
+    let mut newest: Option<Recovery> = None;
+    for entry in fs::read_dir(recovery_dir()?).ok()?.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Ok(Some(lock)) = RecoveryLock::acquire(&path) else {
+            continue;
+        };
+        let Some(mut recovery) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str::<Recovery>(&json).ok())
+        else {
+            continue;
+        };
+
+        // A project saved after the last autosave supersedes the recovery file
+        let project_modified = recovery
+            .project_path
+            .as_ref()
+            .and_then(|p| fs::metadata(p).ok())
+            .and_then(|m| m.modified().ok())
+            .map(unix_time);
+        if project_modified.is_some_and(|modified| modified >= recovery.saved_at) {
+            lock.remove();
+            continue;
+        }
+        if newest
+            .as_ref()
+            .is_none_or(|n| recovery.saved_at > n.saved_at)
+        {
+            recovery.lock = Some(lock);
+            newest = Some(recovery);
+        }
+    }
+    newest
+}
+
+/// Delete a recovery file this instance holds (after a save, or when the user discards
+/// changes). Files held by other instances are never touched.
+pub fn clear_recovery(held: &mut Option<RecoveryLock>) {
+    if let Some(lock) = held.take() {
+        lock.remove();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_lock_excludes_other_holders() {
+        let dir = std::env::temp_dir().join(format!("pss_recovery_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("project.json");
+        fs::write(&path, "{}").unwrap();
+
+        // A file held by a live owner can't be taken, so it is neither offered nor deleted
+        let mut held = RecoveryLock::acquire(&path).unwrap();
+        assert!(held.is_some());
+        assert!(RecoveryLock::acquire(&path).unwrap().is_none());
+
+        clear_recovery(&mut held);
+        assert!(!path.exists());
+        assert!(RecoveryLock::acquire(&path).unwrap().is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,139 @@
+use image::{Rgba, RgbaImage};
+
+use crate::model::{CharacterEffects, OutlineCorners};
+
+/// Alpha-blend `src` over `dst` in place
+pub fn blend_pixel(dst: &mut Rgba<u8>, src: &Rgba<u8>) {
+    let src_a = src[3] as f32 / 255.0;
+    let dst_a = dst[3] as f32 / 255.0;
+    let out_a = src_a + dst_a * (1.0 - src_a);
+
+    if out_a > 0.0 {
+        for i in 0..3 {
+            let src_c = src[i] as f32 / 255.0;
+            let dst_c = dst[i] as f32 / 255.0;
+            let out_c = (src_c * src_a + dst_c * dst_a * (1.0 - src_a)) / out_a;
+            dst[i] = (out_c * 255.0).round() as u8;
+        }
+        dst[3] = (out_a * 255.0).round() as u8;
+    }
+}
+
+/// Pixel offsets the outline reaches from an opaque pixel
+fn outline_offsets(thickness: u32, corners: OutlineCorners) -> Vec<(i32, i32)> {
+    let t = thickness as i32;
+    let mut offsets = Vec::new();
+    for dy in -t..=t {
+        for dx in -t..=t {
+            let inside = match corners {
+                OutlineCorners::Square => true,
+                OutlineCorners::Rounded => dx * dx + dy * dy <= t * t,
+            };
+            if inside && (dx, dy) != (0, 0) {
+                offsets.push((dx, dy));
+            }
+        }
+    }
+    offsets
+}
+
+fn alpha_at(image: &RgbaImage, x: i32, y: i32) -> u8 {
+    if x < 0 || y < 0 || x >= image.width() as i32 || y >= image.height() as i32 {
+        return 0;
+    }
+    image.get_pixel(x as u32, y as u32)[3]
+}
+
+/// The outline and shadow of a composited frame, without the frame itself, or None if
+/// no effect is enabled. Drawing the frame over it gives the final image.
+pub fn effects_layer(frame: &RgbaImage, effects: &CharacterEffects) -> Option<RgbaImage> {
+    if !effects.is_active() {
+        return None;
+    }
+    let (w, h) = frame.dimensions();
+
+    // Outline: transparent pixels within reach of an opaque one
+    let mut outline = RgbaImage::new(w, h);
+    let settings = &effects.outline;
+    if settings.enabled && settings.thickness > 0 {
+        let offsets = outline_offsets(settings.thickness, settings.corners);
+        let [r, g, b] = settings.color;
+        for (x, y, pixel) in outline.enumerate_pixels_mut() {
+            let (x, y) = (x as i32, y as i32);
+            if alpha_at(frame, x, y) == 0
+                && offsets
+                    .iter()
+                    .any(|(dx, dy)| alpha_at(frame, x + dx, y + dy) > 0)
+            {
+                *pixel = Rgba([r, g, b, 255]);
+            }
+        }
+    }
+
+    // Shadow: the outlined silhouette, moved by the offset
+    let mut layer = RgbaImage::new(w, h);
+    let shadow = &effects.shadow;
+    if shadow.enabled && shadow.opacity > 0.0 {
+        let [r, g, b] = shadow.color;
+        let opacity = shadow.opacity.clamp(0.0, 1.0);
+        let (ox, oy) = shadow.offset;
+        for (x, y, pixel) in layer.enumerate_pixels_mut() {
+            let (sx, sy) = (x as i32 - ox, y as i32 - oy);
+            let alpha = alpha_at(frame, sx, sy).max(alpha_at(&outline, sx, sy));
+            if alpha > 0 {
+                *pixel = Rgba([r, g, b, (alpha as f32 * opacity).round() as u8]);
+            }
+        }
+    }
+
+    for (dst, src) in layer.pixels_mut().zip(outline.pixels()) {
+        blend_pixel(dst, src);
+    }
+    Some(layer)
+}
+
+/// Apply a character's outline and shadow to a composited frame
+pub fn apply_effects(frame: &mut RgbaImage, effects: &CharacterEffects) {
+    if let Some(mut layer) = effects_layer(frame, effects) {
+        for (dst, src) in layer.pixels_mut().zip(frame.pixels()) {
+            blend_pixel(dst, src);
+        }
+        *frame = layer;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outline_and_shadow() {
+        let red = Rgba([255, 0, 0, 255]);
+        let mut frame = RgbaImage::new(5, 5);
+        frame.put_pixel(2, 2, red);
+
+        let mut effects = CharacterEffects::default();
+        effects.outline.enabled = true;
+        effects.outline.color = [0, 0, 0];
+        let mut square = frame.clone();
+        apply_effects(&mut square, &effects);
+        assert_eq!(*square.get_pixel(2, 2), red);
+        assert_eq!(square.get_pixel(1, 1)[3], 255);
+        assert_eq!(square.get_pixel(0, 2)[3], 0);
+
+        effects.outline.corners = OutlineCorners::Rounded;
+        effects.shadow.enabled = true;
+        effects.shadow.offset = (2, 2);
+        effects.shadow.color = [0, 0, 255];
+        effects.shadow.opacity = 0.5;
+        let mut rounded = frame.clone();
+        apply_effects(&mut rounded, &effects);
+        assert_eq!(*rounded.get_pixel(1, 2), Rgba([0, 0, 0, 255]));
+        // Rounded corners leave the diagonals open
+        assert_eq!(rounded.get_pixel(1, 1)[3], 0);
+        assert_eq!(rounded.get_pixel(3, 3)[3], 0);
+        // The shadow is cast by both the character and its outline
+        assert_eq!(*rounded.get_pixel(4, 4), Rgba([0, 0, 255, 128]));
+        assert_eq!(*rounded.get_pixel(4, 3), Rgba([0, 0, 255, 128]));
+    }
+}
@@ -1,8 +1,15 @@
+mod effects;
 mod processing;
+mod rotsprite;
 mod texture;
 
-pub use processing::{import_image_as_base64, is_pixel_opaque};
+pub use effects::{apply_effects, blend_pixel, effects_layer};
+pub use processing::{
+    encode_png, import_image_as_base64, is_pixel_opaque, slice_spritesheet, SheetGrid,
+};
+pub use rotsprite::rotsprite;
 pub use texture::{
-    calculate_fit_scale, create_reference_thumbnail, decode_base64_to_texture,
-    decode_base64_to_yellow_texture, load_reference_texture, render_frame_thumbnail,
+    calculate_fit_scale, create_reference_thumbnail, image_texture_key, load_image_file_texture,
+    load_reference_texture, render_frame_thumbnail, rgba_to_texture, rgba_to_yellow_texture,
+    rotation_preview_texture_key, ROTATION_PREVIEW_TEXTURE_PREFIX,
 };
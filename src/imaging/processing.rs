@@ -1,21 +1,7 @@
 use std::fs;
 
-/// Check if a pixel at a given position is opaque in a base64-encoded image
-pub fn is_pixel_opaque(base64_data: &str, x: u32, y: u32) -> bool {
-    use base64::Engine;
-
-    // Decode base64
-    let png_bytes = match base64::engine::general_purpose::STANDARD.decode(base64_data) {
-        Ok(bytes) => bytes,
-        Err(_) => return false,
-    };
-
-    // Load image
-    let img = match image::load_from_memory(&png_bytes) {
-        Ok(img) => img.to_rgba8(),
-        Err(_) => return false,
-    };
-
+/// Check if a pixel at a given position is opaque in a decoded image
+pub fn is_pixel_opaque(img: &image::RgbaImage, x: u32, y: u32) -> bool {
     // Check bounds
     if x >= img.width() || y >= img.height() {
         return false;
@@ -49,6 +35,19 @@ pub fn import_image_as_base64(path: &str) -> Result<String, String> {
     };
 
     // Re-encode as PNG to ensure consistent format
+    encode_png_base64(&img)
+}
+
+/// Encode an image as PNG bytes
+pub fn encode_png(img: &image::RgbaImage) -> Result<Vec<u8>, String> {
+    let mut png_bytes = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut png_bytes), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    Ok(png_bytes)
+}
+
+/// Encode an image as base64 PNG
+fn encode_png_base64(img: &image::DynamicImage) -> Result<String, String> {
     let mut png_bytes = Vec::new();
     let mut cursor = std::io::Cursor::new(&mut png_bytes);
     img.write_to(&mut cursor, image::ImageFormat::Png)
@@ -59,3 +58,137 @@ pub fn import_image_as_base64(path: &str) -> Result<String, String> {
         &png_bytes,
     ))
 }
+
+/// Grid layout of a spritesheet, in pixels. Cells are read left to right, top to bottom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetGrid {
+    pub cell_size: (u32, u32),
+    pub offset: (u32, u32),
+    pub spacing: (u32, u32),
+    /// Cells per row (0 = as many as fit in the sheet width)
+    pub columns: u32,
+}
+
+impl Default for SheetGrid {
+    fn default() -> Self {
+        Self {
+            cell_size: (32, 32),
+            offset: (0, 0),
+            spacing: (0, 0),
+            columns: 0,
+        }
+    }
+}
+
+impl SheetGrid {
+    /// Top-left corner of a cell, or None if the cell lies outside the sheet
+    pub fn cell_origin(&self, index: usize, sheet_size: (u32, u32)) -> Option<(u32, u32)> {
+        let (cell_w, cell_h) = self.cell_size;
+        if cell_w == 0 || cell_h == 0 {
+            return None;
+        }
+        let stride_x = cell_w + self.spacing.0;
+        let stride_y = cell_h + self.spacing.1;
+        let columns = if self.columns > 0 {
+            self.columns
+        } else {
+            (sheet_size.0.saturating_sub(self.offset.0) + self.spacing.0) / stride_x
+        };
+        if columns == 0 {
+            return None;
+        }
+
+        let x = self.offset.0 + (index as u32 % columns) * stride_x;
+        let y = self.offset.1 + (index as u32 / columns) * stride_y;
+        (x + cell_w <= sheet_size.0 && y + cell_h <= sheet_size.1).then_some((x, y))
+    }
+}
+
+/// Crop an image to the bounding box of its non-transparent pixels (None if fully transparent)
+fn trim_transparent(img: &image::RgbaImage) -> Option<image::RgbaImage> {
+    let opaque = img.enumerate_pixels().filter(|(_, _, p)| p[3] > 0);
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+    for (x, y, _) in opaque {
+        min_x = min_x.min(x);
+        min_y = min_y.min(y);
+        max_x = max_x.max(x);
+        max_y = max_y.max(y);
+    }
+    (min_x <= max_x).then(|| {
+        image::imageops::crop_imm(img, min_x, min_y, max_x - min_x + 1, max_y - min_y + 1)
+            .to_image()
+    })
+}
+
+/// Cut the first `count` cells out of a sheet. Empty (fully transparent) cells and cells
+/// outside the sheet are None.
+fn slice_cells(
+    sheet: &image::RgbaImage,
+    grid: &SheetGrid,
+    count: usize,
+    auto_trim: bool,
+) -> Vec<Option<image::RgbaImage>> {
+    (0..count)
+        .map(|index| {
+            let (x, y) = grid.cell_origin(index, sheet.dimensions())?;
+            let cell =
+                image::imageops::crop_imm(sheet, x, y, grid.cell_size.0, grid.cell_size.1)
+                    .to_image();
+            let trimmed = trim_transparent(&cell)?;
+            Some(if auto_trim { trimmed } else { cell })
+        })
+        .collect()
+}
+
+/// Slice a spritesheet file into base64-encoded PNG cells
+pub fn slice_spritesheet(
+    path: &str,
+    grid: &SheetGrid,
+    count: usize,
+    auto_trim: bool,
+) -> Result<Vec<Option<String>>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let sheet = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Invalid image: {}", e))?
+        .to_rgba8();
+
+    slice_cells(&sheet, grid, count, auto_trim)
+        .into_iter()
+        .map(|cell| {
+            cell.map(|img| encode_png_base64(&image::DynamicImage::ImageRgba8(img)))
+                .transpose()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slice_cells_with_offset_and_spacing() {
+        // Two 4x4 cells at x=1 and x=7 (offset 1, spacing 2); the second has one opaque pixel
+        let mut sheet = image::RgbaImage::new(11, 5);
+        for y in 1..5 {
+            for x in 1..5 {
+                sheet.put_pixel(x, y, image::Rgba([255, 0, 0, 255]));
+            }
+        }
+        sheet.put_pixel(9, 3, image::Rgba([0, 255, 0, 255]));
+        let grid = SheetGrid {
+            cell_size: (4, 4),
+            offset: (1, 1),
+            spacing: (2, 0),
+            columns: 0,
+        };
+
+        let cells = slice_cells(&sheet, &grid, 3, false);
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[0].as_ref().unwrap().dimensions(), (4, 4));
+        assert_eq!(cells[1].as_ref().unwrap().get_pixel(2, 2)[1], 255);
+        assert!(cells[2].is_none()); // Past the edge of the sheet
+
+        let trimmed = slice_cells(&sheet, &grid, 2, true);
+        assert_eq!(trimmed[1].as_ref().unwrap().dimensions(), (1, 1));
+    }
+}
@@ -1,88 +1,68 @@
 use bevy_egui::egui;
 use std::fs;
 
+use crate::model::Angle;
+
 const MAX_TEXTURE_SIZE: u32 = 2048;
 
-/// Decode base64 image data to an egui texture
-pub fn decode_base64_to_texture(
-    ctx: &egui::Context,
-    name: &str,
-    base64_data: &str,
-) -> Result<egui::TextureHandle, String> {
-    use base64::Engine;
+/// Texture cache key for a stored image (content-addressed, so shared by every rotation using it)
+pub fn image_texture_key(hash: &str) -> String {
+    format!("image/{}", hash)
+}
 
-    // Decode base64
-    let png_bytes = base64::engine::general_purpose::STANDARD
-        .decode(base64_data)
-        .map_err(|e| format!("Failed to decode base64: {}", e))?;
-
-    // Load image
-    let img = image::load_from_memory(&png_bytes)
-        .map_err(|e| format!("Failed to load image: {}", e))?;
-
-    // Check if image needs to be resized
-    let (width, height) = (img.width(), img.height());
-    let img = if width > MAX_TEXTURE_SIZE || height > MAX_TEXTURE_SIZE {
-        // Calculate new size maintaining aspect ratio
-        let scale = (MAX_TEXTURE_SIZE as f32 / width as f32)
-            .min(MAX_TEXTURE_SIZE as f32 / height as f32);
-        let new_width = (width as f32 * scale) as u32;
-        let new_height = (height as f32 * scale) as u32;
-        img.resize(new_width, new_height, image::imageops::FilterType::Nearest)
-    } else {
-        img
-    };
+/// Texture cache keys of rotation previews start with this, so they can be dropped together
+pub const ROTATION_PREVIEW_TEXTURE_PREFIX: &str = "rotation_preview/";
 
-    // Convert to RGBA
-    let rgba = img.to_rgba8();
+/// Texture cache key for the generated preview of one rotation
+pub fn rotation_preview_texture_key(angle: Angle) -> String {
+    format!("{}{}", ROTATION_PREVIEW_TEXTURE_PREFIX, angle)
+}
+
+/// Scale an image down to fit the GPU texture size limit, if needed
+fn fit_texture_size(rgba: &image::RgbaImage) -> Option<image::RgbaImage> {
+    let (width, height) = rgba.dimensions();
+    if width <= MAX_TEXTURE_SIZE && height <= MAX_TEXTURE_SIZE {
+        return None;
+    }
+    // Calculate new size maintaining aspect ratio
+    let scale =
+        (MAX_TEXTURE_SIZE as f32 / width as f32).min(MAX_TEXTURE_SIZE as f32 / height as f32);
+    let new_width = (width as f32 * scale) as u32;
+    let new_height = (height as f32 * scale) as u32;
+    Some(image::imageops::resize(
+        rgba,
+        new_width,
+        new_height,
+        image::imageops::FilterType::Nearest,
+    ))
+}
+
+/// Create an egui texture from decoded image pixels
+pub fn rgba_to_texture(ctx: &egui::Context, name: &str, rgba: &image::RgbaImage) -> egui::TextureHandle {
+    let resized = fit_texture_size(rgba);
+    let rgba = resized.as_ref().unwrap_or(rgba);
     let size = [rgba.width() as usize, rgba.height() as usize];
-    let pixels = rgba.into_raw();
 
     // Create egui ColorImage
-    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &pixels);
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw());
 
-    // Create texture
-    Ok(ctx.load_texture(
+    ctx.load_texture(
         name,
         color_image,
         egui::TextureOptions::NEAREST, // Pixel art should use nearest neighbor
-    ))
+    )
 }
 
-/// Create a yellow silhouette texture from base64 image data
-/// All pixels become yellow (255, 255, 0) while preserving alpha
-pub fn decode_base64_to_yellow_texture(
+/// Create a yellow silhouette texture from decoded image pixels
+pub fn rgba_to_yellow_texture(
     ctx: &egui::Context,
     name: &str,
-    base64_data: &str,
-) -> Result<egui::TextureHandle, String> {
-    use base64::Engine;
-
-    // Decode base64
-    let png_bytes = base64::engine::general_purpose::STANDARD
-        .decode(base64_data)
-        .map_err(|e| format!("Failed to decode base64: {}", e))?;
-
-    // Load image
-    let img = image::load_from_memory(&png_bytes)
-        .map_err(|e| format!("Failed to load image: {}", e))?;
-
-    // Check if image needs to be resized
-    let (width, height) = (img.width(), img.height());
-    let img = if width > MAX_TEXTURE_SIZE || height > MAX_TEXTURE_SIZE {
-        let scale = (MAX_TEXTURE_SIZE as f32 / width as f32)
-            .min(MAX_TEXTURE_SIZE as f32 / height as f32);
-        let new_width = (width as f32 * scale) as u32;
-        let new_height = (height as f32 * scale) as u32;
-        img.resize(new_width, new_height, image::imageops::FilterType::Nearest)
-    } else {
-        img
-    };
-
-    // Convert to RGBA and make all pixels yellow (preserving alpha)
-    let rgba = img.to_rgba8();
+    rgba: &image::RgbaImage,
+) -> egui::TextureHandle {
+    let resized = fit_texture_size(rgba);
+    let rgba = resized.as_ref().unwrap_or(rgba);
     let size = [rgba.width() as usize, rgba.height() as usize];
-    let mut pixels = rgba.into_raw();
+    let mut pixels = rgba.as_raw().clone();
 
     // Convert every pixel to yellow while preserving alpha
     for chunk in pixels.chunks_mut(4) {
@@ -96,12 +76,7 @@ pub fn decode_base64_to_yellow_texture(
     // Create egui ColorImage
     let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &pixels);
 
-    // Create texture
-    Ok(ctx.load_texture(
-        name,
-        color_image,
-        egui::TextureOptions::NEAREST,
-    ))
+    ctx.load_texture(name, color_image, egui::TextureOptions::NEAREST)
 }
 
 /// Create a small JPG thumbnail for a reference image (for fallback when file is missing)
@@ -200,6 +175,20 @@ pub fn load_reference_texture(
     ))
 }
 
+/// Load an image file as a pixel-art (nearest filtered) texture
+pub fn load_image_file_texture(
+    ctx: &egui::Context,
+    file_path: &str,
+) -> Result<egui::TextureHandle, String> {
+    let bytes = fs::read(file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let rgba = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Invalid image: {}", e))?
+        .to_rgba8();
+    let size = [rgba.width() as usize, rgba.height() as usize];
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &rgba.into_raw());
+    Ok(ctx.load_texture(file_path, color_image, egui::TextureOptions::NEAREST))
+}
+
 /// Render a frame image to a thumbnail texture for the timeline
 pub fn render_frame_thumbnail(
     ctx: &egui::Context,
@@ -27,7 +27,18 @@ fn main() {
         .init_resource::<AppState>()
         .init_resource::<WindowSizeAdjusted>()
         .add_systems(Startup, (setup, configure_fonts))
-        .add_systems(Update, (ui_system, handle_window_close, update_window_title, adjust_window_size))
+        .add_systems(
+            Update,
+            (
+                ui_system,
+                handle_window_close,
+                update_window_title,
+                adjust_window_size,
+                autosave,
+                watch_linked_art,
+                poll_export,
+            ),
+        )
         .run();
 }
 
@@ -94,6 +105,21 @@ fn handle_window_close(
     }
 }
 
+/// Periodically write unsaved changes to the recovery file
+fn autosave(mut state: ResMut<AppState>) {
+    state.autosave_if_due();
+}
+
+/// Report background exports once they finish
+fn poll_export(mut state: ResMut<AppState>) {
+    state.poll_export_job();
+}
+
+/// Re-import linked art files that changed on disk
+fn watch_linked_art(mut state: ResMut<AppState>) {
+    state.watch_linked_art();
+}
+
 fn update_window_title(state: Res<AppState>, mut windows: Query<&mut Window, With<PrimaryWindow>>) {
     if let Ok(mut window) = windows.get_single_mut() {
         let title = match (&state.project, &state.project_path) {
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Hundredths of a degree in a full turn
+const FULL_TURN: u16 = 36000;
+
+/// A rotation angle, counterclockwise from east (0° = E, 90° = N). Stored in hundredths
+/// of a degree so every direction step (22.5°, 11.25°) is exact and usable as a map key.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Angle(u16);
+
+impl Angle {
+    pub const ZERO: Angle = Angle(0);
+
+    pub const fn from_centidegrees(centidegrees: u16) -> Self {
+        Self(centidegrees % FULL_TURN)
+    }
+
+    /// Nearest angle to `degrees`, wrapped into 0..360
+    pub fn from_degrees(degrees: f32) -> Self {
+        let centidegrees = (degrees * 100.0).round() as i64;
+        Self(centidegrees.rem_euclid(FULL_TURN as i64) as u16)
+    }
+
+    /// Angle stored as whole degrees by older versions, which truncated the 22.5° steps
+    /// (22, 67, 112...). Those map back to the exact step; anything else is taken as is.
+    pub fn from_legacy_degrees(degrees: u16) -> Self {
+        (0..16)
+            .map(|i| i * 2250)
+            .find(|&centidegrees| centidegrees / 100 == degrees)
+            .map(Self)
+            .unwrap_or_else(|| Self::from_degrees(degrees as f32))
+    }
+
+    /// Parse degrees as typed or written in a file name ("90", "22.5", "11.25°")
+    pub fn parse(text: &str) -> Option<Self> {
+        let degrees: f32 = text.trim().trim_end_matches('°').parse().ok()?;
+        let in_range = degrees.is_finite() && (0.0..360.0).contains(&degrees);
+        in_range.then(|| Self::from_degrees(degrees))
+    }
+
+    /// Parse a comma or space separated list of angles ("0, 30, 150")
+    pub fn parse_list(text: &str) -> Result<Vec<Self>, String> {
+        text.split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| Self::parse(s).ok_or_else(|| format!("'{}' is not an angle below 360°", s)))
+            .collect()
+    }
+
+    pub fn centidegrees(self) -> u16 {
+        self.0
+    }
+
+    pub fn degrees(self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+
+    /// The angle reflected across the east-west axis: 45° and 315°, 22.5° and 337.5°.
+    /// A rotation drawn at one angle is the other's image flipped top to bottom.
+    pub fn mirrored(self) -> Self {
+        Self((FULL_TURN - self.0) % FULL_TURN)
+    }
+
+    /// Hundredths of a degree between two angles, going the shorter way around
+    pub fn distance(self, other: Angle) -> u16 {
+        let d = (self.0 as i32 - other.0 as i32).rem_euclid(FULL_TURN as i32) as u16;
+        d.min(FULL_TURN - d)
+    }
+}
+
+/// Degrees without trailing zeros: "90", "22.5", "11.25"
+impl fmt::Display for Angle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (whole, fraction) = (self.0 / 100, self.0 % 100);
+        if fraction == 0 {
+            write!(f, "{}", whole)
+        } else if fraction % 10 == 0 {
+            write!(f, "{}.{}", whole, fraction / 10)
+        } else {
+            write!(f, "{}.{:02}", whole, fraction)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_angles_are_exact() {
+        assert_eq!(Angle::from_degrees(22.5).to_string(), "22.5");
+        assert_eq!(Angle::from_degrees(11.25).to_string(), "11.25");
+        assert_eq!(Angle::from_degrees(-90.0).to_string(), "270");
+        assert_eq!(
+            Angle::parse("337.5°"),
+            Some(Angle::from_centidegrees(33750))
+        );
+        assert_eq!(Angle::parse("360"), None);
+        assert_eq!(Angle::parse_list("0, 30 150").map(|a| a.len()), Ok(3));
+        assert!(Angle::parse_list("0, north").is_err());
+
+        assert_eq!(
+            Angle::from_degrees(350.0).distance(Angle::from_degrees(10.0)),
+            2000
+        );
+
+        assert_eq!(Angle::from_legacy_degrees(67), Angle::from_degrees(67.5));
+        assert_eq!(Angle::from_legacy_degrees(90), Angle::from_degrees(90.0));
+        assert_eq!(Angle::from_legacy_degrees(30), Angle::from_degrees(30.0));
+    }
+}
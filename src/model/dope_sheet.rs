@@ -0,0 +1,183 @@
+use std::ops::Range;
+
+use super::{Angle, Animation, PlacedPart, Project};
+
+/// How a layer looks in one frame of the dope sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackCell {
+    pub layer_id: u64,
+    /// State name, or the animation a nested layer plays
+    pub state: String,
+    pub rotation: Angle,
+    pub visible: bool,
+}
+
+impl TrackCell {
+    fn looks_like(&self, other: &TrackCell) -> bool {
+        self.state == other.state
+            && self.rotation == other.rotation
+            && self.visible == other.visible
+    }
+}
+
+/// One row of the dope sheet: a layer followed through an animation's frames by its name
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub layer_name: String,
+    /// Part the layer draws in its first frame (None for a nested animation)
+    pub part_id: Option<u64>,
+    /// States of that part as (id, name, angles), for editing cells
+    pub states: Vec<(u64, String, Vec<Angle>)>,
+    /// One cell per frame, None where the frame has no such layer
+    pub cells: Vec<Option<TrackCell>>,
+}
+
+impl Track {
+    /// Whether the layer appears or changes state, angle or visibility at a frame
+    pub fn changes_at(&self, frame: usize) -> bool {
+        let cell = |i: usize| self.cells.get(i).and_then(|c| c.as_ref());
+        match (cell(frame), frame.checked_sub(1).and_then(cell)) {
+            (Some(cell), Some(previous)) => !cell.looks_like(previous),
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+impl Project {
+    /// The layers of an animation as dope sheet rows, in order of first appearance
+    pub fn dope_sheet(&self, animation: &Animation) -> Vec<Track> {
+        let frame_count = animation.frames.len();
+        let mut tracks: Vec<Track> = Vec::new();
+        for (index, frame) in animation.frames.iter().enumerate() {
+            for placed in &frame.placed_parts {
+                let existing = tracks
+                    .iter()
+                    .position(|t| t.layer_name == placed.layer_name);
+                let track_index = match existing {
+                    Some(i) => i,
+                    None => {
+                        let part = match placed.nested {
+                            Some(_) => None,
+                            None => self.placed_part(placed),
+                        };
+                        tracks.push(Track {
+                            layer_name: placed.layer_name.clone(),
+                            part_id: part.map(|p| p.id),
+                            states: part
+                                .map(|p| {
+                                    p.states
+                                        .iter()
+                                        .map(|s| (s.id, s.name.clone(), s.angles()))
+                                        .collect()
+                                })
+                                .unwrap_or_default(),
+                            cells: vec![None; frame_count],
+                        });
+                        tracks.len() - 1
+                    }
+                };
+                let track = &mut tracks[track_index];
+                // Only the first layer of a name counts if a frame repeats it
+                if track.cells[index].is_some() {
+                    continue;
+                }
+                let state = match placed.nested {
+                    Some(ref nested) => nested.animation_name.clone(),
+                    None => self
+                        .placed_state(placed)
+                        .map(|(_, state)| state.name.clone())
+                        .unwrap_or_else(|| "?".to_string()),
+                };
+                track.cells[index] = Some(TrackCell {
+                    layer_id: placed.id,
+                    state,
+                    rotation: placed.rotation,
+                    visible: placed.visible,
+                });
+            }
+        }
+        tracks
+    }
+}
+
+impl Animation {
+    /// Edit the layer with a name in each of a range of frames that has it. The edit
+    /// returns whether it changed the layer; returns how many layers were changed.
+    pub fn edit_track(
+        &mut self,
+        layer_name: &str,
+        frames: Range<usize>,
+        mut edit: impl FnMut(&mut PlacedPart) -> bool,
+    ) -> usize {
+        let end = frames.end.min(self.frames.len());
+        let start = frames.start.min(end);
+        let mut edited = 0;
+        for frame in &mut self.frames[start..end] {
+            let placed = frame
+                .placed_parts
+                .iter_mut()
+                .find(|p| p.layer_name == layer_name);
+            if placed.is_some_and(&mut edit) {
+                edited += 1;
+            }
+        }
+        edited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Character, Part, RotationMode, State};
+
+    #[test]
+    fn test_dope_sheet_tracks() {
+        let mut project = Project::new("Dope");
+        let mut knight = Character::new(1, "Knight");
+        let mut sword = Part::new(1, "sword");
+        let swing = sword.add_state(State::new("swing", RotationMode::Deg45));
+        knight.add_part(sword);
+        knight.add_part(Part::new(2, "shield"));
+
+        let mut attack = Animation::new("attack");
+        for _ in 0..3 {
+            attack.add_frame();
+        }
+        for (i, frame) in attack.frames.iter_mut().enumerate() {
+            let id = i as u64 * 2;
+            frame
+                .placed_parts
+                .push(PlacedPart::new(id + 1, 1, 1, 1).with_layer_name("sword"));
+            if i < 2 {
+                let shield = PlacedPart::new(id + 2, 1, 2, 1).with_layer_name("shield");
+                frame.placed_parts.push(shield);
+            }
+        }
+        // Drag the swing state from frame 2 to the end
+        let mut set_swing = |p: &mut PlacedPart| std::mem::replace(&mut p.state_id, swing) != swing;
+        assert_eq!(attack.edit_track("sword", 1..4, &mut set_swing), 3);
+        // Only layers that actually change are counted
+        assert_eq!(attack.edit_track("sword", 2..4, &mut set_swing), 0);
+        attack.edit_track("shield", 1..2, |p| std::mem::replace(&mut p.visible, false));
+        knight.add_animation(attack);
+        project.add_character(knight);
+
+        let attack = project
+            .get_character("Knight")
+            .unwrap()
+            .get_animation("attack")
+            .unwrap();
+        let tracks = project.dope_sheet(attack);
+        let names: Vec<&str> = tracks.iter().map(|t| t.layer_name.as_str()).collect();
+        assert_eq!(names, ["sword", "shield"]);
+        assert_eq!(tracks[0].states.len(), 2);
+        let cells = tracks[0].cells.iter().flatten();
+        let states: Vec<&str> = cells.map(|c| c.state.as_str()).collect();
+        assert_eq!(states, ["default", "swing", "swing", "swing"]);
+        let changes: Vec<bool> = (0..4).map(|f| tracks[0].changes_at(f)).collect();
+        assert_eq!(changes, [true, true, false, false]);
+        assert_eq!(tracks[1].cells[2], None);
+        assert!(tracks[1].changes_at(1));
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+/// Which pixels around a diagonal edge the outline fills
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutlineCorners {
+    /// Every pixel within the thickness, diagonals included
+    #[default]
+    Square,
+    /// Only pixels within a circle of the thickness, leaving diagonal corners open
+    Rounded,
+}
+
+/// Outline drawn around the outside of the whole composited character
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Outline {
+    pub enabled: bool,
+    pub color: [u8; 3],
+    /// Width in pixels
+    pub thickness: u32,
+    pub corners: OutlineCorners,
+}
+
+impl Default for Outline {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            color: [24, 20, 37],
+            thickness: 1,
+            corners: OutlineCorners::Square,
+        }
+    }
+}
+
+/// Silhouette of the character (and its outline) drawn offset beneath it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DropShadow {
+    pub enabled: bool,
+    /// Canvas pixels right and down from the character
+    pub offset: (i32, i32),
+    pub color: [u8; 3],
+    /// 0.0 (invisible) to 1.0 (solid)
+    pub opacity: f32,
+}
+
+impl Default for DropShadow {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            offset: (1, 1),
+            color: [0, 0, 0],
+            opacity: 0.4,
+        }
+    }
+}
+
+/// Post-processing applied to every frame of a character after its parts are composited,
+/// so effects stay consistent however the parts overlap
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CharacterEffects {
+    #[serde(default)]
+    pub outline: Outline,
+    #[serde(default)]
+    pub shadow: DropShadow,
+}
+
+impl CharacterEffects {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether any effect changes the rendered frame
+    pub fn is_active(&self) -> bool {
+        (self.outline.enabled && self.outline.thickness > 0)
+            || (self.shadow.enabled && self.shadow.opacity > 0.0)
+    }
+}
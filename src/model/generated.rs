@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Angle, State};
+
+/// Where a generated rotation's image came from, so it can be regenerated when the
+/// drawn rotation it was made from changes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratedRotation {
+    pub source_angle: Angle,
+    /// Image hash of the source rotation at the time of generation
+    pub source_image: String,
+}
+
+impl State {
+    /// Angles whose image was drawn (or imported) rather than generated
+    pub fn drawn_angles(&self) -> Vec<Angle> {
+        let mut angles: Vec<Angle> = self
+            .rotations
+            .values()
+            .filter(|r| r.image.is_some() && r.generated.is_none())
+            .map(|r| r.angle)
+            .collect();
+        angles.sort();
+        angles
+    }
+
+    /// (target, source) angle pairs to generate: every empty or previously generated
+    /// rotation, each from the nearest drawn one. Empty if nothing is drawn yet.
+    pub fn generation_plan(&self) -> Vec<(Angle, Angle)> {
+        let drawn = self.drawn_angles();
+        let mut plan: Vec<(Angle, Angle)> = self
+            .rotations
+            .values()
+            .filter(|r| r.image.is_none() || r.generated.is_some())
+            .filter_map(|r| {
+                let source = drawn
+                    .iter()
+                    .copied()
+                    .min_by_key(|&a| (a.distance(r.angle), a))?;
+                Some((r.angle, source))
+            })
+            .collect();
+        plan.sort();
+        plan
+    }
+
+    /// Whether a generated rotation's source was redrawn or cleared since it was generated
+    pub fn is_generated_stale(&self, angle: Angle) -> bool {
+        let Some(generated) = self
+            .rotations
+            .get(&angle)
+            .and_then(|r| r.generated.as_ref())
+        else {
+            return false;
+        };
+        self.rotations
+            .get(&generated.source_angle)
+            .and_then(|r| r.image.as_ref())
+            != Some(&generated.source_image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::RotationMode;
+
+    #[test]
+    fn test_generation_plan_uses_nearest_drawn_angle() {
+        let mut state = State::new("default", RotationMode::Deg45);
+        assert!(state.generation_plan().is_empty());
+
+        let deg = Angle::from_degrees;
+        state.rotations.get_mut(&deg(0.0)).unwrap().image = Some("east".to_string());
+        state.rotations.get_mut(&deg(180.0)).unwrap().image = Some("west".to_string());
+        let generated = state.rotations.get_mut(&deg(45.0)).unwrap();
+        generated.image = Some("made".to_string());
+        generated.generated = Some(GeneratedRotation {
+            source_angle: deg(0.0),
+            source_image: "east".to_string(),
+        });
+
+        let plan = state.generation_plan();
+        assert_eq!(plan.len(), 6);
+        assert!(plan.contains(&(deg(45.0), deg(0.0))));
+        assert!(plan.contains(&(deg(135.0), deg(180.0))));
+        assert!(plan.contains(&(deg(315.0), deg(0.0))));
+        // Ties go to the smaller angle
+        assert!(plan.contains(&(deg(90.0), deg(0.0))));
+
+        assert!(!state.is_generated_stale(deg(45.0)));
+        state.rotations.get_mut(&deg(0.0)).unwrap().image = Some("redrawn".to_string());
+        assert!(state.is_generated_stale(deg(45.0)));
+    }
+}
@@ -0,0 +1,180 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+/// Content hash of encoded image bytes (hex BLAKE3), used as the image store key
+pub fn content_hash(bytes: &[u8]) -> String {
+    blake3::hash(bytes).to_hex().to_string()
+}
+
+/// Suffix of the cache entries holding flipped copies of images
+const MIRRORED_SUFFIX: &str = "/mirrored";
+
+/// Cache key, and texture key, of an image's flipped copy
+pub fn mirrored_key(hash: &str) -> String {
+    format!("{}{}", hash, MIRRORED_SUFFIX)
+}
+
+/// Decoded RGBA pixels of stored images, filled on first use. Entries are keyed by
+/// content hash and never change, so clones of a store (export jobs, undo snapshots)
+/// share one cache; an edited or re-imported image simply gets a new entry.
+#[derive(Clone, Default)]
+struct DecodedImages {
+    images: Arc<RwLock<HashMap<String, Arc<image::RgbaImage>>>>,
+}
+
+impl DecodedImages {
+    fn get(&self, hash: &str) -> Option<Arc<image::RgbaImage>> {
+        self.images.read().ok()?.get(hash).cloned()
+    }
+
+    fn insert(&self, hash: &str, image: Arc<image::RgbaImage>) {
+        if let Ok(mut images) = self.images.write() {
+            images.insert(hash.to_string(), image);
+        }
+    }
+
+    fn retain(&self, keep: impl Fn(&str) -> bool) {
+        if let Ok(mut images) = self.images.write() {
+            images.retain(|hash, _| keep(hash));
+        }
+    }
+}
+
+impl std::fmt::Debug for DecodedImages {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self.images.read().map(|images| images.len()).unwrap_or(0);
+        write!(f, "DecodedImages({} cached)", count)
+    }
+}
+
+/// Project-level store of PNG images keyed by content hash.
+/// Rotations reference entries by hash, so identical images are stored (and decoded) once.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ImageStore {
+    images: HashMap<String, String>, // hash -> base64-encoded PNG
+    #[serde(skip)]
+    decoded: DecodedImages, // Shared by canvas hit testing, thumbnails and export
+}
+
+impl ImageStore {
+    /// Add PNG bytes to the store, returning their hash (existing entries are reused)
+    pub fn insert_png(&mut self, png_bytes: &[u8]) -> String {
+        let hash = content_hash(png_bytes);
+        self.images
+            .entry(hash.clone())
+            .or_insert_with(|| base64::engine::general_purpose::STANDARD.encode(png_bytes));
+        hash
+    }
+
+    /// Add a base64-encoded PNG to the store, returning its hash
+    pub fn insert_base64(&mut self, base64_data: &str) -> Result<String, String> {
+        let png_bytes = base64::engine::general_purpose::STANDARD
+            .decode(base64_data)
+            .map_err(|e| format!("Failed to decode base64: {}", e))?;
+        let hash = content_hash(&png_bytes);
+        self.images
+            .entry(hash.clone())
+            .or_insert_with(|| base64_data.to_string());
+        Ok(hash)
+    }
+
+    /// Get the base64-encoded PNG for a hash
+    pub fn get(&self, hash: &str) -> Option<&str> {
+        self.images.get(hash).map(String::as_str)
+    }
+
+    /// Decoded pixels of a stored image, decoding it once and caching the result
+    pub fn decoded(&self, hash: &str) -> Result<Arc<image::RgbaImage>, String> {
+        // The cache is shared with clones, so only serve images this store holds
+        let base64_data = self
+            .get(hash)
+            .ok_or_else(|| format!("Image {} is not in the project", hash))?;
+        if let Some(image) = self.decoded.get(hash) {
+            return Ok(image);
+        }
+        let png_bytes = base64::engine::general_purpose::STANDARD
+            .decode(base64_data)
+            .map_err(|e| format!("Base64 decode error: {}", e))?;
+        let image = Arc::new(
+            image::load_from_memory(&png_bytes)
+                .map_err(|e| format!("Image load error: {}", e))?
+                .to_rgba8(),
+        );
+        self.decoded.insert(hash, image.clone());
+        Ok(image)
+    }
+
+    /// Decoded pixels of a stored image flipped top to bottom, for mirrored rotations.
+    /// Cached next to the unflipped image.
+    pub fn decoded_mirrored(&self, hash: &str) -> Result<Arc<image::RgbaImage>, String> {
+        let key = mirrored_key(hash);
+        if self.contains(hash) {
+            if let Some(image) = self.decoded.get(&key) {
+                return Ok(image);
+            }
+        }
+        let image = Arc::new(image::imageops::flip_vertical(self.decoded(hash)?.as_ref()));
+        self.decoded.insert(&key, image.clone());
+        Ok(image)
+    }
+
+    pub fn contains(&self, hash: &str) -> bool {
+        self.images.contains_key(hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Remove every image. Decoded pixels stay cached for clones still holding them.
+    pub fn clear(&mut self) {
+        self.images.clear();
+    }
+
+    /// Drop every entry whose hash is not in `used`, returning how many were removed
+    pub fn retain_used(&mut self, used: &HashSet<String>) -> usize {
+        let before = self.images.len();
+        self.images.retain(|hash, _| used.contains(hash));
+        self.decoded
+            .retain(|key| used.contains(key.strip_suffix(MIRRORED_SUFFIX).unwrap_or(key)));
+        before - self.images.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::imaging::encode_png;
+
+    fn png_base64(width: u32, height: u32) -> String {
+        let bytes = encode_png(&image::RgbaImage::new(width, height)).unwrap();
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    }
+
+    #[test]
+    fn test_decoded_images_are_cached_until_removed() {
+        let mut store = ImageStore::default();
+        let hash = store.insert_base64(&png_base64(3, 2)).unwrap();
+
+        let first = store.decoded(&hash).unwrap();
+        assert_eq!(first.dimensions(), (3, 2));
+        assert!(Arc::ptr_eq(&first, &store.decoded(&hash).unwrap()));
+
+        // Clones share decoded pixels but only serve their own images
+        let mut copy = store.clone();
+        assert!(Arc::ptr_eq(&first, &copy.decoded(&hash).unwrap()));
+        copy.clear();
+        assert!(copy.decoded(&hash).is_err());
+        assert!(Arc::ptr_eq(&first, &store.decoded(&hash).unwrap()));
+
+        store.retain_used(&HashSet::new());
+        assert!(store.decoded(&hash).is_err());
+    }
+}
@@ -0,0 +1,629 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{Angle, Animation, Part, Project};
+
+/// Schema version written by this build. Bump it and append a step to `MIGRATIONS`
+/// whenever the saved format changes.
+pub const CURRENT_SCHEMA_VERSION: u32 = 12;
+
+/// A single upgrade step from `to - 1` to `to`. Returns a description of each change made.
+struct Migration {
+    to: u32,
+    apply: fn(&mut Project) -> Vec<String>,
+}
+
+/// Ordered migration pipeline
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        to: 2,
+        apply: move_animations_to_characters,
+    },
+    Migration {
+        to: 3,
+        apply: move_canvas_size_to_characters,
+    },
+    Migration {
+        to: 4,
+        apply: assign_character_ids,
+    },
+    Migration {
+        to: 5,
+        apply: move_images_to_store,
+    },
+    Migration {
+        to: 6,
+        apply: add_part_library,
+    },
+    Migration {
+        to: 7,
+        apply: add_outfits,
+    },
+    Migration {
+        to: 8,
+        apply: add_nested_animations,
+    },
+    Migration {
+        to: 9,
+        apply: add_generated_rotations,
+    },
+    Migration {
+        to: 10,
+        apply: use_exact_angles,
+    },
+    Migration {
+        to: 11,
+        apply: add_character_effects,
+    },
+    Migration {
+        to: 12,
+        apply: assign_part_ids,
+    },
+];
+
+/// What was changed while upgrading a project to the current schema
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub changes: Vec<String>,
+}
+
+/// Schema version of a freshly parsed project. Files saved before numbered
+/// schemas only carry the free-form `version` string ("1.0" or "2.0").
+fn detected_version(project: &Project) -> u32 {
+    if project.schema_version > 0 {
+        project.schema_version
+    } else if project.version.starts_with('1') || !project.animations.is_empty() {
+        1
+    } else {
+        2
+    }
+}
+
+/// Run every migration newer than the project's schema version, in order
+pub fn migrate(project: &mut Project) -> Result<MigrationReport, String> {
+    let from_version = detected_version(project);
+    if from_version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "Project schema version {} is newer than this version of the app supports ({})",
+            from_version, CURRENT_SCHEMA_VERSION
+        ));
+    }
+
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.to > from_version) {
+        changes.extend((migration.apply)(project));
+    }
+    // Layers the v12 migration couldn't link keep their names and are retried on every load
+    if from_version >= 12 {
+        let (linked, _) = link_legacy_names(project);
+        if linked > 0 {
+            changes.push(format!("Linked {} layer(s) to parts by ID", linked));
+        }
+    }
+    raise_id_counters(project);
+    project.schema_version = CURRENT_SCHEMA_VERSION;
+
+    Ok(MigrationReport {
+        from_version,
+        to_version: CURRENT_SCHEMA_VERSION,
+        changes,
+    })
+}
+
+/// v1 -> v2: animations moved from the project into the character they animate
+fn move_animations_to_characters(project: &mut Project) -> Vec<String> {
+    let mut changes = Vec::new();
+    if project.animations.is_empty() {
+        return changes;
+    }
+
+    for anim in std::mem::take(&mut project.animations) {
+        // Find which characters are used in this animation
+        let used_chars: HashSet<String> = anim
+            .frames
+            .iter()
+            .flat_map(|f| f.placed_parts.iter())
+            .map(|p| p.character_name.clone())
+            .collect();
+
+        // Animations using several characters are assigned to the first one
+        let multi_char = used_chars.len() > 1;
+        let Some(char_name) = used_chars.into_iter().next() else {
+            changes.push(format!(
+                "Dropped animation \"{}\" (no characters used)",
+                anim.name
+            ));
+            continue;
+        };
+        if let Some(character) = project.characters.iter_mut().find(|c| c.name == char_name) {
+            let mut anim = anim;
+            if multi_char {
+                anim.name = format!("{} (multi-char)", anim.name);
+            }
+            changes.push(format!(
+                "Moved animation \"{}\" to {}",
+                anim.name, char_name
+            ));
+            character.animations.push(anim);
+        }
+    }
+
+    // Ensure all characters have at least one animation
+    for character in &mut project.characters {
+        if character.animations.is_empty() {
+            character
+                .animations
+                .push(Animation::new("Untitled Animation"));
+        }
+    }
+
+    project.version = "2.0".to_string();
+    changes
+}
+
+/// v2 -> v3: canvas size moved from the project to each character
+fn move_canvas_size_to_characters(project: &mut Project) -> Vec<String> {
+    let mut changes = Vec::new();
+    let project_canvas = project.canvas_size;
+    for character in &mut project.characters {
+        // Characters with the default canvas size inherit the project's canvas size
+        if character.canvas_size == (64, 64) && project_canvas != (64, 64) {
+            character.canvas_size = project_canvas;
+            changes.push(format!(
+                "Set canvas size of {} to {}x{}",
+                character.name, project_canvas.0, project_canvas.1
+            ));
+        }
+    }
+    changes
+}
+
+/// v3 -> v4: characters get stable IDs and placed parts reference them by ID
+fn assign_character_ids(project: &mut Project) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    let mut next_id = project.characters.iter().map(|c| c.id).max().unwrap_or(0) + 1;
+    let mut assigned = 0;
+    for character in &mut project.characters {
+        if character.id == 0 {
+            character.id = next_id;
+            next_id += 1;
+            assigned += 1;
+        }
+    }
+    if assigned > 0 {
+        changes.push(format!("Assigned IDs to {} character(s)", assigned));
+    }
+
+    let name_to_id: HashMap<String, u64> = project
+        .characters
+        .iter()
+        .map(|c| (c.name.clone(), c.id))
+        .collect();
+
+    let mut linked = 0;
+    for character in &mut project.characters {
+        // Placed parts without a character name belong to the animation's character
+        let parent_char_id = character.id;
+        for animation in &mut character.animations {
+            for frame in &mut animation.frames {
+                for placed_part in &mut frame.placed_parts {
+                    if placed_part.character_id != 0 {
+                        continue;
+                    }
+                    if placed_part.character_name.is_empty() {
+                        placed_part.character_id = parent_char_id;
+                        linked += 1;
+                    } else if let Some(&id) = name_to_id.get(&placed_part.character_name) {
+                        placed_part.character_id = id;
+                        linked += 1;
+                    }
+                }
+            }
+        }
+    }
+    if linked > 0 {
+        changes.push(format!(
+            "Linked {} placed part(s) to characters by ID",
+            linked
+        ));
+    }
+
+    changes
+}
+
+/// v4 -> v5: embedded base64 images moved into the content-addressed image store
+fn move_images_to_store(project: &mut Project) -> Vec<String> {
+    let mut changes = Vec::new();
+    let mut moved = 0;
+    for character in &mut project.characters {
+        for part in &mut character.parts {
+            for state in &mut part.states {
+                for rotation in state.rotations.values_mut() {
+                    let Some(ref base64_data) = rotation.image_data else {
+                        continue;
+                    };
+                    match project.images.insert_base64(base64_data) {
+                        Ok(hash) => {
+                            rotation.image = Some(hash);
+                            rotation.image_data = None;
+                            moved += 1;
+                        }
+                        Err(e) => changes.push(format!(
+                            "Could not read the image of {} / {} / {} at {}°: {}",
+                            character.name, part.name, state.name, rotation.angle, e
+                        )),
+                    }
+                }
+            }
+        }
+    }
+
+    if moved > 0 {
+        changes.insert(
+            0,
+            format!(
+                "Moved {} embedded image(s) into {} shared image(s)",
+                moved,
+                project.images.len()
+            ),
+        );
+    }
+    changes
+}
+
+/// v5 -> v6: shared part library added. Older files have nothing to convert; the bump
+/// stops older builds from opening (and silently dropping) library parts.
+fn add_part_library(_project: &mut Project) -> Vec<String> {
+    Vec::new()
+}
+
+/// v6 -> v7: characters gained outfits. Nothing to convert, see `add_part_library`.
+fn add_outfits(_project: &mut Project) -> Vec<String> {
+    Vec::new()
+}
+
+/// v7 -> v8: layers can play nested animations. Nothing to convert, see `add_part_library`.
+fn add_nested_animations(_project: &mut Project) -> Vec<String> {
+    Vec::new()
+}
+
+/// v8 -> v9: rotations can be generated. Nothing to convert, see `add_part_library`.
+fn add_generated_rotations(_project: &mut Project) -> Vec<String> {
+    Vec::new()
+}
+
+/// Angles stored as whole degrees (with 22.5° steps truncated to 22, 67...) are
+/// converted to exact hundredths of a degree
+fn exact_angle(angle: Angle) -> Angle {
+    Angle::from_legacy_degrees(angle.centidegrees())
+}
+
+/// v9 -> v10: angles changed from whole degrees to exact hundredths of a degree
+fn use_exact_angles(project: &mut Project) -> Vec<String> {
+    let mut fixed = 0;
+    let mut convert_part = |part: &mut Part| {
+        for state in &mut part.states {
+            state.rotations = std::mem::take(&mut state.rotations)
+                .into_values()
+                .map(|mut rotation| {
+                    rotation.angle = exact_angle(rotation.angle);
+                    if rotation.angle.centidegrees() % 100 != 0 {
+                        fixed += 1;
+                    }
+                    if let Some(generated) = &mut rotation.generated {
+                        generated.source_angle = exact_angle(generated.source_angle);
+                    }
+                    (rotation.angle, rotation)
+                })
+                .collect();
+        }
+    };
+    for character in &mut project.characters {
+        character.parts.iter_mut().for_each(&mut convert_part);
+    }
+    project.part_library.iter_mut().for_each(&mut convert_part);
+
+    for character in &mut project.characters {
+        for animation in &mut character.animations {
+            for frame in &mut animation.frames {
+                for placed in &mut frame.placed_parts {
+                    placed.rotation = exact_angle(placed.rotation);
+                }
+            }
+        }
+    }
+
+    if fixed > 0 {
+        vec![format!(
+            "Corrected {} rotation(s) saved at rounded-down 22.5° angles",
+            fixed
+        )]
+    } else {
+        Vec::new()
+    }
+}
+
+/// v10 -> v11: characters gained outline and shadow effects. Nothing to convert, see
+/// `add_part_library`.
+fn add_character_effects(_project: &mut Project) -> Vec<String> {
+    Vec::new()
+}
+
+/// v11 -> v12: parts and states get stable IDs and placements reference them by ID
+/// instead of by name
+fn assign_part_ids(project: &mut Project) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    let mut next_id = project.new_part_id();
+    let (mut parts, mut states) = (0, 0);
+    let mut assign = |part: &mut Part| {
+        if part.id == 0 {
+            part.id = next_id;
+            next_id += 1;
+            parts += 1;
+        }
+        let next_state_id = part.max_state_id() + 1;
+        for (state, id) in part
+            .states
+            .iter_mut()
+            .filter(|s| s.id == 0)
+            .zip(next_state_id..)
+        {
+            state.id = id;
+            states += 1;
+        }
+    };
+    for character in &mut project.characters {
+        character.parts.iter_mut().for_each(&mut assign);
+    }
+    project.part_library.iter_mut().for_each(&mut assign);
+    if parts + states > 0 {
+        changes.push(format!(
+            "Gave {} part(s) and {} state(s) stable IDs",
+            parts, states
+        ));
+    }
+
+    let (linked, unresolved) = link_legacy_names(project);
+    if linked > 0 {
+        changes.push(format!("Linked {} layer(s) to parts by ID", linked));
+    }
+    if unresolved > 0 {
+        changes.push(format!(
+            "{} layer(s) use a part or state that no longer exists (see Validate Project)",
+            unresolved
+        ));
+    }
+
+    changes
+}
+
+/// Move the part and state id counters past every id in use. Files saved before the
+/// counters existed load with them at 0; counters already ahead are left alone.
+fn raise_id_counters(project: &mut Project) {
+    project.next_part_uid = project.next_part_uid.max(project.max_part_id() + 1);
+    let parts = project
+        .characters
+        .iter_mut()
+        .flat_map(|c| c.parts.iter_mut())
+        .chain(project.part_library.iter_mut());
+    for part in parts {
+        part.next_state_id = part.next_state_id.max(part.max_state_id() + 1);
+    }
+}
+
+/// Point layers still carrying pre-v12 part and state names at the ids of those parts,
+/// dropping the names once both resolve. Returns how many layers were linked and how
+/// many still aren't.
+fn link_legacy_names(project: &mut Project) -> (usize, usize) {
+    // (character ID, part name) -> part ID and its state IDs by name
+    let mut part_ids: HashMap<(u64, String), (u64, HashMap<String, u64>)> = HashMap::new();
+    for character in &project.characters {
+        for part in project.character_parts(character) {
+            let state_ids = part.states.iter().map(|s| (s.name.clone(), s.id)).collect();
+            part_ids.insert((character.id, part.name.clone()), (part.id, state_ids));
+        }
+    }
+
+    let (mut linked, mut unresolved) = (0, 0);
+    for character in &mut project.characters {
+        for animation in &mut character.animations {
+            for frame in &mut animation.frames {
+                for placed in &mut frame.placed_parts {
+                    if placed.nested.is_some() || placed.legacy_part_name.is_empty() {
+                        continue;
+                    }
+                    if placed.layer_name.is_empty() {
+                        placed.layer_name = placed.legacy_part_name.clone();
+                    }
+                    let key = (placed.character_id, placed.legacy_part_name.clone());
+                    let Some((part_id, state_ids)) = part_ids.get(&key) else {
+                        unresolved += 1;
+                        continue;
+                    };
+                    placed.part_id = *part_id;
+                    match state_ids.get(&placed.legacy_state_name) {
+                        Some(&state_id) => {
+                            placed.state_id = state_id;
+                            placed.legacy_part_name.clear();
+                            placed.legacy_state_name.clear();
+                            linked += 1;
+                        }
+                        None => unresolved += 1,
+                    }
+                }
+            }
+        }
+    }
+    (linked, unresolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn load_fixture(name: &str) -> Project {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name);
+        let json = std::fs::read_to_string(&path).unwrap();
+        Project::from_json(&json).unwrap()
+    }
+
+    #[test]
+    fn test_migration_steps_are_ordered() {
+        let versions: Vec<u32> = MIGRATIONS.iter().map(|m| m.to).collect();
+        assert!(versions.windows(2).all(|w| w[1] == w[0] + 1));
+        assert_eq!(versions.last(), Some(&CURRENT_SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_v1_fixture() {
+        let project = load_fixture("v1_project_animations.pss");
+        let report = project.migration_report.as_ref().unwrap();
+        assert_eq!(report.from_version, 1);
+        assert!(project.was_migrated());
+        assert!(project.animations.is_empty());
+
+        let knight = project.get_character("Knight").unwrap();
+        assert_eq!(knight.canvas_size, (32, 48));
+        assert!(knight.animations.iter().any(|a| a.name == "Walk"));
+        let placed = &knight.animations[0].frames[0].placed_parts[0];
+        assert_eq!(placed.character_id, knight.id);
+
+        // Both characters had no ID and get distinct ones
+        let slime = project.get_character("Slime").unwrap();
+        assert_ne!(knight.id, 0);
+        assert_ne!(slime.id, 0);
+        assert_ne!(knight.id, slime.id);
+        assert_eq!(slime.animations.len(), 1);
+    }
+
+    #[test]
+    fn test_v2_fixture() {
+        let project = load_fixture("v2_embedded_images.pss");
+        let report = project.migration_report.as_ref().unwrap();
+        assert_eq!(report.from_version, 2);
+        assert_eq!(project.images.len(), 1);
+
+        let hero = project.get_character("Hero").unwrap();
+        assert_eq!(hero.canvas_size, (128, 128));
+        let state = &hero.parts[0].states[0];
+        let (east, west) = (Angle::ZERO, Angle::from_degrees(180.0));
+        assert!(state.rotations[&east].image.is_some());
+        assert_eq!(state.rotations[&east].image, state.rotations[&west].image);
+        let placed = &hero.animations[0].frames[0].placed_parts[0];
+        assert_eq!(placed.character_id, hero.id);
+
+        // Unreadable embedded art is reported rather than silently dropped
+        let mut project = project.clone();
+        let rotation = project.characters[0].parts[0].states[0]
+            .rotations
+            .get_mut(&east)
+            .unwrap();
+        rotation.image_data = Some("not base64!".to_string());
+        let changes = move_images_to_store(&mut project);
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].starts_with("Could not read the image of Hero / "));
+    }
+
+    #[test]
+    fn test_v9_whole_degree_angles() {
+        let project = load_fixture("v9_whole_degree_angles.pss");
+        let report = project.migration_report.as_ref().unwrap();
+        assert_eq!(
+            report
+                .changes
+                .iter()
+                .filter(|c| c.contains("22.5°"))
+                .count(),
+            1
+        );
+
+        let state = &project.get_part("Hero", "cape").unwrap().states[0];
+        let angles: Vec<String> = state.angles().iter().map(|a| a.to_string()).collect();
+        assert_eq!(angles, ["0", "22.5", "67.5", "90"]);
+        let generated = state.rotations[&Angle::from_degrees(22.5)]
+            .generated
+            .as_ref();
+        assert_eq!(generated.unwrap().source_angle, Angle::from_degrees(67.5));
+        let hero = project.get_character("Hero").unwrap();
+        let placed = &hero.animations[0].frames[0].placed_parts[0];
+        assert_eq!(placed.rotation, Angle::from_degrees(337.5));
+    }
+
+    #[test]
+    fn test_v11_part_names() {
+        let project = load_fixture("v11_part_names.pss");
+        let report = project.migration_report.as_ref().unwrap();
+        assert_eq!(report.from_version, 11);
+
+        let hero = project.get_character("Hero").unwrap();
+        let placed = &hero.animations[0].frames[0].placed_parts;
+        let names = |i: usize| {
+            project
+                .placed_state(&placed[i])
+                .map(|(part, state)| (part.name.as_str(), state.name.as_str()))
+        };
+        assert_eq!(names(0), Some(("head", "default")));
+        // Linked library parts resolve too
+        assert_eq!(names(1), Some(("sword", "swing")));
+        assert_eq!(placed[1].layer_name, "sword");
+        assert_eq!(names(2), None);
+        assert!(placed[3].nested.is_some());
+        assert_ne!(
+            project.get_part("Hero", "head").unwrap().id,
+            placed[1].part_id
+        );
+
+        // The missing tail keeps its names through a save and links once it exists again
+        let mut project = Project::from_json(&project.to_json().unwrap()).unwrap();
+        let part_id = project.new_part_id();
+        let hero = project.get_character_mut("Hero").unwrap();
+        hero.add_part(Part::new(part_id, "tail"));
+        let project = Project::from_json(&project.to_json().unwrap()).unwrap();
+        let report = project.migration_report.as_ref().unwrap();
+        assert_eq!(report.changes, ["Linked 1 layer(s) to parts by ID"]);
+        let tail = &project.get_character("Hero").unwrap().animations[0].frames[0].placed_parts[2];
+        let (part, state) = project.placed_state(tail).unwrap();
+        assert_eq!(
+            (part.name.as_str(), state.name.as_str()),
+            ("tail", "default")
+        );
+        assert!(tail.legacy_part_name.is_empty());
+    }
+
+    #[test]
+    fn test_current_fixture_is_unchanged() {
+        let project = load_fixture("v12_current.pss");
+        let report = project.migration_report.as_ref().unwrap();
+        assert_eq!(report.from_version, CURRENT_SCHEMA_VERSION);
+        assert!(report.changes.is_empty());
+        assert!(!project.was_migrated());
+        assert_eq!(
+            project.get_part("Hero", "sword").map(|p| p.default_z),
+            Some(1)
+        );
+        let hero = project.get_character("Hero").unwrap();
+        assert!(hero.get_outfit("armored").is_some());
+        assert!(hero.effects.outline.enabled);
+        assert_eq!(hero.effects.shadow.offset, (1, 2));
+        let placed = &hero.animations[0].frames[0].placed_parts;
+        assert_eq!(
+            placed[1].nested.as_ref().map(|n| n.animation_name.as_str()),
+            Some("Blink")
+        );
+    }
+
+    #[test]
+    fn test_newer_schema_is_rejected() {
+        let json = format!(
+            r#"{{ "version": "2.0", "schema_version": {}, "name": "Future", "characters": [] }}"#,
+            CURRENT_SCHEMA_VERSION + 1
+        );
+        assert!(Project::from_json(&json).is_err());
+    }
+}
@@ -1,64 +1,140 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-
-/// Rotation mode determines the angle increments for pre-drawn rotations
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+mod angle;
+mod dope_sheet;
+mod effects;
+mod generated;
+mod image_store;
+mod migration;
+mod nested;
+mod outfit;
+mod part_library;
+mod references;
+mod retarget;
+mod timeline;
+mod usages;
+mod validation;
+
+pub use angle::Angle;
+pub use dope_sheet::Track;
+pub use effects::{CharacterEffects, OutlineCorners};
+pub use generated::GeneratedRotation;
+pub use image_store::{content_hash, mirrored_key, ImageStore};
+pub use migration::{MigrationReport, CURRENT_SCHEMA_VERSION};
+pub use nested::NestedAnimation;
+pub use outfit::{Outfit, OutfitSwap};
+pub use retarget::PartMap;
+pub use usages::{ReplaceScope, Usage, UsageQuery};
+pub use validation::{Issue, IssueLocation};
+
+/// Rotation mode determines which angles a state has pre-drawn rotations for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum RotationMode {
+    Deg90, // 4 rotations: 0, 90, 180, 270
     #[default]
-    Deg45,   // 8 rotations: 0, 45, 90, 135, 180, 225, 270, 315
-    Deg22_5, // 16 rotations: 0, 22.5, 45, ... 337.5
+    Deg45,    // 8 rotations: 0, 45, 90, 135, 180, 225, 270, 315
+    Deg22_5,  // 16 rotations: 0, 22.5, 45, ... 337.5
+    Deg11_25, // 32 rotations: 0, 11.25, 22.5, ... 348.75
+    /// Any angles, chosen per state
+    Custom(Vec<Angle>),
 }
 
 impl RotationMode {
-    pub fn angles(&self) -> Vec<u16> {
+    /// Evenly spaced modes, from fewest to most directions
+    pub const PRESETS: [RotationMode; 4] = [
+        RotationMode::Deg90,
+        RotationMode::Deg45,
+        RotationMode::Deg22_5,
+        RotationMode::Deg11_25,
+    ];
+
+    /// Custom mode with the angles sorted and duplicates removed
+    pub fn custom(mut angles: Vec<Angle>) -> Self {
+        angles.sort();
+        angles.dedup();
+        RotationMode::Custom(angles)
+    }
+
+    pub fn angles(&self) -> Vec<Angle> {
+        if let RotationMode::Custom(angles) = self {
+            return angles.clone();
+        }
+        let step = self.step().map_or(36000, |s| s.centidegrees());
+        (0..36000 / step)
+            .map(|i| Angle::from_centidegrees(i * step))
+            .collect()
+    }
+
+    /// Spacing of an evenly spaced mode
+    pub fn step(&self) -> Option<Angle> {
         match self {
-            RotationMode::Deg45 => vec![0, 45, 90, 135, 180, 225, 270, 315],
-            RotationMode::Deg22_5 => (0..16).map(|i| (i * 225) / 10).collect(), // 0, 22, 45, 67, 90...
+            RotationMode::Deg90 => Some(Angle::from_centidegrees(9000)),
+            RotationMode::Deg45 => Some(Angle::from_centidegrees(4500)),
+            RotationMode::Deg22_5 => Some(Angle::from_centidegrees(2250)),
+            RotationMode::Deg11_25 => Some(Angle::from_centidegrees(1125)),
+            RotationMode::Custom(_) => None,
         }
     }
 
-    pub fn step(&self) -> u16 {
+    pub fn label(&self) -> String {
         match self {
-            RotationMode::Deg45 => 45,
-            RotationMode::Deg22_5 => 22, // Actually 22.5, but we use integers
+            RotationMode::Custom(angles) => format!("Custom ({} rotations)", angles.len()),
+            _ => format!("{} rotations", self.angles().len()),
         }
     }
 
-    /// Get the mirror angle for automatic rotation generation
-    /// e.g., 45° mirrors to 315°, 90° mirrors to 270°
-    pub fn mirror_angle(&self, angle: u16) -> u16 {
-        if angle == 0 || angle == 180 {
-            angle // These don't need mirroring
-        } else {
-            360 - angle
+    /// Angles in the order they appear on a sheet, starting at `start` and going
+    /// counterclockwise (increasing angle) or clockwise
+    pub fn angles_from(&self, start: Angle, clockwise: bool) -> Vec<Angle> {
+        let mut angles = self.angles();
+        let start_index = angles.iter().position(|&a| a == start).unwrap_or(0);
+        angles.rotate_left(start_index);
+        if clockwise && !angles.is_empty() {
+            angles[1..].reverse();
         }
+        angles
     }
 }
 
 /// A single rotation variant of a state (the actual image data)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Rotation {
-    pub angle: u16,
-    /// Base64-encoded PNG data, or None if this rotation should be auto-generated via mirroring
+    pub angle: Angle,
+    /// Hash of the image in the project's image store, or None if this rotation should be
+    /// auto-generated via mirroring
+    #[serde(default, alias = "image_file")]
+    pub image: Option<String>,
+    /// Legacy embedded base64 PNG data (moved into the image store on load)
+    #[serde(default, skip_serializing)]
     pub image_data: Option<String>,
-    #[serde(skip)]
-    pub is_mirrored: bool, // Runtime flag: true if this was generated from mirroring
+    /// External file the image was imported from, re-imported when it changes on disk
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_path: Option<String>,
+    /// Set when the image was generated by rotating another rotation rather than drawn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated: Option<GeneratedRotation>,
 }
 
 impl Rotation {
-    pub fn new(angle: u16) -> Self {
+    pub fn new(angle: Angle) -> Self {
         Self {
             angle,
+            image: None,
             image_data: None,
-            is_mirrored: false,
+            source_path: None,
+            generated: None,
         }
     }
 
-    pub fn with_image(angle: u16, image_data: String) -> Self {
+    pub fn with_image(angle: Angle, image_hash: String) -> Self {
         Self {
             angle,
-            image_data: Some(image_data),
-            is_mirrored: false,
+            image: Some(image_hash),
+            image_data: None,
+            source_path: None,
+            generated: None,
         }
     }
 }
@@ -66,10 +142,13 @@ impl Rotation {
 /// A state represents a specific visual variant of a part (e.g., "straight", "turned", "flap1")
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct State {
+    /// Stable identifier within the part, referenced by placements (0 until added to a part)
+    #[serde(default)]
+    pub id: u64,
     pub name: String,
     pub rotation_mode: RotationMode,
     /// Map of angle -> Rotation data
-    pub rotations: HashMap<u16, Rotation>,
+    pub rotations: HashMap<Angle, Rotation>,
 }
 
 impl State {
@@ -83,40 +162,86 @@ impl State {
         }
 
         Self {
+            id: 0,
             name,
             rotation_mode,
             rotations,
         }
     }
 
-    /// Get a rotation, auto-generating via mirror if needed
-    pub fn get_rotation(&self, angle: u16) -> Option<&Rotation> {
-        self.rotations.get(&angle)
+    /// The rotation to draw at an angle. An angle without an image falls back to the
+    /// mirrored angle's image, flagged true so it is drawn flipped (see `Angle::mirrored`).
+    pub fn get_rotation(&self, angle: Angle) -> Option<(&Rotation, bool)> {
+        let rotation = self.rotations.get(&angle);
+        if rotation.is_some_and(|r| r.image.is_some()) {
+            return rotation.map(|r| (r, false));
+        }
+        let mirror = self
+            .rotations
+            .get(&angle.mirrored())
+            .filter(|r| r.image.is_some());
+        match mirror {
+            Some(mirror) => Some((mirror, true)),
+            None => rotation.map(|r| (r, false)),
+        }
+    }
+
+    /// Switch to another rotation mode, adding empty slots for its angles. Empty slots
+    /// outside the mode are removed; drawn ones are kept so no art is lost. Returns how
+    /// many drawn rotations were kept outside the new mode.
+    pub fn set_rotation_mode(&mut self, mode: RotationMode) -> usize {
+        let angles = mode.angles();
+        self.rotations
+            .retain(|angle, r| angles.contains(angle) || r.image.is_some());
+        for &angle in &angles {
+            self.rotations
+                .entry(angle)
+                .or_insert_with(|| Rotation::new(angle));
+        }
+        self.rotation_mode = mode;
+        self.rotations.len() - angles.len()
+    }
+
+    /// Angles of the state's rotations, in increasing order
+    pub fn angles(&self) -> Vec<Angle> {
+        let mut angles: Vec<Angle> = self.rotations.keys().copied().collect();
+        angles.sort();
+        angles
     }
 
     /// Check if this state has any actual image data
     pub fn has_images(&self) -> bool {
-        self.rotations.values().any(|r| r.image_data.is_some())
+        self.rotations.values().any(|r| r.image.is_some())
     }
 }
 
 /// A part of a character (e.g., "head", "torso", "cape")
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Part {
+    /// Unique identifier within the project (assigned on creation, never changes);
+    /// placements reference parts by it, so the name is only for display
+    #[serde(default)]
+    pub id: u64,
     pub name: String,
     pub states: Vec<State>,
     pub default_z: i32,
+    /// Id the next new state gets. Only goes up, so a deleted state's id is never reused.
+    #[serde(default)]
+    pub next_state_id: u64,
 }
 
 impl Part {
-    pub fn new(name: impl Into<String>) -> Self {
+    pub fn new(id: u64, name: impl Into<String>) -> Self {
         let name = name.into();
         // Create with a default state
-        let default_state = State::new("default", RotationMode::Deg45);
+        let mut default_state = State::new("default", RotationMode::Deg45);
+        default_state.id = 1;
         Self {
+            id,
             name,
             states: vec![default_state],
             default_z: 0,
+            next_state_id: 2,
         }
     }
 
@@ -128,8 +253,22 @@ impl Part {
         self.states.iter_mut().find(|s| s.name == name)
     }
 
-    pub fn add_state(&mut self, state: State) {
+    pub fn get_state_by_id(&self, id: u64) -> Option<&State> {
+        self.states.iter().find(|s| s.id == id)
+    }
+
+    /// Add a state, giving it a new id that no state of this part has had. Returns the id.
+    pub fn add_state(&mut self, mut state: State) -> u64 {
+        let id = self.next_state_id.max(self.max_state_id() + 1);
+        self.next_state_id = id + 1;
+        state.id = id;
         self.states.push(state);
+        id
+    }
+
+    /// Highest id among this part's states, 0 if it has none
+    pub fn max_state_id(&self) -> u64 {
+        self.states.iter().map(|s| s.id).max().unwrap_or(0)
     }
 }
 
@@ -141,10 +280,22 @@ pub struct Character {
     pub id: u64,
     pub name: String,
     pub parts: Vec<Part>,
+    /// Names of parts from the project's shared part library that this character uses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub library_parts: Vec<String>,
     #[serde(default)]
     pub animations: Vec<Animation>,
     #[serde(default = "default_canvas_size")]
     pub canvas_size: (u32, u32),
+    /// Variants that swap part states or parts when previewing and exporting
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outfits: Vec<Outfit>,
+    /// Outfits to export; every animation is exported once per outfit listed here
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub export_outfits: Vec<String>,
+    /// Outline and shadow applied to every composited frame
+    #[serde(default, skip_serializing_if = "CharacterEffects::is_default")]
+    pub effects: CharacterEffects,
 }
 
 fn default_canvas_size() -> (u32, u32) {
@@ -157,8 +308,12 @@ impl Character {
             id,
             name: name.into(),
             parts: Vec::new(),
+            library_parts: Vec::new(),
             animations: vec![Animation::new("Untitled Animation")],
             canvas_size: (64, 64),
+            outfits: Vec::new(),
+            export_outfits: Vec::new(),
+            effects: CharacterEffects::default(),
         }
     }
 
@@ -197,15 +352,28 @@ pub struct PlacedPart {
     /// Legacy character name (kept for backwards compatibility with old projects)
     #[serde(default)]
     pub character_name: String,
-    pub part_name: String,
+    /// Part drawn, by `Part::id`, resolved on the character (own parts, then linked library parts)
+    #[serde(default)]
+    pub part_id: u64,
+    /// State drawn, by `State::id` within the part
     #[serde(default)]
-    pub layer_name: String, // Display name for the layer (may differ from part_name)
-    pub state_name: String,
-    pub rotation: u16,       // Current rotation angle
+    pub state_id: u64,
+    /// Part and state names saved before schema v12, resolved to ids when loading. Kept
+    /// (and saved) while they don't resolve, so re-creating the part can still fix the layer.
+    #[serde(default, rename = "part_name", skip_serializing_if = "String::is_empty")]
+    pub legacy_part_name: String,
+    #[serde(default, rename = "state_name", skip_serializing_if = "String::is_empty")]
+    pub legacy_state_name: String,
+    #[serde(default)]
+    pub layer_name: String, // Display name for the layer (may differ from the part name)
+    pub rotation: Angle,      // Current rotation angle
     pub position: (f32, f32), // (x, y) position on canvas
     pub z_override: Option<i32>, // Frame-level z-index override
     #[serde(default = "default_visible")]
     pub visible: bool, // Whether this layer is visible
+    /// Set when this layer plays another animation instead of drawing a part
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nested: Option<NestedAnimation>,
 }
 
 fn default_visible() -> bool {
@@ -213,24 +381,22 @@ fn default_visible() -> bool {
 }
 
 impl PlacedPart {
-    pub fn new(
-        id: u64,
-        character_id: u64,
-        part_name: impl Into<String>,
-        state_name: impl Into<String>,
-    ) -> Self {
-        let part_name = part_name.into();
+    /// Placement of a part state; without a layer name the part's name is shown
+    pub fn new(id: u64, character_id: u64, part_id: u64, state_id: u64) -> Self {
         Self {
             id,
             character_id,
             character_name: String::new(), // Legacy field, no longer used for new parts
-            layer_name: part_name.clone(), // Default layer name is the part name
-            part_name,
-            state_name: state_name.into(),
-            rotation: 0,
+            part_id,
+            state_id,
+            legacy_part_name: String::new(),
+            legacy_state_name: String::new(),
+            layer_name: String::new(),
+            rotation: Angle::ZERO,
             position: (0.0, 0.0),
             z_override: None,
             visible: true,
+            nested: None,
         }
     }
 
@@ -289,12 +455,18 @@ impl Frame {
         let mut hasher = DefaultHasher::new();
         self.placed_parts.len().hash(&mut hasher);
         for part in &self.placed_parts {
-            part.part_name.hash(&mut hasher);
-            part.state_name.hash(&mut hasher);
+            part.part_id.hash(&mut hasher);
+            part.state_id.hash(&mut hasher);
             part.rotation.hash(&mut hasher);
             ((part.position.0 * 100.0) as i32).hash(&mut hasher);
             ((part.position.1 * 100.0) as i32).hash(&mut hasher);
             part.visible.hash(&mut hasher);
+            if let Some(ref nested) = part.nested {
+                nested.character_id.hash(&mut hasher);
+                nested.animation_name.hash(&mut hasher);
+                nested.start_offset_ms.hash(&mut hasher);
+                nested.looping.hash(&mut hasher);
+            }
         }
         hasher.finish()
     }
@@ -418,27 +590,41 @@ fn default_opacity() -> f32 {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
     pub version: String,
+    /// Numbered schema version (0 in files saved before schemas were numbered)
+    #[serde(default)]
+    pub schema_version: u32,
     pub name: String,
     /// Legacy field - canvas size is now per-character
     #[serde(default = "default_canvas_size", skip_serializing)]
     pub canvas_size: (u32, u32),
     pub characters: Vec<Character>,
+    /// Parts shared by several characters (weapons, hats, ...), linked by name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub part_library: Vec<Part>,
     /// Legacy field for v1 compatibility - animations are now per-character
     #[serde(default, skip_serializing)]
     pub animations: Vec<Animation>,
     /// Legacy field - reference images are now per-frame
     #[serde(default, skip_serializing)]
     pub reference_layer: ReferenceLayer,
+    /// Content-addressed part images referenced by rotations
+    #[serde(default, skip_serializing_if = "ImageStore::is_empty")]
+    pub images: ImageStore,
     /// Deduplicated reference image thumbnails (file_path -> base64 JPG)
     #[serde(default)]
     pub reference_thumbnails: HashMap<String, String>,
     /// Saved editor state
     #[serde(default)]
     pub editor_state: EditorState,
+    /// Id the next new part gets. Only goes up, so a deleted part's id is never reused.
+    #[serde(default)]
+    pub next_part_uid: u64,
     #[serde(skip)]
     pub next_part_id: u64, // Runtime counter for unique part placement IDs
     #[serde(skip)]
     pub next_character_id: u64, // Runtime counter for unique character IDs
+    #[serde(skip)]
+    pub migration_report: Option<MigrationReport>, // What loading changed to reach the current schema
 }
 
 impl Default for Project {
@@ -451,15 +637,20 @@ impl Project {
     pub fn new(name: impl Into<String>) -> Self {
         Self {
             version: "2.0".to_string(),
+            schema_version: CURRENT_SCHEMA_VERSION,
             name: name.into(),
             canvas_size: (64, 64),
             characters: Vec::new(),
+            part_library: Vec::new(),
             animations: Vec::new(), // Empty - animations are per-character now
             reference_layer: ReferenceLayer::new(),
+            images: ImageStore::default(),
             reference_thumbnails: HashMap::new(),
             editor_state: EditorState::default(),
+            next_part_uid: 1,
             next_part_id: 1,
             next_character_id: 1,
+            migration_report: None,
         }
     }
 
@@ -497,123 +688,103 @@ impl Project {
         id
     }
 
-    /// Save project to JSON string
-    pub fn to_json(&self) -> Result<String, serde_json::Error> {
-        serde_json::to_string_pretty(self)
+    /// Iterate over every rotation of every state in the project, including library parts
+    pub fn rotations(&self) -> impl Iterator<Item = &Rotation> {
+        self.characters
+            .iter()
+            .flat_map(|c| c.parts.iter())
+            .chain(self.part_library.iter())
+            .flat_map(|p| p.states.iter())
+            .flat_map(|s| s.rotations.values())
     }
 
-    /// Load project from JSON string, with automatic migration from v1 format
-    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
-        let mut project: Self = serde_json::from_str(json)?;
+    /// Get the base64 PNG data for a rotation from the image store
+    pub fn rotation_image(&self, rotation: &Rotation) -> Option<&str> {
+        rotation.image.as_deref().and_then(|hash| self.images.get(hash))
+    }
 
-        // Initialize next_part_id to be higher than any existing part ID
-        let max_id = project.characters.iter()
-            .flat_map(|c| c.animations.iter())
-            .flat_map(|a| a.frames.iter())
-            .flat_map(|f| f.placed_parts.iter())
-            .map(|p| p.id)
-            .max()
-            .unwrap_or(0);
-        project.next_part_id = max_id + 1;
-
-        // Migrate v1 projects: move project-level animations to characters
-        if !project.animations.is_empty() {
-            // Build a set of which characters are used by each animation
-            for anim in std::mem::take(&mut project.animations) {
-                // Find which characters are used in this animation
-                let used_chars: std::collections::HashSet<String> = anim.frames.iter()
-                    .flat_map(|f| f.placed_parts.iter())
-                    .map(|p| p.character_name.clone())
-                    .collect();
-
-                if used_chars.len() == 1 {
-                    // Animation uses only one character - assign to that character
-                    let char_name = used_chars.into_iter().next().unwrap();
-                    if let Some(character) = project.characters.iter_mut()
-                        .find(|c| c.name == char_name)
-                    {
-                        character.animations.push(anim);
-                    }
-                } else if !used_chars.is_empty() {
-                    // Animation uses multiple characters - assign to first character
-                    let char_name = used_chars.into_iter().next().unwrap();
-                    if let Some(character) = project.characters.iter_mut()
-                        .find(|c| c.name == char_name)
+    /// Decoded pixels for a rotation from the image store's shared cache.
+    /// None when the rotation has no image (or its image is missing from the store).
+    pub fn rotation_pixels(&self, rotation: &Rotation) -> Option<Result<Arc<image::RgbaImage>, String>> {
+        self.rotation_image(rotation)?;
+        rotation.image.as_deref().map(|hash| self.images.decoded(hash))
+    }
+
+    /// Hashes of every image referenced by a rotation
+    pub fn used_image_hashes(&self) -> HashSet<String> {
+        self.rotations().filter_map(|r| r.image.clone()).collect()
+    }
+
+    /// Source files of every rotation linked to external art
+    pub fn linked_source_paths(&self) -> HashSet<String> {
+        self.rotations()
+            .filter_map(|r| r.source_path.clone())
+            .collect()
+    }
+
+    /// Point every rotation linked to `source_path` at a new image, returning how many changed
+    pub fn relink_image(&mut self, source_path: &str, image_hash: &str) -> usize {
+        let mut changed = 0;
+        let parts = self
+            .characters
+            .iter_mut()
+            .flat_map(|c| c.parts.iter_mut())
+            .chain(self.part_library.iter_mut());
+        for part in parts {
+            for state in &mut part.states {
+                for rotation in state.rotations.values_mut() {
+                    if rotation.source_path.as_deref() == Some(source_path)
+                        && rotation.image.as_deref() != Some(image_hash)
                     {
-                        let mut anim = anim;
-                        anim.name = format!("{} (multi-char)", anim.name);
-                        character.animations.push(anim);
+                        rotation.image = Some(image_hash.to_string());
+                        changed += 1;
                     }
                 }
-                // If no characters used, animation is orphaned and dropped
-            }
-
-            // Ensure all characters have at least one animation
-            for character in &mut project.characters {
-                if character.animations.is_empty() {
-                    character.animations.push(Animation::new("Untitled Animation"));
-                }
             }
-
-            // Update version to indicate migration happened
-            project.version = "2.0".to_string();
         }
+        changed
+    }
 
-        // Migrate canvas_size from project level to character level
-        // Characters with default canvas size inherit the project's canvas_size
-        let project_canvas = project.canvas_size;
-        for character in &mut project.characters {
-            if character.canvas_size == (64, 64) && project_canvas != (64, 64) {
-                character.canvas_size = project_canvas;
-            }
-        }
+    /// Remove images no rotation references anymore, returning how many were dropped
+    pub fn collect_unused_images(&mut self) -> usize {
+        let used = self.used_image_hashes();
+        self.images.retain_used(&used)
+    }
 
-        // Migrate character IDs: assign IDs to characters that don't have them
-        // (old projects where id defaults to 0)
-        let max_char_id = project.characters.iter().map(|c| c.id).max().unwrap_or(0);
-        project.next_character_id = max_char_id + 1;
+    /// Save project to JSON string
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
 
-        for character in &mut project.characters {
-            if character.id == 0 {
-                character.id = project.next_character_id;
-                project.next_character_id += 1;
-            }
-        }
+    /// Load project from JSON string, migrating older schema versions to the current one
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let mut project: Self = serde_json::from_str(json)?;
 
-        // Build name-to-id mapping for migrating PlacedParts
-        let name_to_id: HashMap<String, u64> = project.characters.iter()
-            .map(|c| (c.name.clone(), c.id))
-            .collect();
+        let report = migration::migrate(&mut project).map_err(serde::de::Error::custom)?;
+        project.migration_report = Some(report);
 
-        // Migrate PlacedParts: resolve character_name to character_id
-        for character in &mut project.characters {
-            let parent_char_id = character.id; // PlacedParts in this character's animations belong to this character
-            for animation in &mut character.animations {
-                for frame in &mut animation.frames {
-                    for placed_part in &mut frame.placed_parts {
-                        // If character_id is 0 (not set), try to resolve
-                        if placed_part.character_id == 0 {
-                            if !placed_part.character_name.is_empty() {
-                                // Try to resolve from character_name first
-                                if let Some(&id) = name_to_id.get(&placed_part.character_name) {
-                                    placed_part.character_id = id;
-                                }
-                            } else {
-                                // If no character_name, infer from parent character
-                                placed_part.character_id = parent_char_id;
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        // Initialize runtime ID counters past any existing IDs
+        project.next_part_id = project
+            .characters
+            .iter()
+            .flat_map(|c| c.animations.iter())
+            .flat_map(|a| a.frames.iter())
+            .flat_map(|f| f.placed_parts.iter())
+            .map(|p| p.id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        project.next_character_id =
+            project.characters.iter().map(|c| c.id).max().unwrap_or(0) + 1;
 
         Ok(project)
     }
 
-    /// Check if this project was migrated from v1 (had project-level animations)
+    /// Check if loading this project changed anything to bring it up to the current schema
     pub fn was_migrated(&self) -> bool {
-        self.version == "2.0" && self.animations.is_empty()
+        self.migration_report
+            .as_ref()
+            .is_some_and(|report| !report.changes.is_empty())
     }
 }
 
@@ -634,22 +805,168 @@ mod tests {
     #[test]
     fn test_character_parts() {
         let mut char = Character::new(1, "Hero");
-        char.add_part(Part::new("head"));
-        char.add_part(Part::new("torso"));
+        char.add_part(Part::new(1, "head"));
+        char.add_part(Part::new(2, "torso"));
 
         assert_eq!(char.parts.len(), 2);
         assert!(char.get_part("head").is_some());
         assert!(char.get_part("torso").is_some());
     }
 
+    #[test]
+    fn test_deleted_ids_are_not_reused() {
+        let mut project = Project::new("Ids");
+        project.add_character(Character::new(1, "Hero"));
+        let head_id = project.new_part_id();
+        let mut head = Part::new(head_id, "head");
+        let swing = head.add_state(State::new("swing", RotationMode::Deg45));
+        head.states.retain(|s| s.id != swing);
+        let idle = head.add_state(State::new("idle", RotationMode::Deg45));
+        assert_ne!(idle, swing);
+        let hero = project.get_character_mut("Hero").unwrap();
+        hero.add_part(head);
+        let cape_id = project.new_part_id();
+        project
+            .get_character_mut("Hero")
+            .unwrap()
+            .add_part(Part::new(cape_id, "cape"));
+
+        // The counters are saved, so ids stay retired across a reload too
+        let hero = project.get_character_mut("Hero").unwrap();
+        hero.parts.retain(|p| p.id != cape_id);
+        let mut project = Project::from_json(&project.to_json().unwrap()).unwrap();
+        assert!(project.new_part_id() > cape_id);
+        let head = project.get_part_mut("Hero", "head").unwrap();
+        head.states.retain(|s| s.id != idle);
+        let run = head.add_state(State::new("run", RotationMode::Deg45));
+        assert!(run > idle);
+    }
+
+    #[test]
+    fn test_rotation_modes() {
+        assert_eq!(RotationMode::Deg90.angles().len(), 4);
+        assert_eq!(RotationMode::Deg11_25.angles().len(), 32);
+        // 22.5° steps are exact, not truncated to 22, 67, 112...
+        let angles = RotationMode::Deg22_5.angles();
+        assert_eq!(angles[1], Angle::from_degrees(22.5));
+        assert_eq!(angles[3].to_string(), "67.5");
+
+        let mut state = State::new("default", RotationMode::Deg45);
+        let east = Angle::ZERO;
+        let ne = Angle::from_degrees(45.0);
+        state.rotations.get_mut(&ne).unwrap().image = Some("drawn".to_string());
+        let custom = RotationMode::custom(vec![Angle::from_degrees(30.0), east, east]);
+        assert_eq!(custom.angles(), [east, Angle::from_degrees(30.0)]);
+        assert_eq!(state.set_rotation_mode(custom), 1);
+        assert_eq!(state.angles(), [east, Angle::from_degrees(30.0), ne]);
+    }
+
     #[test]
     fn test_rotation_mirroring() {
+        let mirror = |degrees: f32| Angle::from_degrees(degrees).mirrored().to_string();
+        assert_eq!(mirror(0.0), "0");
+        assert_eq!(mirror(45.0), "315");
+        assert_eq!(mirror(90.0), "270");
+        assert_eq!(mirror(180.0), "180");
+        assert_eq!(mirror(22.5), "337.5");
+        assert_eq!(mirror(11.25), "348.75");
+        // Every 22.5° and 11.25° angle mirrors onto another angle of its mode
+        for mode in [RotationMode::Deg22_5, RotationMode::Deg11_25] {
+            let angles = mode.angles();
+            assert!(angles.iter().all(|a| angles.contains(&a.mirrored())));
+        }
+
+        // An empty angle shows its mirrored angle's image, flipped
+        let mut state = State::new("default", RotationMode::Deg22_5);
+        let drawn = Angle::from_degrees(22.5);
+        state.rotations.get_mut(&drawn).unwrap().image = Some("drawn".to_string());
+        let (rotation, mirrored) = state.get_rotation(drawn.mirrored()).unwrap();
+        assert_eq!((rotation.angle, mirrored), (drawn, true));
+        assert!(!state.get_rotation(drawn).unwrap().1);
+        assert!(state.get_rotation(Angle::from_degrees(90.0)).unwrap().0.image.is_none());
+    }
+
+    #[test]
+    fn test_legacy_images_move_to_store() {
+        let png_signature = "iVBORw0KGgo=";
+        let json = format!(
+            r#"{{
+                "version": "2.0",
+                "name": "Legacy",
+                "characters": [{{
+                    "id": 1,
+                    "name": "Hero",
+                    "parts": [{{
+                        "name": "shield",
+                        "default_z": 0,
+                        "states": [{{
+                            "name": "default",
+                            "rotation_mode": "Deg45",
+                            "rotations": {{
+                                "0": {{ "angle": 0, "image_data": "{0}" }},
+                                "180": {{ "angle": 180, "image_data": "{0}" }},
+                                "90": {{ "angle": 90, "image_data": null }}
+                            }}
+                        }}]
+                    }}]
+                }}]
+            }}"#,
+            png_signature
+        );
+
+        let mut project = Project::from_json(&json).unwrap();
+        assert_eq!(project.images.len(), 1);
+
+        let state = &project.characters[0].parts[0].states[0];
+        let front = &state.rotations[&Angle::ZERO];
+        assert_eq!(front.image, state.rotations[&Angle::from_degrees(180.0)].image);
+        assert_eq!(project.rotation_image(front), Some(png_signature));
+        assert!(state.rotations[&Angle::from_degrees(90.0)].image.is_none());
+        assert!(!project.to_json().unwrap().contains("image_data"));
+
+        // Unreferenced images are dropped
+        project.characters[0].parts[0].states[0]
+            .rotations
+            .values_mut()
+            .for_each(|r| r.image = None);
+        assert_eq!(project.collect_unused_images(), 1);
+        assert!(project.images.is_empty());
+    }
+
+    #[test]
+    fn test_angles_from() {
         let mode = RotationMode::Deg45;
-        assert_eq!(mode.mirror_angle(0), 0);
-        assert_eq!(mode.mirror_angle(45), 315);
-        assert_eq!(mode.mirror_angle(90), 270);
-        assert_eq!(mode.mirror_angle(135), 225);
-        assert_eq!(mode.mirror_angle(180), 180);
+        assert_eq!(mode.angles_from(Angle::ZERO, false), mode.angles());
+        let degrees: Vec<f32> = mode
+            .angles_from(Angle::from_degrees(270.0), true)
+            .iter()
+            .map(|a| a.degrees())
+            .collect();
+        assert_eq!(degrees, [270.0, 225.0, 180.0, 135.0, 90.0, 45.0, 0.0, 315.0]);
+    }
+
+    #[test]
+    fn test_relink_image() {
+        let mut project = Project::new("Linked");
+        let mut character = Character::new(1, "Hero");
+        let mut part = Part::new(1, "arm");
+        for degrees in [0.0, 90.0] {
+            let angle = Angle::from_degrees(degrees);
+            let rotation = part.states[0].rotations.get_mut(&angle).unwrap();
+            rotation.image = Some("old".to_string());
+            rotation.source_path = Some("/art/arm.png".to_string());
+        }
+        let west = Angle::from_degrees(180.0);
+        part.states[0].rotations.get_mut(&west).unwrap().image = Some("other".to_string());
+        character.add_part(part);
+        project.add_character(character);
+
+        assert_eq!(project.linked_source_paths().len(), 1);
+        assert_eq!(project.relink_image("/art/arm.png", "new"), 2);
+        assert_eq!(project.relink_image("/art/arm.png", "new"), 0);
+        let rotations = &project.characters[0].parts[0].states[0].rotations;
+        assert_eq!(rotations[&Angle::from_degrees(90.0)].image.as_deref(), Some("new"));
+        assert_eq!(rotations[&west].image.as_deref(), Some("other"));
     }
 
     #[test]
@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::{Animation, Frame, PlacedPart, Project, Rotation};
+
+/// Nested animations inside nested animations are followed this many levels deep,
+/// which also stops an animation that (indirectly) contains itself
+const MAX_NESTING_DEPTH: usize = 4;
+
+/// Another animation played as a layer inside a frame (a torch flame, a blinking eye)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NestedAnimation {
+    /// Character owning the animation (may be a different character)
+    pub character_id: u64,
+    pub animation_name: String,
+    /// Host time at which the nested animation starts. Before that a non-looping
+    /// animation holds its first frame; a looping one is simply shifted in phase.
+    #[serde(default)]
+    pub start_offset_ms: u32,
+    /// Loop forever, otherwise hold the last frame once it has played through
+    #[serde(default = "default_looping")]
+    pub looping: bool,
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+impl NestedAnimation {
+    pub fn new(character_id: u64, animation_name: impl Into<String>) -> Self {
+        Self {
+            character_id,
+            animation_name: animation_name.into(),
+            start_offset_ms: 0,
+            looping: true,
+        }
+    }
+}
+
+impl PlacedPart {
+    /// Placement that plays another animation instead of drawing a part
+    pub fn new_nested(id: u64, nested: NestedAnimation) -> Self {
+        let mut placed = Self::new(id, nested.character_id, 0, 0)
+            .with_layer_name(nested.animation_name.as_str());
+        placed.nested = Some(nested);
+        placed
+    }
+}
+
+impl Animation {
+    /// Time from the start of the animation to the start of a frame
+    pub fn frame_start_ms(&self, index: usize) -> u32 {
+        self.frames.iter().take(index).map(|f| f.duration_ms).sum()
+    }
+
+    pub fn total_duration_ms(&self) -> u32 {
+        self.frames.iter().map(|f| f.duration_ms).sum()
+    }
+
+    /// Index of the frame showing `time_ms` into the animation. Looping wraps around;
+    /// otherwise earlier times show the first frame and later ones hold the last.
+    pub fn frame_at_time(&self, time_ms: i64, looping: bool) -> Option<usize> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        let total = self.total_duration_ms() as i64;
+        if total == 0 {
+            return Some(0);
+        }
+        let time = if looping {
+            time_ms.rem_euclid(total)
+        } else {
+            time_ms.clamp(0, total - 1)
+        };
+        let mut frame_end = 0;
+        for (index, frame) in self.frames.iter().enumerate() {
+            frame_end += frame.duration_ms as i64;
+            if time < frame_end {
+                return Some(index);
+            }
+        }
+        Some(self.frames.len() - 1)
+    }
+}
+
+/// One part image to draw for a frame, after nested animations are expanded
+#[derive(Debug, Clone)]
+pub struct FrameSprite<'a> {
+    /// Top-level placement the sprite belongs to; selecting or dragging it acts on this
+    pub owner: &'a PlacedPart,
+    /// The part drawn: the owner itself or a part inside a nested animation
+    pub placed: &'a PlacedPart,
+    pub rotation: Option<&'a Rotation>,
+    /// The rotation is the mirrored angle's image and is drawn flipped top to bottom
+    pub mirrored: bool,
+    /// Canvas position, including the offsets of any nested animations it is inside
+    pub position: (f32, f32),
+}
+
+impl Project {
+    /// Decoded pixels of a sprite, flipped when it shows the mirrored angle's image.
+    /// None when it has no image.
+    pub fn sprite_pixels(
+        &self,
+        sprite: &FrameSprite,
+    ) -> Option<Result<Arc<image::RgbaImage>, String>> {
+        let rotation = sprite.rotation?;
+        if !sprite.mirrored {
+            return self.rotation_pixels(rotation);
+        }
+        self.rotation_image(rotation)?;
+        let hash = rotation.image.as_deref()?;
+        Some(self.images.decoded_mirrored(hash))
+    }
+
+    pub fn nested_animation(&self, nested: &NestedAnimation) -> Option<&Animation> {
+        self.get_character_by_id(nested.character_id)?
+            .get_animation(&nested.animation_name)
+    }
+
+    /// Content hash of a frame including the animations its nested layers play, so
+    /// editing a nested animation refreshes the thumbnails of frames showing it
+    pub fn frame_content_hash(&self, frame: &Frame) -> u64 {
+        self.nested_content_hash(frame, 0)
+    }
+
+    fn nested_content_hash(&self, frame: &Frame, depth: usize) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let mut hasher = DefaultHasher::new();
+        frame.content_hash().hash(&mut hasher);
+        if depth < MAX_NESTING_DEPTH {
+            let nested = frame.placed_parts.iter().filter_map(|p| p.nested.as_ref());
+            for animation in nested.filter_map(|n| self.nested_animation(n)) {
+                for inner in &animation.frames {
+                    inner.duration_ms.hash(&mut hasher);
+                    self.nested_content_hash(inner, depth + 1).hash(&mut hasher);
+                }
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Visible sprites of a frame, bottom to top. Nested animations show the frame
+    /// playing at the start time of this frame.
+    pub fn frame_sprites<'a>(
+        &'a self,
+        animation: &'a Animation,
+        frame_idx: usize,
+        outfit: Option<&str>,
+    ) -> Vec<FrameSprite<'a>> {
+        let mut sprites = Vec::new();
+        if let Some(frame) = animation.frames.get(frame_idx) {
+            let time = animation.frame_start_ms(frame_idx) as i64;
+            for placed in frame.placed_parts.iter().filter(|p| p.visible) {
+                self.collect_sprites(placed, placed, (0.0, 0.0), time, 0, &mut sprites);
+            }
+        }
+        for sprite in &mut sprites {
+            let rotation = self.placed_rotation(sprite.placed, outfit);
+            sprite.rotation = rotation.map(|(rotation, _)| rotation);
+            sprite.mirrored = rotation.is_some_and(|(_, mirrored)| mirrored);
+        }
+        sprites
+    }
+
+    fn collect_sprites<'a>(
+        &'a self,
+        owner: &'a PlacedPart,
+        placed: &'a PlacedPart,
+        offset: (f32, f32),
+        time_ms: i64,
+        depth: usize,
+        sprites: &mut Vec<FrameSprite<'a>>,
+    ) {
+        let position = (offset.0 + placed.position.0, offset.1 + placed.position.1);
+        let Some(ref nested) = placed.nested else {
+            sprites.push(FrameSprite {
+                owner,
+                placed,
+                rotation: None,
+                mirrored: false,
+                position,
+            });
+            return;
+        };
+        if depth >= MAX_NESTING_DEPTH {
+            return;
+        }
+        let Some(animation) = self.nested_animation(nested) else {
+            return;
+        };
+        let local_time = time_ms - nested.start_offset_ms as i64;
+        let Some(frame) = animation
+            .frame_at_time(local_time, nested.looping)
+            .and_then(|i| animation.frames.get(i))
+        else {
+            return;
+        };
+        let total = animation.total_duration_ms() as i64;
+        let local_time = if nested.looping && total > 0 {
+            local_time.rem_euclid(total)
+        } else {
+            local_time
+        };
+        for inner in frame.placed_parts.iter().filter(|p| p.visible) {
+            self.collect_sprites(owner, inner, position, local_time, depth + 1, sprites);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Character, Frame, Part};
+
+    #[test]
+    fn test_frame_at_time() {
+        let mut blink = Animation::new("blink");
+        blink.frames[0].duration_ms = 300;
+        blink.frames.push(Frame::new(100));
+
+        assert_eq!(blink.frame_at_time(0, true), Some(0));
+        assert_eq!(blink.frame_at_time(350, true), Some(1));
+        assert_eq!(blink.frame_at_time(450, true), Some(0));
+        assert_eq!(blink.frame_at_time(-50, true), Some(1));
+        assert_eq!(blink.frame_at_time(-50, false), Some(0));
+        assert_eq!(blink.frame_at_time(1000, false), Some(1));
+    }
+
+    #[test]
+    fn test_nested_sprites_follow_host_time() {
+        let mut project = Project::new("Torch");
+        let mut torch = Character::new(1, "Torch");
+        torch.add_part(Part::new(1, "flame"));
+        let mut flicker = Animation::new("flicker");
+        flicker.frames.push(Frame::new(100));
+        for (i, frame) in flicker.frames.iter_mut().enumerate() {
+            let mut flame = PlacedPart::new(i as u64 + 1, 1, 1, 1);
+            flame.position = (i as f32, 2.0);
+            frame.placed_parts.push(flame);
+        }
+        torch.add_animation(flicker);
+
+        let mut host = Animation::new("wave");
+        host.add_frame();
+        for frame in &mut host.frames {
+            let mut placed = PlacedPart::new_nested(10, NestedAnimation::new(1, "flicker"));
+            placed.position = (10.0, 10.0);
+            frame.placed_parts.push(placed);
+        }
+        project.add_character(torch);
+
+        // Host frame 1 starts at 100ms, where the flicker is on its second frame
+        let sprites = project.frame_sprites(&host, 1, None);
+        assert_eq!(sprites.len(), 1);
+        assert_eq!(sprites[0].owner.id, 10);
+        assert_eq!(sprites[0].placed.part_id, 1);
+        assert_eq!(sprites[0].position, (11.0, 12.0));
+        assert!(sprites[0].rotation.is_some());
+
+        // Editing the nested animation changes the host frame's content hash
+        let before = project.frame_content_hash(&host.frames[0]);
+        let torch = project.get_character_mut("Torch").unwrap();
+        torch.animations[1].frames[0].placed_parts[0].position = (5.0, 5.0);
+        assert_ne!(project.frame_content_hash(&host.frames[0]), before);
+    }
+}
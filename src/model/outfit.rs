@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{Character, PlacedPart, Project, Rotation};
+
+/// What an outfit draws in place of one part
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OutfitSwap {
+    /// Same part, different state (e.g. torso "default" -> "armored")
+    State(String),
+    /// A different part, in the same state if it has one, otherwise its first state
+    Part(String),
+}
+
+/// Named variant of a character ("armored", "casual") that swaps parts or states
+/// at render time, so the same animations can be shown and exported in several looks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Outfit {
+    pub name: String,
+    /// Part name -> replacement
+    #[serde(default)]
+    pub swaps: BTreeMap<String, OutfitSwap>,
+}
+
+impl Outfit {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            swaps: BTreeMap::new(),
+        }
+    }
+}
+
+impl Character {
+    pub fn get_outfit(&self, name: &str) -> Option<&Outfit> {
+        self.outfits.iter().find(|o| o.name == name)
+    }
+
+    pub fn get_outfit_mut(&mut self, name: &str) -> Option<&mut Outfit> {
+        self.outfits.iter_mut().find(|o| o.name == name)
+    }
+}
+
+impl Project {
+    /// Rotation drawn for a placed part, with the swaps of the named outfit applied, and
+    /// whether it is the mirrored angle's image (see `State::get_rotation`).
+    /// Outfits are looked up on the placed part's own character; unknown names change nothing.
+    pub fn placed_rotation(
+        &self,
+        placed: &PlacedPart,
+        outfit: Option<&str>,
+    ) -> Option<(&Rotation, bool)> {
+        let character = self.get_character_by_id(placed.character_id)?;
+        let part = self.resolve_part_id(character, placed.part_id)?;
+        let swap = outfit
+            .and_then(|name| character.get_outfit(name))
+            .and_then(|o| o.swaps.get(&part.name));
+
+        let state = match swap {
+            None => part.get_state_by_id(placed.state_id)?,
+            Some(OutfitSwap::State(state_name)) => part.get_state(state_name)?,
+            Some(OutfitSwap::Part(part_name)) => {
+                let state_name = &part.get_state_by_id(placed.state_id)?.name;
+                let part = self.resolve_part(character, part_name)?;
+                part.get_state(state_name).or_else(|| part.states.first())?
+            }
+        };
+        state.get_rotation(placed.rotation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Angle, Part, RotationMode, State};
+
+    #[test]
+    fn test_outfit_swaps() {
+        let mut project = Project::new("Outfits");
+        let mut knight = Character::new(1, "Knight");
+        let mut torso = Part::new(1, "torso");
+        torso.add_state(State::new("armored", RotationMode::Deg45));
+        knight.add_part(torso);
+        knight.add_part(Part::new(2, "legs"));
+        knight.add_part(Part::new(3, "greaves"));
+
+        let mut armored = Outfit::new("armored");
+        armored.swaps.insert(
+            "torso".to_string(),
+            OutfitSwap::State("armored".to_string()),
+        );
+        armored
+            .swaps
+            .insert("legs".to_string(), OutfitSwap::Part("greaves".to_string()));
+        knight.outfits.push(armored);
+        project.add_character(knight);
+
+        let hash = project.images.insert_base64("iVBORw0KGgo=").unwrap();
+        let knight = project.get_character_mut("Knight").unwrap();
+        knight.get_part_mut("torso").unwrap().states[1]
+            .rotations
+            .get_mut(&Angle::ZERO)
+            .unwrap()
+            .image = Some(hash.clone());
+        knight.get_part_mut("greaves").unwrap().states[0]
+            .rotations
+            .get_mut(&Angle::ZERO)
+            .unwrap()
+            .image = Some(hash.clone());
+
+        let torso = PlacedPart::new(1, 1, 1, 1);
+        let legs = PlacedPart::new(2, 1, 2, 1);
+        let plain = |p: &PlacedPart| project.placed_rotation(p, None).unwrap().0.image.clone();
+        let worn = |p: &PlacedPart| {
+            project
+                .placed_rotation(p, Some("armored"))
+                .unwrap()
+                .0
+                .image
+                .clone()
+        };
+        assert_eq!(plain(&torso), None);
+        assert_eq!(worn(&torso), Some(hash.clone()));
+        assert_eq!(plain(&legs), None);
+        assert_eq!(worn(&legs), Some(hash));
+    }
+}
@@ -0,0 +1,263 @@
+use super::{Character, Part, Project};
+
+impl Character {
+    /// Whether this character links the library part `name` (its own parts take precedence)
+    pub fn uses_library_part(&self, name: &str) -> bool {
+        self.get_part(name).is_none() && self.library_parts.iter().any(|n| n == name)
+    }
+}
+
+impl Project {
+    pub fn library_part(&self, name: &str) -> Option<&Part> {
+        self.part_library.iter().find(|p| p.name == name)
+    }
+
+    /// Resolve a part for a character: its own parts first, then linked library parts
+    pub fn resolve_part<'a>(
+        &'a self,
+        character: &'a Character,
+        part_name: &str,
+    ) -> Option<&'a Part> {
+        character.get_part(part_name).or_else(|| {
+            if character.library_parts.iter().any(|n| n == part_name) {
+                self.library_part(part_name)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn get_part(&self, char_name: &str, part_name: &str) -> Option<&Part> {
+        self.get_character(char_name)
+            .and_then(|c| self.resolve_part(c, part_name))
+    }
+
+    pub fn get_part_by_character_id(&self, character_id: u64, part_name: &str) -> Option<&Part> {
+        self.get_character_by_id(character_id)
+            .and_then(|c| self.resolve_part(c, part_name))
+    }
+
+    /// Mutable part lookup; editing a linked library part changes it for every character using it
+    pub fn get_part_mut(&mut self, char_name: &str, part_name: &str) -> Option<&mut Part> {
+        let character = self.characters.iter_mut().find(|c| c.name == char_name)?;
+        if character.get_part(part_name).is_some() {
+            return character.get_part_mut(part_name);
+        }
+        if character.library_parts.iter().any(|n| n == part_name) {
+            self.part_library.iter_mut().find(|p| p.name == part_name)
+        } else {
+            None
+        }
+    }
+
+    /// Every part a character can use: its own parts, then linked library parts
+    pub fn character_parts<'a>(
+        &'a self,
+        character: &'a Character,
+    ) -> impl Iterator<Item = &'a Part> {
+        character.parts.iter().chain(
+            character
+                .library_parts
+                .iter()
+                .filter(|name| character.get_part(name).is_none())
+                .filter_map(|name| self.library_part(name)),
+        )
+    }
+
+    /// Number of characters linking a library part
+    pub fn library_part_users(&self, name: &str) -> usize {
+        self.characters
+            .iter()
+            .filter(|c| c.library_parts.iter().any(|n| n == name))
+            .count()
+    }
+
+    /// Move a character's own part into the library and link it in its place. The part
+    /// gets a fresh id, since copies of it (from cloning the character) may share the old one.
+    pub fn share_part(&mut self, char_name: &str, part_name: &str) -> Result<(), String> {
+        if self.library_part(part_name).is_some() {
+            return Err(format!(
+                "The library already has a part named '{}'",
+                part_name
+            ));
+        }
+        let new_id = self.new_part_id();
+        let character = self
+            .get_character_mut(char_name)
+            .ok_or_else(|| format!("Character '{}' not found", char_name))?;
+        let index = character
+            .parts
+            .iter()
+            .position(|p| p.name == part_name)
+            .ok_or_else(|| format!("Part '{}' not found", part_name))?;
+        let character_id = character.id;
+        let mut part = character.parts.remove(index);
+        character.library_parts.push(part_name.to_string());
+        let old_part = part.clone();
+        part.id = new_id;
+        self.repoint_placements(character_id, &old_part, &part);
+        self.part_library.push(part);
+        Ok(())
+    }
+
+    /// Link a library part into a character, replacing the character's own copy if it has
+    /// one; placements of the copy move to the library part
+    pub fn link_library_part(&mut self, char_name: &str, part_name: &str) -> Result<(), String> {
+        let library_part = self
+            .library_part(part_name)
+            .cloned()
+            .ok_or_else(|| format!("No library part named '{}'", part_name))?;
+        let character = self
+            .get_character_mut(char_name)
+            .ok_or_else(|| format!("Character '{}' not found", char_name))?;
+        let character_id = character.id;
+        let own_copy = character.get_part(part_name).cloned();
+        character.parts.retain(|p| p.name != part_name);
+        if !character.library_parts.iter().any(|n| n == part_name) {
+            character.library_parts.push(part_name.to_string());
+        }
+        if let Some(own_copy) = own_copy {
+            self.repoint_placements(character_id, &own_copy, &library_part);
+        }
+        Ok(())
+    }
+
+    /// Replace a character's link to a library part with a private copy it can edit alone.
+    /// The copy gets its own id and the character's placements move to it.
+    pub fn make_local_copy(&mut self, char_name: &str, part_name: &str) -> Result<(), String> {
+        let library_part = self
+            .library_part(part_name)
+            .cloned()
+            .ok_or_else(|| format!("No library part named '{}'", part_name))?;
+        let mut part = library_part.clone();
+        part.id = self.new_part_id();
+        let character = self
+            .get_character_mut(char_name)
+            .ok_or_else(|| format!("Character '{}' not found", char_name))?;
+        if !character.uses_library_part(part_name) {
+            return Err(format!(
+                "'{}' is not a shared part of {}",
+                part_name, char_name
+            ));
+        }
+        let character_id = character.id;
+        character.library_parts.retain(|n| n != part_name);
+        self.repoint_placements(character_id, &library_part, &part);
+        if let Some(character) = self.get_character_mut(char_name) {
+            character.parts.push(part);
+        }
+        Ok(())
+    }
+
+    /// Stop a character using a library part. The part leaves the library once no
+    /// character uses it; returns whether that happened.
+    pub fn unlink_library_part(&mut self, char_name: &str, part_name: &str) -> bool {
+        if let Some(character) = self.get_character_mut(char_name) {
+            character.library_parts.retain(|n| n != part_name);
+        }
+        if self.library_part_users(part_name) == 0 {
+            self.part_library.retain(|p| p.name != part_name);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Rename a library part along with every character's link to it
+    pub fn rename_library_part(&mut self, old_name: &str, new_name: &str) -> Result<(), String> {
+        if self.library_part(new_name).is_some() {
+            return Err(format!(
+                "The library already has a part named '{}'",
+                new_name
+            ));
+        }
+        let part = self
+            .part_library
+            .iter_mut()
+            .find(|p| p.name == old_name)
+            .ok_or_else(|| format!("No library part named '{}'", old_name))?;
+        part.name = new_name.to_string();
+        for character in &mut self.characters {
+            for name in &mut character.library_parts {
+                if name == old_name {
+                    *name = new_name.to_string();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::PlacedPart;
+
+    fn two_heroes() -> Project {
+        let mut project = Project::new("Heroes");
+        for (id, name) in [(1, "Knight"), (2, "Ranger")] {
+            let mut character = Character::new(id, name);
+            character.add_part(Part::new(id * 10 + 1, "head"));
+            character.add_part(Part::new(id * 10 + 2, "sword"));
+            project.add_character(character);
+        }
+        project
+    }
+
+    #[test]
+    fn test_shared_part_edits_reach_every_user() {
+        let mut project = two_heroes();
+        let swing = PlacedPart::new(1, 2, 22, 1);
+        project.characters[1].animations[0].frames[0]
+            .placed_parts
+            .push(swing);
+        project.share_part("Knight", "sword").unwrap();
+        project.link_library_part("Ranger", "sword").unwrap();
+
+        // The Ranger's own copy is replaced by the link, and its placements follow
+        assert!(project
+            .get_character("Ranger")
+            .unwrap()
+            .get_part("sword")
+            .is_none());
+        assert_eq!(project.library_part_users("sword"), 2);
+        let swing = &project.characters[1].animations[0].frames[0].placed_parts[0];
+        assert_eq!(
+            project.placed_part(swing).map(|p| p.id),
+            Some(project.part_library[0].id)
+        );
+
+        project.get_part_mut("Knight", "sword").unwrap().default_z = 7;
+        assert_eq!(project.get_part("Ranger", "sword").unwrap().default_z, 7);
+        assert_eq!(
+            project
+                .get_part_by_character_id(2, "sword")
+                .unwrap()
+                .default_z,
+            7
+        );
+
+        let ranger = project.get_character("Ranger").unwrap();
+        let names: Vec<&str> = project
+            .character_parts(ranger)
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, ["head", "sword"]);
+    }
+
+    #[test]
+    fn test_local_copy_and_unlink() {
+        let mut project = two_heroes();
+        project.share_part("Knight", "sword").unwrap();
+        project.link_library_part("Ranger", "sword").unwrap();
+
+        project.make_local_copy("Ranger", "sword").unwrap();
+        project.get_part_mut("Ranger", "sword").unwrap().default_z = 3;
+        assert_eq!(project.get_part("Knight", "sword").unwrap().default_z, 0);
+
+        // Unlinking the last user drops the part from the library
+        assert!(project.unlink_library_part("Knight", "sword"));
+        assert!(project.part_library.is_empty());
+        assert!(project.get_part("Knight", "sword").is_none());
+    }
+}
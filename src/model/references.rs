@@ -0,0 +1,291 @@
+use super::{Character, OutfitSwap, Part, PlacedPart, Project, State};
+
+/// Whether a layer name was made from a part name: "head", or "head 2" for the second one
+pub(super) fn is_named_after(layer_name: &str, part_name: &str) -> bool {
+    layer_name.strip_prefix(part_name).is_some_and(|rest| {
+        rest.is_empty()
+            || rest
+                .strip_prefix(' ')
+                .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+impl Character {
+    pub fn get_part_by_id(&self, id: u64) -> Option<&Part> {
+        self.parts.iter().find(|p| p.id == id)
+    }
+
+    /// Point the name-keyed settings of a character (outfit swaps, z overrides) at a
+    /// part's new name
+    fn rename_part_references(&mut self, old_name: &str, new_name: &str) {
+        for outfit in &mut self.outfits {
+            if let Some(swap) = outfit.swaps.remove(old_name) {
+                outfit.swaps.insert(new_name.to_string(), swap);
+            }
+            for swap in outfit.swaps.values_mut() {
+                if let OutfitSwap::Part(ref mut name) = swap {
+                    if name == old_name {
+                        *name = new_name.to_string();
+                    }
+                }
+            }
+        }
+        let overrides = self.animations.iter_mut().flat_map(|a| {
+            std::iter::once(&mut a.z_overrides)
+                .chain(a.frames.iter_mut().map(|f| &mut f.z_overrides))
+        });
+        for z_overrides in overrides {
+            if let Some(z) = z_overrides.remove(old_name) {
+                z_overrides.insert(new_name.to_string(), z);
+            }
+        }
+    }
+}
+
+impl Project {
+    /// Id for a new part, never used by any part of the project before
+    pub fn new_part_id(&mut self) -> u64 {
+        let id = self.next_part_uid.max(self.max_part_id() + 1);
+        self.next_part_uid = id + 1;
+        id
+    }
+
+    /// Highest id among the characters' parts and the library, 0 if there are none
+    pub fn max_part_id(&self) -> u64 {
+        self.characters
+            .iter()
+            .flat_map(|c| c.parts.iter())
+            .chain(self.part_library.iter())
+            .map(|p| p.id)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Resolve a part id for a character: its own parts first, then linked library parts
+    pub fn resolve_part_id<'a>(
+        &'a self,
+        character: &'a Character,
+        part_id: u64,
+    ) -> Option<&'a Part> {
+        character.get_part_by_id(part_id).or_else(|| {
+            self.part_library
+                .iter()
+                .find(|p| p.id == part_id && character.uses_library_part(&p.name))
+        })
+    }
+
+    /// The part a placement draws, before outfit swaps
+    pub fn placed_part(&self, placed: &PlacedPart) -> Option<&Part> {
+        let character = self.get_character_by_id(placed.character_id)?;
+        self.resolve_part_id(character, placed.part_id)
+    }
+
+    /// The part and state a placement draws, before outfit swaps
+    pub fn placed_state(&self, placed: &PlacedPart) -> Option<(&Part, &State)> {
+        let part = self.placed_part(placed)?;
+        Some((part, part.get_state_by_id(placed.state_id)?))
+    }
+
+    /// Every placement in every animation
+    pub(super) fn placements_mut(&mut self) -> impl Iterator<Item = &mut PlacedPart> {
+        self.characters
+            .iter_mut()
+            .flat_map(|c| c.animations.iter_mut())
+            .flat_map(|a| a.frames.iter_mut())
+            .flat_map(|f| f.placed_parts.iter_mut())
+    }
+
+    /// Move a character's placements of one part to another part, keeping each state
+    /// by name where the new part has it and falling back to its first state
+    pub(super) fn repoint_placements(&mut self, character_id: u64, from: &Part, to: &Part) {
+        let state_ids: Vec<(u64, u64)> = from
+            .states
+            .iter()
+            .filter_map(|s| {
+                let new_state = to.get_state(&s.name).or_else(|| to.states.first())?;
+                Some((s.id, new_state.id))
+            })
+            .collect();
+        let (from_id, to_id) = (from.id, to.id);
+        for placed in self.placements_mut() {
+            if placed.character_id != character_id || placed.part_id != from_id {
+                continue;
+            }
+            placed.part_id = to_id;
+            if let Some(&(_, state_id)) = state_ids.iter().find(|(old, _)| *old == placed.state_id)
+            {
+                placed.state_id = state_id;
+            }
+        }
+    }
+
+    /// Rename one of a character's parts, or the library part it links. Placements refer
+    /// to the part by id and keep working; outfits, z overrides and layers named after
+    /// the part follow the new name.
+    pub fn rename_part(
+        &mut self,
+        char_name: &str,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), String> {
+        let character = self
+            .get_character(char_name)
+            .ok_or_else(|| format!("Character '{}' not found", char_name))?;
+        if old_name == new_name {
+            return Ok(());
+        }
+        if self.resolve_part(character, new_name).is_some() {
+            return Err(format!(
+                "{} already has a part named '{}'",
+                char_name, new_name
+            ));
+        }
+        let shared = character.uses_library_part(old_name);
+        let part_id = self
+            .resolve_part(character, old_name)
+            .map(|p| p.id)
+            .ok_or_else(|| format!("Part '{}' not found", old_name))?;
+
+        // Characters whose settings name the part: every user of a library part
+        let users: Vec<u64> = if shared {
+            self.rename_library_part(old_name, new_name)?;
+            self.characters
+                .iter()
+                .filter(|c| c.uses_library_part(new_name))
+                .map(|c| c.id)
+                .collect()
+        } else {
+            let character = self
+                .get_character_mut(char_name)
+                .ok_or("Character not found")?;
+            if let Some(part) = character.parts.iter_mut().find(|p| p.id == part_id) {
+                part.name = new_name.to_string();
+            }
+            vec![character.id]
+        };
+
+        for character in self.characters.iter_mut().filter(|c| users.contains(&c.id)) {
+            character.rename_part_references(old_name, new_name);
+        }
+        for placed in self.placements_mut() {
+            if users.contains(&placed.character_id)
+                && placed.part_id == part_id
+                && is_named_after(&placed.layer_name, old_name)
+            {
+                placed.layer_name = format!("{}{}", new_name, &placed.layer_name[old_name.len()..]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Rename a state of a character's part (shared with every user of a library part).
+    /// Placements refer to the state by id; outfits swapping to it follow the new name.
+    pub fn rename_state(
+        &mut self,
+        char_name: &str,
+        part_name: &str,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<(), String> {
+        let part = self
+            .get_part_mut(char_name, part_name)
+            .ok_or_else(|| format!("Part '{}' not found", part_name))?;
+        if old_name == new_name {
+            return Ok(());
+        }
+        if part.get_state(new_name).is_some() {
+            return Err(format!(
+                "'{}' already has a state named '{}'",
+                part_name, new_name
+            ));
+        }
+        let state = part
+            .states
+            .iter_mut()
+            .find(|s| s.name == old_name)
+            .ok_or_else(|| format!("State '{}' not found", old_name))?;
+        state.name = new_name.to_string();
+
+        let shared = self
+            .get_character(char_name)
+            .is_some_and(|c| c.uses_library_part(part_name));
+        for character in &mut self.characters {
+            let uses_part = if shared {
+                character.uses_library_part(part_name)
+            } else {
+                character.name == char_name
+            };
+            if !uses_part {
+                continue;
+            }
+            for outfit in &mut character.outfits {
+                if let Some(OutfitSwap::State(ref mut name)) = outfit.swaps.get_mut(part_name) {
+                    if name == old_name {
+                        *name = new_name.to_string();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Animation, Outfit, RotationMode};
+
+    #[test]
+    fn test_renames_keep_placements() {
+        let mut project = Project::new("Renames");
+        let mut knight = Character::new(1, "Knight");
+        let mut torso = Part::new(1, "torso");
+        let armored = torso.add_state(State::new("armored", RotationMode::Deg45));
+        knight.add_part(torso);
+        knight.add_part(Part::new(2, "cape"));
+        let mut outfit = Outfit::new("battle");
+        outfit.swaps.insert(
+            "torso".to_string(),
+            OutfitSwap::State("armored".to_string()),
+        );
+        outfit
+            .swaps
+            .insert("cape".to_string(), OutfitSwap::Part("torso".to_string()));
+        knight.outfits.push(outfit);
+
+        let mut walk = Animation::new("walk");
+        walk.frames[0].placed_parts = vec![
+            PlacedPart::new(1, 1, 1, armored).with_layer_name("torso"),
+            PlacedPart::new(2, 1, 1, 1).with_layer_name("torso 2"),
+            PlacedPart::new(3, 1, 2, 1).with_layer_name("torsos"),
+        ];
+        knight.add_animation(walk);
+        project.add_character(knight);
+
+        assert!(project.rename_part("Knight", "torso", "cape").is_err());
+        project.rename_part("Knight", "torso", "chest").unwrap();
+        project
+            .rename_state("Knight", "chest", "armored", "plated")
+            .unwrap();
+
+        let knight = project.get_character("Knight").unwrap();
+        let placed = &knight.get_animation("walk").unwrap().frames[0].placed_parts;
+        let (part, state) = project.placed_state(&placed[0]).unwrap();
+        assert_eq!(
+            (part.name.as_str(), state.name.as_str()),
+            ("chest", "plated")
+        );
+        let layers: Vec<&str> = placed.iter().map(|p| p.layer_name.as_str()).collect();
+        assert_eq!(layers, ["chest", "chest 2", "torsos"]);
+
+        let outfit = knight.get_outfit("battle").unwrap();
+        assert_eq!(
+            outfit.swaps.get("chest"),
+            Some(&OutfitSwap::State("plated".to_string()))
+        );
+        assert_eq!(
+            outfit.swaps.get("cape"),
+            Some(&OutfitSwap::Part("chest".to_string()))
+        );
+    }
+}
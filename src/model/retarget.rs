@@ -0,0 +1,299 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::{Character, PlacedPart, Project};
+
+/// Source part name -> target part name; None drops the part from the copy
+pub type PartMap = BTreeMap<String, Option<String>>;
+
+/// What a retargeted animation will be missing on the target character
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RetargetIssues {
+    /// Source parts that are dropped or map to no part of the target
+    pub missing_parts: Vec<String>,
+    /// (target part, state) pairs used by the animation that the target part lacks
+    pub missing_states: Vec<(String, String)>,
+}
+
+impl RetargetIssues {
+    pub fn is_empty(&self) -> bool {
+        self.missing_parts.is_empty() && self.missing_states.is_empty()
+    }
+}
+
+/// Whether a placement draws one of the character's own parts (not a nested animation)
+fn is_part_of(placed: &PlacedPart, character_id: u64) -> bool {
+    placed.character_id == character_id && placed.nested.is_none()
+}
+
+impl Project {
+    /// Names of the source character's parts placed anywhere in one of its animations
+    fn animation_part_names(&self, source: &Character, animation_name: &str) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        if let Some(animation) = source.get_animation(animation_name) {
+            for frame in &animation.frames {
+                for placed in frame
+                    .placed_parts
+                    .iter()
+                    .filter(|p| is_part_of(p, source.id))
+                {
+                    let Some(part) = self.resolve_part_id(source, placed.part_id) else {
+                        continue;
+                    };
+                    if !names.contains(&part.name) {
+                        names.push(part.name.clone());
+                    }
+                }
+            }
+        }
+        names.sort();
+        names
+    }
+
+    /// Map each part the animation uses to the target's part of the same name, if any
+    pub fn auto_part_map(
+        &self,
+        source_name: &str,
+        animation_name: &str,
+        target_name: &str,
+    ) -> PartMap {
+        let (Some(source), Some(target)) = (
+            self.get_character(source_name),
+            self.get_character(target_name),
+        ) else {
+            return PartMap::new();
+        };
+        self.animation_part_names(source, animation_name)
+            .into_iter()
+            .map(|name| {
+                let matched = self.resolve_part(target, &name).map(|p| p.name.clone());
+                (name, matched)
+            })
+            .collect()
+    }
+
+    /// Parts and states the copy would lack on the target with the given mapping
+    pub fn retarget_issues(
+        &self,
+        source_name: &str,
+        animation_name: &str,
+        target_name: &str,
+        part_map: &PartMap,
+    ) -> RetargetIssues {
+        let mut issues = RetargetIssues::default();
+        let (Some(source), Some(target)) = (
+            self.get_character(source_name),
+            self.get_character(target_name),
+        ) else {
+            return issues;
+        };
+        let Some(animation) = source.get_animation(animation_name) else {
+            return issues;
+        };
+
+        for frame in &animation.frames {
+            for placed in frame
+                .placed_parts
+                .iter()
+                .filter(|p| is_part_of(p, source.id))
+            {
+                let Some((source_part, source_state)) = self.placed_state(placed) else {
+                    continue;
+                };
+                let target_part = part_map
+                    .get(&source_part.name)
+                    .and_then(|m| m.as_deref())
+                    .and_then(|name| self.resolve_part(target, name));
+                match target_part {
+                    None => {
+                        if !issues.missing_parts.contains(&source_part.name) {
+                            issues.missing_parts.push(source_part.name.clone());
+                        }
+                    }
+                    Some(part) => {
+                        let key = (part.name.clone(), source_state.name.clone());
+                        if part.get_state(&source_state.name).is_none()
+                            && !issues.missing_states.contains(&key)
+                        {
+                            issues.missing_states.push(key);
+                        }
+                    }
+                }
+            }
+        }
+        issues
+    }
+
+    /// Copy an animation into another character, remapping its parts through `part_map`.
+    /// Parts of other characters and nested animations are kept as they are. With
+    /// `scale_positions`, positions are scaled by the ratio of the two canvas sizes.
+    /// Returns the name of the new animation and what it is missing on the target.
+    pub fn retarget_animation(
+        &mut self,
+        source_name: &str,
+        animation_name: &str,
+        target_name: &str,
+        part_map: &PartMap,
+        scale_positions: bool,
+    ) -> Result<(String, RetargetIssues), String> {
+        if source_name == target_name {
+            return Err("Choose a different character to copy to".to_string());
+        }
+        let source = self
+            .get_character(source_name)
+            .ok_or_else(|| format!("Character '{}' not found", source_name))?;
+        let target = self
+            .get_character(target_name)
+            .ok_or_else(|| format!("Character '{}' not found", target_name))?;
+        let mut animation = source
+            .get_animation(animation_name)
+            .cloned()
+            .ok_or_else(|| format!("Animation '{}' not found", animation_name))?;
+
+        let issues = self.retarget_issues(source_name, animation_name, target_name, part_map);
+        let (source_id, target_id) = (source.id, target.id);
+        let scale = if scale_positions {
+            (
+                target.canvas_size.0 as f32 / source.canvas_size.0 as f32,
+                target.canvas_size.1 as f32 / source.canvas_size.1 as f32,
+            )
+        } else {
+            (1.0, 1.0)
+        };
+
+        let mut name = animation.name.clone();
+        let mut n = 2;
+        while target.get_animation(&name).is_some() {
+            name = format!("{} ({})", animation.name, n);
+            n += 1;
+        }
+        animation.name = name.clone();
+
+        // Source part id -> (source name, target part id and name, source state id -> target
+        // state id). States the target part lacks map to 0 and show up in `issues`.
+        let mut part_ids = HashMap::new();
+        for part in self.character_parts(source) {
+            let Some(target_part) = part_map
+                .get(&part.name)
+                .and_then(|m| m.as_deref())
+                .and_then(|name| self.resolve_part(target, name))
+            else {
+                continue;
+            };
+            let states: HashMap<u64, u64> = part
+                .states
+                .iter()
+                .map(|s| (s.id, target_part.get_state(&s.name).map_or(0, |t| t.id)))
+                .collect();
+            let names = (part.name.clone(), target_part.name.clone());
+            part_ids.insert(part.id, (names, target_part.id, states));
+        }
+
+        let mapped = |part_name: &str| part_map.get(part_name).cloned().flatten();
+        animation.z_overrides = animation
+            .z_overrides
+            .into_iter()
+            .filter_map(|(part_name, z)| mapped(&part_name).map(|n| (n, z)))
+            .collect();
+
+        for frame in &mut animation.frames {
+            frame
+                .placed_parts
+                .retain(|p| !is_part_of(p, source_id) || part_ids.contains_key(&p.part_id));
+            for placed in &mut frame.placed_parts {
+                if !is_part_of(placed, source_id) {
+                    continue;
+                }
+                if let Some(((old_name, new_name), part_id, states)) = part_ids.get(&placed.part_id)
+                {
+                    if placed.layer_name == *old_name {
+                        placed.layer_name = new_name.clone();
+                    }
+                    placed.part_id = *part_id;
+                    placed.state_id = states.get(&placed.state_id).copied().unwrap_or(0);
+                }
+                placed.character_id = target_id;
+                placed.position = (
+                    (placed.position.0 * scale.0).round(),
+                    (placed.position.1 * scale.1).round(),
+                );
+            }
+            frame.z_overrides = std::mem::take(&mut frame.z_overrides)
+                .into_iter()
+                .filter_map(|(part_name, z)| mapped(&part_name).map(|n| (n, z)))
+                .collect();
+            for placed in &mut frame.placed_parts {
+                placed.id = self.next_id();
+            }
+        }
+
+        if let Some(target) = self.get_character_mut(target_name) {
+            target.add_animation(animation);
+        }
+        Ok((name, issues))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Animation, Part, RotationMode, State};
+
+    #[test]
+    fn test_retarget_animation() {
+        let mut project = Project::new("Retarget");
+        let mut hero = Character::new(1, "Hero");
+        hero.add_part(Part::new(1, "head"));
+        let mut cape = Part::new(2, "cape");
+        let flutter = cape.add_state(State::new("flutter", RotationMode::Deg45));
+        hero.add_part(cape);
+        let mut walk = Animation::new("walk");
+        let mut head = PlacedPart::new(1, 1, 1, 1).with_layer_name("head");
+        head.position = (10.0, 20.0);
+        walk.frames[0].placed_parts.push(head);
+        let mut cape = PlacedPart::new(2, 1, 2, flutter).with_layer_name("cape");
+        cape.position = (4.0, 4.0);
+        walk.frames[0].placed_parts.push(cape);
+        hero.add_animation(walk);
+        project.add_character(hero);
+
+        let mut villain = Character::new(2, "Villain");
+        villain.canvas_size = (128, 128);
+        villain.add_part(Part::new(3, "head"));
+        villain.add_part(Part::new(4, "cloak"));
+        project.add_character(villain);
+        project.next_part_id = 3;
+
+        let mut map = project.auto_part_map("Hero", "walk", "Villain");
+        assert_eq!(map.get("head"), Some(&Some("head".to_string())));
+        assert_eq!(map.get("cape"), Some(&None));
+        assert_eq!(
+            project
+                .retarget_issues("Hero", "walk", "Villain", &map)
+                .missing_parts,
+            ["cape"]
+        );
+
+        map.insert("cape".to_string(), Some("cloak".to_string()));
+        let (name, issues) = project
+            .retarget_animation("Hero", "walk", "Villain", &map, true)
+            .unwrap();
+        assert_eq!(name, "walk");
+        assert_eq!(
+            issues.missing_states,
+            [("cloak".to_string(), "flutter".to_string())]
+        );
+
+        let villain = project.get_character("Villain").unwrap();
+        let parts = &villain.get_animation("walk").unwrap().frames[0].placed_parts;
+        assert!(parts.iter().all(|p| p.character_id == 2));
+        assert_eq!(parts[0].position, (20.0, 40.0));
+        assert_eq!(parts[1].part_id, 4);
+        assert_eq!(parts[1].layer_name, "cloak");
+
+        // A second copy gets a fresh name
+        let (name, _) = project
+            .retarget_animation("Hero", "walk", "Villain", &map, false)
+            .unwrap();
+        assert_eq!(name, "walk (2)");
+    }
+}
@@ -0,0 +1,149 @@
+use std::ops::Range;
+
+use super::{Animation, Frame};
+
+/// Give every placed part in the frames a fresh id from the project's counter
+fn renumber(frames: &mut [Frame], next_id: &mut u64) {
+    for frame in frames {
+        for placed in &mut frame.placed_parts {
+            placed.id = *next_id;
+            *next_id += 1;
+        }
+    }
+}
+
+/// Frame editing used by the timeline. Ranges are clamped to the animation;
+/// methods taking `next_id` copy frames and need the project's placement id counter.
+impl Animation {
+    fn clamp_range(&self, range: Range<usize>) -> Range<usize> {
+        range.start.min(self.frames.len())..range.end.min(self.frames.len())
+    }
+
+    pub fn insert_frame(&mut self, index: usize) {
+        let index = index.min(self.frames.len());
+        self.frames.insert(index, Frame::new(100));
+    }
+
+    /// Move a run of frames so it starts where frame `to` was (`to` counts positions
+    /// before the move, so `frames.len()` moves them to the end). Returns the new range.
+    pub fn move_frames(&mut self, range: Range<usize>, to: usize) -> Range<usize> {
+        let range = self.clamp_range(range);
+        let moved: Vec<Frame> = self.frames.drain(range.clone()).collect();
+        let to = if to > range.end {
+            to - range.len()
+        } else {
+            to.min(range.start)
+        };
+        let to = to.min(self.frames.len());
+        let len = moved.len();
+        self.frames.splice(to..to, moved);
+        to..to + len
+    }
+
+    pub fn reverse_frames(&mut self, range: Range<usize>) {
+        let range = self.clamp_range(range);
+        self.frames[range].reverse();
+    }
+
+    /// Insert a copy of the frames right after them. Returns the range of the copy.
+    pub fn duplicate_frames(&mut self, range: Range<usize>, next_id: &mut u64) -> Range<usize> {
+        let range = self.clamp_range(range);
+        let mut copies = self.frames[range.clone()].to_vec();
+        renumber(&mut copies, next_id);
+        let len = copies.len();
+        self.frames.splice(range.end..range.end, copies);
+        range.end..range.end + len
+    }
+
+    /// Insert the frames in reverse after them, without repeating either end, so the
+    /// run plays forward then back and loops smoothly (a b c d -> a b c d c b)
+    pub fn append_ping_pong(&mut self, range: Range<usize>, next_id: &mut u64) -> usize {
+        let range = self.clamp_range(range);
+        if range.len() < 3 {
+            return 0;
+        }
+        let mut copies: Vec<Frame> = self.frames[range.start + 1..range.end - 1]
+            .iter()
+            .rev()
+            .cloned()
+            .collect();
+        renumber(&mut copies, next_id);
+        let count = copies.len();
+        self.frames.splice(range.end..range.end, copies);
+        count
+    }
+
+    pub fn set_frames_duration(&mut self, range: Range<usize>, duration_ms: u32) {
+        let range = self.clamp_range(range);
+        for frame in &mut self.frames[range] {
+            frame.duration_ms = duration_ms;
+        }
+    }
+
+    /// Remove frames, always keeping at least one. Returns how many were removed.
+    pub fn delete_frames(&mut self, range: Range<usize>) -> usize {
+        // A loaded file can have an animation without frames
+        if self.frames.is_empty() {
+            return 0;
+        }
+        let mut range = self.clamp_range(range);
+        if range.len() >= self.frames.len() {
+            range.end = range.start + self.frames.len() - 1;
+        }
+        let count = range.len();
+        self.frames.drain(range);
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Animation whose frame durations identify the frames: 1, 2, 3, ...
+    fn numbered(count: u32) -> Animation {
+        let mut animation = Animation::new("test");
+        animation.frames = (1..=count).map(Frame::new).collect();
+        animation
+    }
+
+    fn order(animation: &Animation) -> Vec<u32> {
+        animation.frames.iter().map(|f| f.duration_ms).collect()
+    }
+
+    #[test]
+    fn test_move_frames() {
+        let mut animation = numbered(5);
+        assert_eq!(animation.move_frames(0..2, 4), 2..4);
+        assert_eq!(order(&animation), [3, 4, 1, 2, 5]);
+
+        assert_eq!(animation.move_frames(3..5, 0), 0..2);
+        assert_eq!(order(&animation), [2, 5, 3, 4, 1]);
+
+        assert_eq!(animation.move_frames(0..1, 5), 4..5);
+        assert_eq!(order(&animation), [5, 3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn test_range_operations() {
+        let mut animation = numbered(4);
+        let mut next_id = 1;
+        assert_eq!(animation.append_ping_pong(0..4, &mut next_id), 2);
+        assert_eq!(order(&animation), [1, 2, 3, 4, 3, 2]);
+
+        animation.reverse_frames(1..3);
+        assert_eq!(order(&animation), [1, 3, 2, 4, 3, 2]);
+
+        assert_eq!(animation.duplicate_frames(0..2, &mut next_id), 2..4);
+        assert_eq!(order(&animation), [1, 3, 1, 3, 2, 4, 3, 2]);
+
+        animation.set_frames_duration(6..20, 50);
+        assert_eq!(order(&animation), [1, 3, 1, 3, 2, 4, 50, 50]);
+
+        assert_eq!(animation.delete_frames(0..100), 7);
+        assert_eq!(order(&animation), [50]);
+
+        animation.frames.clear();
+        assert_eq!(animation.delete_frames(0..1), 0);
+    }
+}
@@ -0,0 +1,441 @@
+use super::references::is_named_after;
+use super::{Angle, IssueLocation, PlacedPart, Project};
+
+/// What Find Usages looks for: a character's part, one of its states, or a single
+/// rotation of a state
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageQuery {
+    pub character: String,
+    pub part: String,
+    pub state: Option<String>,
+    pub angle: Option<Angle>,
+}
+
+impl UsageQuery {
+    pub fn part(character: &str, part: &str) -> Self {
+        Self {
+            character: character.to_string(),
+            part: part.to_string(),
+            state: None,
+            angle: None,
+        }
+    }
+
+    pub fn state(character: &str, part: &str, state: &str) -> Self {
+        Self {
+            state: Some(state.to_string()),
+            ..Self::part(character, part)
+        }
+    }
+
+    pub fn rotation(character: &str, part: &str, state: &str, angle: Angle) -> Self {
+        Self {
+            angle: Some(angle),
+            ..Self::state(character, part, state)
+        }
+    }
+
+    /// "head", "head / default" or "head / default at 45°"
+    pub fn describe(&self) -> String {
+        match (&self.state, self.angle) {
+            (None, _) => self.part.clone(),
+            (Some(state), None) => format!("{} / {}", self.part, state),
+            (Some(state), Some(angle)) => format!("{} / {} at {}°", self.part, state, angle),
+        }
+    }
+}
+
+/// A layer drawing what was looked for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Usage {
+    pub location: IssueLocation,
+    /// "Hero / walk / frame 3: sword (iron, 45°)"
+    pub description: String,
+}
+
+/// Animations a find and replace looks in, by character name and animation index
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ReplaceScope {
+    #[default]
+    Project,
+    Animations(Vec<(String, usize)>),
+}
+
+impl ReplaceScope {
+    pub fn contains(&self, character: &str, animation: usize) -> bool {
+        match self {
+            ReplaceScope::Project => true,
+            ReplaceScope::Animations(animations) => animations
+                .iter()
+                .any(|(c, a)| c == character && *a == animation),
+        }
+    }
+}
+
+/// The ids a query resolves to
+struct QueryIds {
+    /// Characters whose placements count. A state of a library part is shared by every
+    /// character linking it; the part itself is only the queried character's to delete.
+    character_ids: Vec<u64>,
+    part_id: u64,
+    state_id: Option<u64>,
+    angle: Option<Angle>,
+}
+
+impl QueryIds {
+    fn matches(&self, placed: &PlacedPart) -> bool {
+        placed.nested.is_none()
+            && self.character_ids.contains(&placed.character_id)
+            && placed.part_id == self.part_id
+            && self.state_id.is_none_or(|id| placed.state_id == id)
+            && self.angle.is_none_or(|a| placed.rotation == a)
+    }
+}
+
+impl Project {
+    /// Ids of the part (and state) a query is about, or None if they don't exist
+    fn query_ids(&self, query: &UsageQuery) -> Option<QueryIds> {
+        let character = self.get_character(&query.character)?;
+        let part = self.resolve_part(character, &query.part)?;
+        let state_id = match query.state {
+            Some(ref state) => Some(part.get_state(state)?.id),
+            None => None,
+        };
+        let character_ids = if state_id.is_some() && character.uses_library_part(&query.part) {
+            self.characters
+                .iter()
+                .filter(|c| c.uses_library_part(&query.part))
+                .map(|c| c.id)
+                .collect()
+        } else {
+            vec![character.id]
+        };
+        Some(QueryIds {
+            character_ids,
+            part_id: part.id,
+            state_id,
+            angle: query.angle,
+        })
+    }
+
+    /// Every layer, in any character's animations, that draws a character's part, state
+    /// or rotation. States and rotations of a library part count for every character
+    /// linking it.
+    pub fn find_usages(&self, query: &UsageQuery) -> Vec<Usage> {
+        let Some(ids) = self.query_ids(query) else {
+            return Vec::new();
+        };
+        let mut usages = Vec::new();
+        for character in &self.characters {
+            for (animation_index, animation) in character.animations.iter().enumerate() {
+                for (frame_index, frame) in animation.frames.iter().enumerate() {
+                    let matching = frame.placed_parts.iter().filter(|p| ids.matches(p));
+                    for placed in matching {
+                        let state = self
+                            .placed_state(placed)
+                            .map(|(_, state)| state.name.as_str())
+                            .unwrap_or("?");
+                        usages.push(Usage {
+                            location: IssueLocation::Layer {
+                                character: character.name.clone(),
+                                animation: animation_index,
+                                frame: frame_index,
+                                layer_id: placed.id,
+                            },
+                            description: format!(
+                                "{} / {} / frame {}: {} ({}, {}°)",
+                                character.name,
+                                animation.name,
+                                frame_index + 1,
+                                placed.layer_name,
+                                state,
+                                placed.rotation
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+        usages
+    }
+
+    /// Point every layer found by a query at another part and state of the same
+    /// character, so deleting the original leaves nothing broken. Layers named after
+    /// the old part take the new part's name. Returns how many layers changed.
+    pub fn replace_usages(
+        &mut self,
+        query: &UsageQuery,
+        part_name: &str,
+        state_name: &str,
+    ) -> Result<usize, String> {
+        let ids = self
+            .query_ids(query)
+            .ok_or_else(|| format!("'{}' not found", query.describe()))?;
+        let character = self
+            .get_character(&query.character)
+            .ok_or_else(|| format!("Character '{}' not found", query.character))?;
+        let part = self
+            .resolve_part(character, part_name)
+            .ok_or_else(|| format!("Part '{}' not found", part_name))?;
+        let state = part
+            .get_state(state_name)
+            .ok_or_else(|| format!("'{}' has no state named '{}'", part_name, state_name))?;
+        let (part_id, state_id) = (part.id, state.id);
+        if part_id != ids.part_id && ids.character_ids.len() > 1 {
+            return Err(format!(
+                "'{}' is shared; pick one of its states",
+                query.part
+            ));
+        }
+
+        let mut replaced = 0;
+        for placed in self.placements_mut() {
+            if !ids.matches(placed) {
+                continue;
+            }
+            if part_id != ids.part_id && is_named_after(&placed.layer_name, &query.part) {
+                let suffix = &placed.layer_name[query.part.len()..];
+                placed.layer_name = format!("{}{}", part_name, suffix);
+            }
+            placed.part_id = part_id;
+            placed.state_id = state_id;
+            replaced += 1;
+        }
+        Ok(replaced)
+    }
+
+    /// The usages of a query within some animations
+    pub fn find_usages_in(&self, query: &UsageQuery, scope: &ReplaceScope) -> Vec<Usage> {
+        self.find_usages(query)
+            .into_iter()
+            .filter(|usage| match usage.location {
+                IssueLocation::Layer {
+                    ref character,
+                    animation,
+                    ..
+                } => scope.contains(character, animation),
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Ids a state replacement matches, the id of the new state and the angles it has
+    fn replace_state_target(
+        &self,
+        query: &UsageQuery,
+        state_name: &str,
+    ) -> Result<(QueryIds, u64, Vec<Angle>), String> {
+        let ids = self
+            .query_ids(query)
+            .ok_or_else(|| format!("'{}' not found", query.describe()))?;
+        let state = self
+            .get_part(&query.character, &query.part)
+            .and_then(|part| part.get_state(state_name))
+            .ok_or_else(|| format!("'{}' has no state named '{}'", query.part, state_name))?;
+        Ok((ids, state.id, state.angles()))
+    }
+
+    /// How many layers a state replacement would skip because the new state has no
+    /// rotation at the angle they would end up at
+    pub fn replace_state_skips(
+        &self,
+        query: &UsageQuery,
+        scope: &ReplaceScope,
+        state_name: &str,
+        rotation: Option<Angle>,
+    ) -> usize {
+        let Ok((ids, _, angles)) = self.replace_state_target(query, state_name) else {
+            return 0;
+        };
+        let mut skipped = 0;
+        for character in &self.characters {
+            for (index, animation) in character.animations.iter().enumerate() {
+                if !scope.contains(&character.name, index) {
+                    continue;
+                }
+                let placements = animation.frames.iter().flat_map(|f| f.placed_parts.iter());
+                skipped += placements
+                    .filter(|p| ids.matches(p))
+                    .filter(|p| !angles.contains(&rotation.unwrap_or(p.rotation)))
+                    .count();
+            }
+        }
+        skipped
+    }
+
+    /// Switch every layer a query finds within some animations to another state of the
+    /// same part, and to another angle if one is given. Layers the new state has no
+    /// rotation for at their angle are left alone. Returns how many layers changed and
+    /// how many were skipped.
+    pub fn replace_state(
+        &mut self,
+        query: &UsageQuery,
+        scope: &ReplaceScope,
+        state_name: &str,
+        rotation: Option<Angle>,
+    ) -> Result<(usize, usize), String> {
+        let (ids, state_id, angles) = self.replace_state_target(query, state_name)?;
+
+        let (mut replaced, mut skipped) = (0, 0);
+        for character in &mut self.characters {
+            for (index, animation) in character.animations.iter_mut().enumerate() {
+                if !scope.contains(&character.name, index) {
+                    continue;
+                }
+                let placements = animation
+                    .frames
+                    .iter_mut()
+                    .flat_map(|f| f.placed_parts.iter_mut());
+                for placed in placements.filter(|p| ids.matches(p)) {
+                    let angle = rotation.unwrap_or(placed.rotation);
+                    if !angles.contains(&angle) {
+                        skipped += 1;
+                        continue;
+                    }
+                    placed.state_id = state_id;
+                    placed.rotation = angle;
+                    replaced += 1;
+                }
+            }
+        }
+        Ok((replaced, skipped))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Animation, Character, Part, RotationMode, State};
+
+    #[test]
+    fn test_find_and_replace_usages() {
+        let mut project = Project::new("Usages");
+        let mut knight = Character::new(1, "Knight");
+        let mut sword = Part::new(1, "sword");
+        let iron = sword.add_state(State::new("iron", RotationMode::Deg45));
+        knight.add_part(sword);
+        knight.add_part(Part::new(2, "axe"));
+
+        let mut walk = Animation::new("walk");
+        walk.add_frame();
+        let mut raised = PlacedPart::new(3, 1, 1, iron).with_layer_name("sword");
+        raised.rotation = Angle::from_degrees(45.0);
+        walk.frames[0]
+            .placed_parts
+            .push(PlacedPart::new(1, 1, 1, iron).with_layer_name("sword"));
+        walk.frames[0]
+            .placed_parts
+            .push(PlacedPart::new(2, 1, 1, 1).with_layer_name("sword 2"));
+        walk.frames[1].placed_parts.push(raised);
+        knight.add_animation(walk);
+        project.add_character(knight);
+
+        assert_eq!(
+            project
+                .find_usages(&UsageQuery::part("Knight", "sword"))
+                .len(),
+            3
+        );
+        let iron_query = UsageQuery::state("Knight", "sword", "iron");
+        let usages = project.find_usages(&iron_query);
+        assert_eq!(usages.len(), 2);
+        assert_eq!(
+            usages[1].description,
+            "Knight / walk / frame 2: sword (iron, 45°)"
+        );
+        let raised = UsageQuery::rotation("Knight", "sword", "iron", Angle::from_degrees(45.0));
+        assert_eq!(project.find_usages(&raised).len(), 1);
+
+        assert_eq!(
+            project.replace_usages(&iron_query, "sword", "default"),
+            Ok(2)
+        );
+        assert!(project.find_usages(&iron_query).is_empty());
+        let sword = UsageQuery::part("Knight", "sword");
+        assert_eq!(project.replace_usages(&sword, "axe", "default"), Ok(3));
+        let knight = project.get_character("Knight").unwrap();
+        let layers: Vec<&str> = knight.animations[1].frames[0]
+            .placed_parts
+            .iter()
+            .map(|p| p.layer_name.as_str())
+            .collect();
+        assert_eq!(layers, ["axe", "axe 2"]);
+        assert!(project.replace_usages(&sword, "axe", "missing").is_err());
+    }
+
+    #[test]
+    fn test_replace_state_in_scope() {
+        let mut project = Project::new("Swords");
+        let mut knight = Character::new(1, "Knight");
+        let mut sword = Part::new(1, "sword");
+        let iron = sword.add_state(State::new("iron", RotationMode::Deg45));
+        sword.add_state(State::new("gold", RotationMode::Deg45));
+        knight.add_part(sword);
+        for name in ["walk", "attack"] {
+            let mut animation = Animation::new(name);
+            animation.frames[0]
+                .placed_parts
+                .push(PlacedPart::new(1, 1, 1, iron));
+            knight.add_animation(animation);
+        }
+        project.add_character(knight);
+
+        let query = UsageQuery::state("Knight", "sword", "iron");
+        let attack = ReplaceScope::Animations(vec![("Knight".to_string(), 2)]);
+        assert_eq!(
+            project.find_usages_in(&query, &ReplaceScope::Project).len(),
+            2
+        );
+        assert_eq!(project.find_usages_in(&query, &attack).len(), 1);
+
+        let west = Angle::from_degrees(180.0);
+        assert_eq!(
+            project.replace_state(&query, &attack, "gold", Some(west)),
+            Ok((1, 0))
+        );
+        assert_eq!(project.find_usages(&query).len(), 1);
+        let gold = UsageQuery::rotation("Knight", "sword", "gold", west);
+        assert_eq!(project.find_usages(&gold).len(), 1);
+        assert!(project
+            .replace_state(&query, &attack, "bronze", None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_replace_state_skips_missing_angles() {
+        let mut project = Project::new("Shields");
+        let mut knight = Character::new(1, "Knight");
+        let mut shield = Part::new(1, "shield");
+        let round = shield.add_state(State::new("round", RotationMode::Deg45));
+        shield.add_state(State::new("square", RotationMode::Deg90));
+        knight.add_part(shield);
+        let mut walk = Animation::new("walk");
+        walk.add_frame();
+        for (frame, degrees) in [(0, 45.0), (1, 90.0)] {
+            let mut placed = PlacedPart::new(frame as u64 + 1, 1, 1, round);
+            placed.rotation = Angle::from_degrees(degrees);
+            walk.frames[frame].placed_parts.push(placed);
+        }
+        knight.add_animation(walk);
+        project.add_character(knight);
+
+        // "square" has no 45° rotation, so that layer keeps "round"
+        let query = UsageQuery::state("Knight", "shield", "round");
+        let scope = ReplaceScope::Project;
+        assert_eq!(
+            project.replace_state_skips(&query, &scope, "square", None),
+            1
+        );
+        assert_eq!(
+            project.replace_state(&query, &scope, "square", None),
+            Ok((1, 1))
+        );
+        let kept = UsageQuery::rotation("Knight", "shield", "round", Angle::from_degrees(45.0));
+        assert_eq!(project.find_usages(&kept).len(), 1);
+        let east = Some(Angle::ZERO);
+        assert_eq!(
+            project.replace_state_skips(&query, &scope, "square", east),
+            0
+        );
+    }
+}
@@ -0,0 +1,485 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::{Angle, Character, OutfitSwap, Part, PlacedPart, Project, Rotation, State};
+
+/// Where a problem is, so the report can jump to it
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueLocation {
+    /// A layer of a frame, by the placed part's id
+    Layer {
+        character: String,
+        animation: usize,
+        frame: usize,
+        layer_id: u64,
+    },
+    /// A frame as a whole (its reference image)
+    Frame {
+        character: String,
+        animation: usize,
+        frame: usize,
+    },
+    /// A part, and optionally one of its states, in the character editor
+    Part {
+        character: String,
+        part: String,
+        state: Option<String>,
+    },
+    /// A character's settings in the character editor (outfits, linked parts)
+    Character(String),
+}
+
+/// A one-click repair offered for a problem
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueFix {
+    RemoveLayer,
+    /// Point the layer at another character that has its part
+    UseCharacter {
+        id: u64,
+        name: String,
+    },
+    UseState {
+        id: u64,
+        name: String,
+    },
+    UseRotation(Angle),
+    RemoveReference,
+    UnlinkLibraryPart(String),
+    RemoveOutfitSwap {
+        outfit: String,
+        part: String,
+    },
+}
+
+impl IssueFix {
+    /// Button text
+    pub fn label(&self) -> String {
+        match self {
+            IssueFix::RemoveLayer => "Remove layer".to_string(),
+            IssueFix::UseCharacter { name, .. } => format!("Use {}", name),
+            IssueFix::UseState { name, .. } => format!("Use '{}'", name),
+            IssueFix::UseRotation(angle) => format!("Use {}°", angle),
+            IssueFix::RemoveReference => "Remove reference".to_string(),
+            IssueFix::UnlinkLibraryPart(_) => "Unlink".to_string(),
+            IssueFix::RemoveOutfitSwap { .. } => "Remove swap".to_string(),
+        }
+    }
+}
+
+/// A broken reference or suspicious asset found by `Project::validate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub location: IssueLocation,
+    pub message: String,
+    pub fix: Option<IssueFix>,
+}
+
+/// Part images wider or taller than this many canvases are reported as unusually large
+const LARGE_IMAGE_CANVASES: u32 = 2;
+
+impl Project {
+    /// Find everything that would silently render nothing or look wrong in an export:
+    /// layers pointing at missing characters, parts, states or images, empty states,
+    /// missing reference images and oversized part images
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        // Library parts shared by several characters are checked once
+        let mut checked_library_parts = HashSet::new();
+
+        for character in &self.characters {
+            self.validate_links(character, &mut issues);
+            for part in self.character_parts(character) {
+                if character.uses_library_part(&part.name)
+                    && !checked_library_parts.insert(part.name.clone())
+                {
+                    continue;
+                }
+                self.validate_part(character, part, &mut issues);
+            }
+
+            for (anim_idx, animation) in character.animations.iter().enumerate() {
+                for (frame_idx, frame) in animation.frames.iter().enumerate() {
+                    for placed in &frame.placed_parts {
+                        let location = IssueLocation::Layer {
+                            character: character.name.clone(),
+                            animation: anim_idx,
+                            frame: frame_idx,
+                            layer_id: placed.id,
+                        };
+                        if let Some((message, fix)) = self.check_layer(character, placed) {
+                            let message = format!(
+                                "{} / {} frame {}: {}",
+                                character.name,
+                                animation.name,
+                                frame_idx + 1,
+                                message
+                            );
+                            issues.push(Issue {
+                                location,
+                                message,
+                                fix,
+                            });
+                        }
+                    }
+
+                    let Some(ref reference) = frame.reference else {
+                        continue;
+                    };
+                    if !Path::new(&reference.file_path).exists() {
+                        issues.push(Issue {
+                            location: IssueLocation::Frame {
+                                character: character.name.clone(),
+                                animation: anim_idx,
+                                frame: frame_idx,
+                            },
+                            message: format!(
+                                "{} / {} frame {}: reference image {} is missing",
+                                character.name,
+                                animation.name,
+                                frame_idx + 1,
+                                reference.file_path
+                            ),
+                            fix: Some(IssueFix::RemoveReference),
+                        });
+                    }
+                }
+            }
+        }
+        issues
+    }
+
+    /// Library links and outfit swaps naming parts or states that no longer exist
+    fn validate_links(&self, character: &Character, issues: &mut Vec<Issue>) {
+        for name in &character.library_parts {
+            if character.get_part(name).is_none() && self.library_part(name).is_none() {
+                issues.push(Issue {
+                    location: IssueLocation::Character(character.name.clone()),
+                    message: format!(
+                        "{} links library part '{}', which no longer exists",
+                        character.name, name
+                    ),
+                    fix: Some(IssueFix::UnlinkLibraryPart(name.clone())),
+                });
+            }
+        }
+
+        for outfit in &character.outfits {
+            for (part_name, swap) in &outfit.swaps {
+                let problem = match (self.resolve_part(character, part_name), swap) {
+                    (None, _) => Some(format!("part '{}' no longer exists", part_name)),
+                    (Some(part), OutfitSwap::State(state)) if part.get_state(state).is_none() => {
+                        Some(format!("'{}' has no state '{}'", part_name, state))
+                    }
+                    (Some(_), OutfitSwap::Part(other))
+                        if self.resolve_part(character, other).is_none() =>
+                    {
+                        Some(format!("replacement part '{}' no longer exists", other))
+                    }
+                    _ => None,
+                };
+                if let Some(problem) = problem {
+                    issues.push(Issue {
+                        location: IssueLocation::Character(character.name.clone()),
+                        message: format!(
+                            "{} outfit '{}': {}",
+                            character.name, outfit.name, problem
+                        ),
+                        fix: Some(IssueFix::RemoveOutfitSwap {
+                            outfit: outfit.name.clone(),
+                            part: part_name.clone(),
+                        }),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Empty states, images missing from the store and images much larger than the canvas
+    fn validate_part(&self, character: &Character, part: &Part, issues: &mut Vec<Issue>) {
+        let (canvas_w, canvas_h) = character.canvas_size;
+        for state in &part.states {
+            let location = IssueLocation::Part {
+                character: character.name.clone(),
+                part: part.name.clone(),
+                state: Some(state.name.clone()),
+            };
+            let mut issue = |message: String| {
+                issues.push(Issue {
+                    location: location.clone(),
+                    message: format!("{} / {}: {}", part.name, state.name, message),
+                    fix: None,
+                })
+            };
+
+            if state.rotations.values().all(|r| r.image.is_none()) {
+                issue("state has no images".to_string());
+                continue;
+            }
+            let mut angles: Vec<&Angle> = state.rotations.keys().collect();
+            angles.sort();
+            for angle in angles {
+                let Some(ref hash) = state.rotations[angle].image else {
+                    continue;
+                };
+                match self.images.decoded(hash) {
+                    Err(_) if !self.images.contains(hash) => {
+                        issue(format!("the {}° image is missing from the project", angle))
+                    }
+                    Err(e) => issue(format!("the {}° image can't be read: {}", angle, e)),
+                    Ok(image)
+                        if image.width() > canvas_w * LARGE_IMAGE_CANVASES
+                            || image.height() > canvas_h * LARGE_IMAGE_CANVASES =>
+                    {
+                        issue(format!(
+                            "the {}° image is {}×{}, much larger than the {}×{} canvas",
+                            angle,
+                            image.width(),
+                            image.height(),
+                            canvas_w,
+                            canvas_h
+                        ))
+                    }
+                    Ok(_) => {}
+                }
+            }
+        }
+    }
+
+    /// Why a layer renders nothing, and how to fix it, or None if it draws fine
+    fn check_layer(
+        &self,
+        owner: &Character,
+        placed: &PlacedPart,
+    ) -> Option<(String, Option<IssueFix>)> {
+        let layer = &placed.layer_name;
+        if let Some(ref nested) = placed.nested {
+            return self.nested_animation(nested).is_none().then(|| {
+                let message = format!(
+                    "layer '{}' plays animation '{}', which no longer exists",
+                    layer, nested.animation_name
+                );
+                (message, Some(IssueFix::RemoveLayer))
+            });
+        }
+
+        let Some(character) = self.get_character_by_id(placed.character_id) else {
+            // Suggest the character the old name referred to, or the frame's own character
+            let fix = self
+                .get_character(&placed.character_name)
+                .into_iter()
+                .chain([owner])
+                .find(|c| self.resolve_part_id(c, placed.part_id).is_some())
+                .map(|c| IssueFix::UseCharacter {
+                    id: c.id,
+                    name: c.name.clone(),
+                })
+                .unwrap_or(IssueFix::RemoveLayer);
+            let message = format!(
+                "layer '{}' belongs to a character that no longer exists",
+                layer
+            );
+            return Some((message, Some(fix)));
+        };
+        let Some(part) = self.resolve_part_id(character, placed.part_id) else {
+            let message = format!(
+                "layer '{}' uses a part that {} no longer has",
+                layer, character.name
+            );
+            return Some((message, Some(IssueFix::RemoveLayer)));
+        };
+        let Some(state) = part.get_state_by_id(placed.state_id) else {
+            let fix = part
+                .states
+                .first()
+                .map(|s| IssueFix::UseState {
+                    id: s.id,
+                    name: s.name.clone(),
+                })
+                .unwrap_or(IssueFix::RemoveLayer);
+            let message = format!(
+                "layer '{}' uses a state that part '{}' no longer has",
+                layer, part.name
+            );
+            return Some((message, Some(fix)));
+        };
+
+        // An angle left empty shows its mirrored angle's image, if that one is drawn
+        let has_image = |(rotation, _): (&Rotation, bool)| self.rotation_image(rotation).is_some();
+        if state.get_rotation(placed.rotation).is_some_and(has_image) {
+            return None;
+        }
+        let message = format!(
+            "layer '{}' shows {} / {} at {}°, which has no image",
+            layer, part.name, state.name, placed.rotation
+        );
+        let fix = nearest_drawn_angle(self, state, placed.rotation).map(IssueFix::UseRotation);
+        Some((message, fix))
+    }
+
+    /// Apply a fix, returning a status message
+    pub fn apply_fix(&mut self, issue: &Issue) -> Result<String, String> {
+        let fix = issue.fix.as_ref().ok_or("Nothing to fix automatically")?;
+        match (&issue.location, fix) {
+            (
+                IssueLocation::Layer {
+                    character,
+                    animation,
+                    frame,
+                    layer_id,
+                },
+                fix,
+            ) => {
+                let frame = self
+                    .get_character_mut(character)
+                    .and_then(|c| c.animations.get_mut(*animation))
+                    .and_then(|a| a.frames.get_mut(*frame))
+                    .ok_or("Frame not found")?;
+                if let IssueFix::RemoveLayer = fix {
+                    let before = frame.placed_parts.len();
+                    frame.placed_parts.retain(|p| p.id != *layer_id);
+                    if frame.placed_parts.len() == before {
+                        return Err("Layer not found".to_string());
+                    }
+                    return Ok("Removed layer".to_string());
+                }
+                let placed = frame
+                    .placed_parts
+                    .iter_mut()
+                    .find(|p| p.id == *layer_id)
+                    .ok_or("Layer not found")?;
+                match fix {
+                    IssueFix::UseCharacter { id, .. } => placed.character_id = *id,
+                    IssueFix::UseState { id, .. } => placed.state_id = *id,
+                    IssueFix::UseRotation(angle) => placed.rotation = *angle,
+                    _ => return Err("This fix doesn't apply to a layer".to_string()),
+                }
+                Ok(format!("Layer '{}' fixed", placed.layer_name))
+            }
+            (
+                IssueLocation::Frame {
+                    character,
+                    animation,
+                    frame,
+                },
+                IssueFix::RemoveReference,
+            ) => {
+                let frame = self
+                    .get_character_mut(character)
+                    .and_then(|c| c.animations.get_mut(*animation))
+                    .and_then(|a| a.frames.get_mut(*frame))
+                    .ok_or("Frame not found")?;
+                frame.reference = None;
+                Ok("Removed missing reference image".to_string())
+            }
+            (IssueLocation::Character(name), IssueFix::UnlinkLibraryPart(part)) => {
+                let character = self.get_character_mut(name).ok_or("Character not found")?;
+                character.library_parts.retain(|n| n != part);
+                Ok(format!("Unlinked '{}' from {}", part, name))
+            }
+            (IssueLocation::Character(name), IssueFix::RemoveOutfitSwap { outfit, part }) => {
+                let outfit = self
+                    .get_character_mut(name)
+                    .and_then(|c| c.get_outfit_mut(outfit))
+                    .ok_or("Outfit not found")?;
+                outfit.swaps.remove(part);
+                Ok(format!(
+                    "Removed the '{}' swap from outfit '{}'",
+                    part, outfit.name
+                ))
+            }
+            _ => Err("This fix doesn't apply here".to_string()),
+        }
+    }
+}
+
+/// The closest angle of a state that has an image
+fn nearest_drawn_angle(project: &Project, state: &State, angle: Angle) -> Option<Angle> {
+    state
+        .rotations
+        .values()
+        .filter(|r| project.rotation_image(r).is_some())
+        .map(|r| r.angle)
+        .min_by_key(|&a| (a.distance(angle), a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::imaging::encode_png;
+    use crate::model::{Animation, Frame, FrameReference, Outfit, RotationMode};
+
+    #[test]
+    fn test_validate_and_fix() {
+        let mut project = Project::new("Broken");
+        let dot = image::RgbaImage::from_pixel(1, 1, image::Rgba([255, 0, 0, 255]));
+        let hash = project.images.insert_png(&encode_png(&dot).unwrap());
+
+        let mut hero = Character::new(1, "Hero");
+        let mut head = Part::new(1, "head");
+        head.states[0]
+            .rotations
+            .get_mut(&Angle::ZERO)
+            .unwrap()
+            .image = Some(hash);
+        head.add_state(State::new("empty", RotationMode::Deg90));
+        hero.add_part(head);
+        hero.library_parts.push("hat".to_string());
+        let mut armored = Outfit::new("armored");
+        armored
+            .swaps
+            .insert("head".to_string(), OutfitSwap::State("helmet".to_string()));
+        hero.outfits.push(armored);
+
+        let mut idle = Animation::new("idle");
+        let layers = [
+            PlacedPart::new(1, 1, 1, 1),
+            PlacedPart::new(2, 7, 1, 1),
+            PlacedPart::new(3, 1, 9, 1),
+            PlacedPart::new(4, 1, 1, 9),
+            PlacedPart::new(5, 1, 1, 1),
+        ];
+        idle.frames[0].placed_parts.extend(layers);
+        idle.frames[0].placed_parts[4].rotation = Angle::from_degrees(90.0);
+        let mut frame = Frame::new(100);
+        frame.reference = Some(FrameReference::new(
+            "/no/such/reference.png".to_string(),
+            1.0,
+        ));
+        idle.frames.push(frame);
+        hero.add_animation(idle);
+        project.add_character(hero);
+
+        let issues = project.validate();
+        let fixes: Vec<Option<IssueFix>> = issues.iter().map(|i| i.fix.clone()).collect();
+        assert_eq!(
+            fixes,
+            [
+                Some(IssueFix::UnlinkLibraryPart("hat".to_string())),
+                Some(IssueFix::RemoveOutfitSwap {
+                    outfit: "armored".to_string(),
+                    part: "head".to_string()
+                }),
+                None, // The "empty" state
+                Some(IssueFix::UseCharacter {
+                    id: 1,
+                    name: "Hero".to_string()
+                }),
+                Some(IssueFix::RemoveLayer),
+                Some(IssueFix::UseState {
+                    id: 1,
+                    name: "default".to_string()
+                }),
+                Some(IssueFix::UseRotation(Angle::ZERO)),
+                Some(IssueFix::RemoveReference),
+            ]
+        );
+
+        for issue in issues.iter().filter(|i| i.fix.is_some()) {
+            project.apply_fix(issue).unwrap();
+        }
+        let remaining = project.validate();
+        assert_eq!(remaining.len(), 1, "{:?}", remaining);
+        let idle = project.characters[0].get_animation("idle").unwrap();
+        assert_eq!(idle.frames[0].placed_parts.len(), 4);
+    }
+}
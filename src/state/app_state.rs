@@ -2,23 +2,69 @@ use bevy::prelude::*;
 use bevy_egui::egui;
 use std::collections::HashMap;
 use std::fs;
+use std::ops::Range;
 use std::path::PathBuf;
 
-use crate::model::{Animation, Character, EditorState, PlacedPart, Project};
+use crate::export::{ExportJob, ExporterRegistry};
+use crate::file::{
+    bundle_path_for, bundle_root, clear_recovery, home_dir, is_bundle_path, read_project,
+    read_recovery, save_bundle, write_recovery, FileBrowser, FolderImportPlan, Recovery,
+    RecoveryLock,
+};
+use crate::imaging::{
+    calculate_fit_scale, create_reference_thumbnail, encode_png, import_image_as_base64,
+    rotsprite, slice_spritesheet, ROTATION_PREVIEW_TEXTURE_PREFIX,
+};
+use crate::model::{
+    Angle, Animation, Character, EditorState, Frame, FrameReference, GeneratedRotation, Issue,
+    IssueLocation, NestedAnimation, PlacedPart, Project, RotationMode, Usage, UsageQuery,
+};
 use super::config::AppConfig;
-use super::types::{ActiveTab, ContextMenuTarget, DraggedPart, GalleryDrag, PendingAction, ZOOM_LEVELS};
+use super::types::{
+    ActiveTab, BrowsePurpose, ContextMenuTarget, DraggedPart, GalleryDrag, PendingAction,
+    RetargetSettings, RotationPreview, RotationPreviews, SheetImportSettings,
+    StateReplaceSettings, UndoStep, ZOOM_LEVELS,
+};
 
 #[derive(Resource)]
 pub struct AppState {
     pub project: Option<Project>,
     pub project_path: Option<PathBuf>,
     pub config: AppConfig,
-    pub last_saved_json: Option<String>, // JSON of last saved state for dirty checking
+    pub last_saved_json: Option<String>, // JSON of last saved state (debug builds only)
+    revision: u64,                       // Bumped by every project edit
+    saved_revision: Option<u64>,         // Revision matching the file (None: never saved or upgraded)
     pub last_saved_time: Option<std::time::Instant>, // When we last saved
 
     // Pending action for unsaved changes dialog
     pub pending_action: Option<PendingAction>,
 
+    // Offer to convert single-file projects to the bundle format on save
+    pub show_bundle_migration_dialog: bool,
+    pub bundle_migration_declined: bool, // User chose to keep the .pss format this session
+
+    // Summary of changes made upgrading an older project on load
+    pub show_migration_report_dialog: bool,
+
+    // Problems found by Validate Project, listed until the window is closed
+    pub show_validation_dialog: bool,
+    pub validation_issues: Vec<Issue>,
+
+    // Layers found by Find Usages, listed while the query is set
+    pub usage_query: Option<UsageQuery>,
+    pub usages: Vec<Usage>,
+    // Part and state that usages move to when deleting a part or state (None leaves them)
+    pub delete_replacement: Option<(String, String)>,
+
+    // Autosave and crash recovery
+    pub pending_recovery: Option<Recovery>, // Unsaved work found on startup, awaiting restore/discard
+    pub recovery_lock: Option<RecoveryLock>, // Recovery file this instance autosaves to
+    pub last_autosave_time: std::time::Instant,
+
+    // Linked external art: last seen modification time of each source file
+    pub linked_art_mtimes: HashMap<String, std::time::SystemTime>,
+    pub last_linked_art_check: std::time::Instant,
+
     // UI state
     pub show_grid: bool,
     pub show_labels: bool,
@@ -26,6 +72,11 @@ pub struct AppState {
     pub zoom_level: f32,
     pub current_animation: usize,
     pub current_frame: usize,
+    pub frame_selection: Option<(usize, usize)>, // Shift-click range in the timeline (inclusive)
+    pub timeline_drag: Option<Range<usize>>, // Frames being dragged to a new position
+    pub timeline_dope_sheet: bool, // Show the timeline as one row per layer
+    pub dope_sheet_drag: Option<(String, usize)>, // Layer and frame a state drag started on
+    pub frame_range_duration: u32, // Input for "Set Duration" on the selected frames
     pub is_playing: bool,
     pub playback_time: f32, // Accumulated time in current frame (seconds)
     pub selected_part_id: Option<u64>,
@@ -40,10 +91,12 @@ pub struct AppState {
     // Per-character animation state
     pub active_character: Option<String>, // Currently selected character
     pub active_tab: ActiveTab, // Canvas or CharacterEditor
+    pub preview_outfit: Option<String>, // Outfit of the active character shown on the canvas
 
     // Character editor state
     pub editor_selected_part: Option<String>,
     pub editor_selected_state: Option<String>,
+    pub editor_selected_outfit: Option<String>,
 
     // Dragging state (for canvas parts)
     pub dragging_part: Option<DraggedPart>,
@@ -76,8 +129,8 @@ pub struct AppState {
     pub clone_source_character: Option<String>,
     pub clone_character_name: String,
 
-    // Rotation import state (from character editor)
-    pub pending_rotation_import: Option<u16>,
+    // Built-in file browser, open while the user is picking a file or folder
+    pub file_browser: Option<FileBrowser>,
 
     // Dialog input buffers
     pub new_character_name: String,
@@ -87,8 +140,30 @@ pub struct AppState {
     pub selected_character_for_part: Option<String>,
     pub selected_part_for_state: Option<String>,
     pub selected_state_for_import: Option<String>,
-    pub selected_rotation_for_import: u16,
+    pub selected_rotation_for_import: Angle,
     pub import_image_path: String,
+    pub show_folder_import_dialog: bool,
+    pub folder_import_path: String,
+    pub folder_import_plan: Option<FolderImportPlan>, // Preview of the last scan
+    pub show_sheet_import_dialog: bool,
+    pub sheet_import: SheetImportSettings,
+    pub show_retarget_dialog: bool,
+    pub retarget: RetargetSettings,
+    pub show_replace_states_dialog: bool,
+    pub replace_states: StateReplaceSettings,
+
+    // Edits that Edit > Undo / Redo can take back (see `apply_undoable`)
+    undo_step: Option<UndoStep>,
+    redo_step: Option<UndoStep>,
+    pub custom_angles_edit: Option<(String, String)>, // (state key, angles being typed)
+
+    // Formats listed in the Export menu
+    pub exporters: ExporterRegistry,
+    // Export running in the background, shown in the export progress window
+    pub export_job: Option<ExportJob>,
+
+    // Generated rotations awaiting accept or discard in the rotation wheel
+    pub rotation_previews: Option<RotationPreviews>,
 
     // Status message
     pub status_message: Option<(String, std::time::Instant)>, // (message, when set)
@@ -124,14 +199,34 @@ impl AppState {
             project_path: None,
             config: AppConfig::load(),
             last_saved_json: None,
+            revision: 0,
+            saved_revision: None,
             last_saved_time: None,
             pending_action: None,
+            show_bundle_migration_dialog: false,
+            bundle_migration_declined: false,
+            show_migration_report_dialog: false,
+            show_validation_dialog: false,
+            validation_issues: Vec::new(),
+            usage_query: None,
+            usages: Vec::new(),
+            delete_replacement: None,
+            pending_recovery: read_recovery(),
+            recovery_lock: None,
+            last_autosave_time: std::time::Instant::now(),
+            linked_art_mtimes: HashMap::new(),
+            last_linked_art_check: std::time::Instant::now(),
             show_grid: true,
             show_labels: true,
             show_overlay_info: true,
             zoom_level: 16.0,
             current_animation: 0,
             current_frame: 0,
+            frame_selection: None,
+            timeline_drag: None,
+            timeline_dope_sheet: false,
+            dope_sheet_drag: None,
+            frame_range_duration: 100,
             is_playing: false,
             playback_time: 0.0,
             selected_part_id: None,
@@ -144,8 +239,10 @@ impl AppState {
             needs_zoom_fit: true,
             active_character: None,
             active_tab: ActiveTab::Canvas,
+            preview_outfit: None,
             editor_selected_part: None,
             editor_selected_state: None,
+            editor_selected_outfit: None,
             dragging_part: None,
             drag_offset: (0.0, 0.0),
             drag_accumulator: (0.0, 0.0),
@@ -165,7 +262,7 @@ impl AppState {
             rename_new_name: String::new(),
             clone_source_character: None,
             clone_character_name: String::new(),
-            pending_rotation_import: None,
+            file_browser: None,
             new_character_name: String::new(),
             new_part_name: String::new(),
             new_state_name: String::new(),
@@ -173,8 +270,23 @@ impl AppState {
             selected_character_for_part: None,
             selected_part_for_state: None,
             selected_state_for_import: None,
-            selected_rotation_for_import: 0,
+            selected_rotation_for_import: Angle::ZERO,
             import_image_path: String::new(),
+            show_folder_import_dialog: false,
+            folder_import_path: String::new(),
+            folder_import_plan: None,
+            show_sheet_import_dialog: false,
+            sheet_import: SheetImportSettings::default(),
+            show_retarget_dialog: false,
+            custom_angles_edit: None,
+            exporters: ExporterRegistry::default(),
+            export_job: None,
+            rotation_previews: None,
+            retarget: RetargetSettings::default(),
+            show_replace_states_dialog: false,
+            replace_states: StateReplaceSettings::default(),
+            undo_step: None,
+            redo_step: None,
             status_message: None,
             texture_cache: HashMap::new(),
             frame_thumbnail_cache: HashMap::new(),
@@ -193,55 +305,28 @@ impl AppState {
         let current_frame = self.current_frame;
         let char_name = self.active_character.clone();
 
+        let Some(ids) = self.project.as_ref().and_then(|project| {
+            let part = project.get_part_by_character_id(character_id, part)?;
+            Some((part.id, part.get_state(state)?.id))
+        }) else {
+            self.set_status(format!("Can't place {} / {}: not found", part, state));
+            return;
+        };
+
+        self.mark_changed();
         if let Some(ref mut project) = self.project {
             let id = project.next_id();
 
             // Generate unique layer name
-            let layer_name = if let Some(ref char_name) = char_name {
-                if let Some(character_obj) = project.get_character(char_name) {
-                    if let Some(anim) = character_obj.animations.get(current_anim) {
-                        if let Some(frame) = anim.frames.get(current_frame) {
-                            // Check existing layer names (fall back to part_name for old projects)
-                            let base_name = part;
-                            let existing_names: std::collections::HashSet<String> = frame
-                                .placed_parts
-                                .iter()
-                                .map(|p| {
-                                    if p.layer_name.is_empty() {
-                                        p.part_name.clone()
-                                    } else {
-                                        p.layer_name.clone()
-                                    }
-                                })
-                                .collect();
-
-                            if !existing_names.contains(base_name) {
-                                base_name.to_string()
-                            } else {
-                                // Find next available number
-                                let mut n = 2;
-                                loop {
-                                    let candidate = format!("{} {}", base_name, n);
-                                    if !existing_names.contains(&candidate) {
-                                        break candidate;
-                                    }
-                                    n += 1;
-                                }
-                            }
-                        } else {
-                            part.to_string()
-                        }
-                    } else {
-                        part.to_string()
-                    }
-                } else {
-                    part.to_string()
-                }
-            } else {
-                part.to_string()
-            };
+            let layer_name = char_name
+                .as_ref()
+                .and_then(|name| project.get_character(name))
+                .and_then(|c| c.animations.get(current_anim))
+                .and_then(|anim| anim.frames.get(current_frame))
+                .map(|frame| unique_layer_name(frame, part))
+                .unwrap_or_else(|| part.to_string());
 
-            let mut placed = PlacedPart::new(id, character_id, part, state)
+            let mut placed = PlacedPart::new(id, character_id, ids.0, ids.1)
                 .with_layer_name(&layer_name);
             placed.position = (x, y);
 
@@ -259,6 +344,30 @@ impl AppState {
         }
     }
 
+    /// Add a layer to the current frame that plays another character's (or this one's) animation
+    pub fn place_animation_on_canvas(&mut self, character_id: u64, animation_name: &str) {
+        let current_frame = self.current_frame;
+        self.mark_changed();
+        let Some(ref mut project) = self.project else {
+            return;
+        };
+        let id = project.next_id();
+        let Some(frame) = self
+            .active_character
+            .as_ref()
+            .and_then(|name| project.get_character_mut(name))
+            .and_then(|c| c.animations.get_mut(self.current_animation))
+            .and_then(|anim| anim.frames.get_mut(current_frame))
+        else {
+            return;
+        };
+        let placed = PlacedPart::new_nested(id, NestedAnimation::new(character_id, animation_name))
+            .with_layer_name(unique_layer_name(frame, animation_name));
+        frame.placed_parts.push(placed);
+        self.selected_part_id = Some(id);
+        self.selection_time = Some(std::time::Instant::now());
+    }
+
     pub fn get_selected_placed_part(&self) -> Option<&PlacedPart> {
         let id = self.selected_part_id?;
         let anim = self.current_animation()?;
@@ -310,12 +419,22 @@ impl AppState {
             reference_show_on_top: self.reference_show_on_top,
         };
 
-        let json = project.to_json().map_err(|e| format!("Serialize error: {}", e))?;
-        fs::write(path, &json).map_err(|e| format!("Write error: {}", e))?;
+        // Drop images no rotation references anymore
+        project.collect_unused_images();
+
+        match bundle_root(path) {
+            Some(root) => save_bundle(project, &root)?,
+            None => {
+                let json = project.to_json().map_err(|e| format!("Serialize error: {}", e))?;
+                fs::write(path, &json).map_err(|e| format!("Write error: {}", e))?;
+            }
+        }
 
         // Track saved state for dirty checking
-        self.last_saved_json = Some(json);
+        self.last_saved_json = saved_json(project);
+        self.saved_revision = Some(self.revision);
         self.last_saved_time = Some(std::time::Instant::now());
+        self.clear_recovery_file();
 
         Ok(())
     }
@@ -329,10 +448,59 @@ impl AppState {
         result
     }
 
+    /// Whether saving should first offer to convert a single-file project to a bundle
+    pub fn should_offer_bundle_migration(&self) -> bool {
+        !self.bundle_migration_declined
+            && self
+                .project_path
+                .as_ref()
+                .is_some_and(|path| !is_bundle_path(path))
+    }
+
+    /// Save the project as a bundle next to its current .pss file, leaving the original untouched
+    pub fn migrate_to_bundle(&mut self) -> Result<PathBuf, String> {
+        let legacy_path = self.project_path.as_ref().ok_or("No file path set")?;
+        let bundle_path = bundle_path_for(legacy_path);
+        if bundle_path.exists() {
+            return Err(format!("{} already exists", bundle_path.display()));
+        }
+        self.save_project_as(&bundle_path.to_string_lossy())?;
+        Ok(bundle_path)
+    }
+
+    /// Open a project file, reporting it (and any images it couldn't read) in the status bar
     pub fn load_project(&mut self, path: &str) -> Result<(), String> {
-        let json = fs::read_to_string(path).map_err(|e| format!("Read error: {}", e))?;
-        let project = Project::from_json(&json).map_err(|e| format!("Parse error: {}", e))?;
+        let (project, problems) = read_project(std::path::Path::new(path))?;
+        // Bundles opened through their project.json are tracked by directory
+        let path = bundle_root(std::path::Path::new(path))
+            .map(|root| root.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+
+        // Track saved state (an upgraded project differs from the file until saved)
+        let migrated = project.was_migrated();
+        self.last_saved_json = if migrated { None } else { saved_json(&project) };
+        self.saved_revision = if migrated { None } else { Some(self.revision) };
+        self.last_saved_time = Some(std::time::Instant::now());
+        self.show_migration_report_dialog = migrated;
+
+        self.open_project(project, Some(PathBuf::from(&path)));
+        self.clear_recovery_file();
+        self.config.add_recent(&path);
+        match problems.first() {
+            Some(first) => self.set_status(format!(
+                "Loaded {} with {} unreadable image(s), see Validate Project. {}",
+                path,
+                problems.len(),
+                first
+            )),
+            None => self.set_status(format!("Loaded {}", path)),
+        }
+
+        Ok(())
+    }
 
+    /// Make a loaded project the current one, restoring its saved editor state
+    fn open_project(&mut self, project: Project, path: Option<PathBuf>) {
         // Restore editor state from project
         let editor_state = &project.editor_state;
         self.active_character = editor_state.active_character.clone();
@@ -355,52 +523,680 @@ impl AppState {
             ActiveTab::Canvas
         };
 
-        // Track saved state
-        self.last_saved_json = project.to_json().ok();
-        self.last_saved_time = Some(std::time::Instant::now());
-
         self.project = Some(project);
-        self.project_path = Some(PathBuf::from(path));
+        self.project_path = path;
+        self.undo_step = None;
+        self.redo_step = None;
         self.selected_part_id = None;
+        self.rotation_previews = None;
+        self.preview_outfit = None;
+        self.frame_selection = None;
         self.needs_zoom_fit = true;
-        self.config.add_recent(path);
+        self.bundle_migration_declined = false;
+        self.linked_art_mtimes.clear();
+        self.texture_cache.clear();
+        self.frame_thumbnail_cache.clear();
+    }
+
+    /// Restore the project found in the recovery file; it stays unsaved until the user saves
+    pub fn restore_recovery(&mut self) -> Result<(), String> {
+        let recovery = self.pending_recovery.as_ref().ok_or("No recovery file")?;
+        let project = recovery.project()?;
+        let Some(recovery) = self.pending_recovery.take() else {
+            return Err("No recovery file".to_string());
+        };
+        let path = recovery.project_path.map(PathBuf::from);
+        // The restored file is this instance's now, removed once the project is saved
+        clear_recovery(&mut self.recovery_lock);
+        self.recovery_lock = recovery.lock;
+
+        self.last_saved_json = None;
+        self.saved_revision = None;
+        self.last_saved_time = None;
+        self.open_project(project, path);
+        Ok(())
+    }
+
+    /// Throw away the recovery file found on startup
+    pub fn discard_recovery(&mut self) {
+        if let Some(mut recovery) = self.pending_recovery.take() {
+            clear_recovery(&mut recovery.lock);
+        }
+    }
+
+    /// Report a finished background export in the status bar
+    pub fn poll_export_job(&mut self) {
+        let Some(outcome) = self.export_job.as_mut().and_then(|job| job.try_finish()) else {
+            return;
+        };
+        let destination = self.export_job.take().map(|job| job.destination).unwrap_or_default();
+
+        let written = outcome.written.len();
+        let message = if let Some((label, error)) = outcome.failed.first() {
+            format!(
+                "Export failed for {}: {} ({} of {} sheets written)",
+                label,
+                error,
+                written,
+                written + outcome.failed.len()
+            )
+        } else if outcome.cancelled {
+            format!("Export cancelled after {} sheet(s)", written)
+        } else if let [paths] = outcome.written.as_slice() {
+            format!("Exported to {}", paths.join(" and "))
+        } else {
+            format!("Exported {} animations to {}", written, destination)
+        };
+        self.set_status(message);
+    }
+
+    /// Check the project for broken references and show the report
+    pub fn validate_project(&mut self) {
+        let Some(ref project) = self.project else {
+            return;
+        };
+        self.validation_issues = project.validate();
+        self.show_validation_dialog = true;
+        let count = self.validation_issues.len();
+        if count == 0 {
+            self.set_status("Validation found no problems");
+        } else {
+            self.set_status(format!("Validation found {} problem(s)", count));
+        }
+    }
+
+    /// Apply the one-click fix of a reported problem, then check the project again
+    pub fn fix_issue(&mut self, index: usize) {
+        let Some(issue) = self.validation_issues.get(index).cloned() else {
+            return;
+        };
+        let Some(project) = self.project_mut() else {
+            return;
+        };
+        let result = project.apply_fix(&issue);
+        self.validation_issues = project.validate();
+        self.frame_thumbnail_cache.clear();
+        match result {
+            Ok(message) => self.set_status(message),
+            Err(e) => self.set_status(format!("Fix failed: {}", e)),
+        }
+    }
+
+    /// List the layers drawing a part, state or rotation in the Find Usages window
+    pub fn find_usages(&mut self, query: UsageQuery) {
+        let Some(ref project) = self.project else {
+            return;
+        };
+        self.usages = project.find_usages(&query);
+        self.set_status(format!("{} layer(s) use {}", self.usages.len(), query.describe()));
+        self.usage_query = Some(query);
+    }
+
+    /// Show the frame, layer or part a reported problem is about
+    pub fn go_to_issue(&mut self, location: &IssueLocation) {
+        match location {
+            IssueLocation::Layer { character, animation, frame, .. }
+            | IssueLocation::Frame { character, animation, frame } => {
+                if self.active_character.as_ref() != Some(character) {
+                    self.active_character = Some(character.clone());
+                    self.preview_outfit = None;
+                    self.needs_zoom_fit = true;
+                    self.frame_thumbnail_cache.clear();
+                }
+                self.active_tab = ActiveTab::Canvas;
+                self.current_animation = *animation;
+                self.current_frame = *frame;
+                self.frame_selection = None;
+                self.is_playing = false;
+                if let IssueLocation::Layer { layer_id, .. } = location {
+                    self.selected_part_id = Some(*layer_id);
+                    self.selection_time = Some(std::time::Instant::now());
+                }
+            }
+            IssueLocation::Part { character, part, state } => {
+                self.active_tab = ActiveTab::CharacterEditor(character.clone());
+                self.editor_selected_part = Some(part.clone());
+                self.editor_selected_state = state.clone();
+            }
+            IssueLocation::Character(character) => {
+                self.active_tab = ActiveTab::CharacterEditor(character.clone());
+            }
+        }
+    }
+
+    /// Write the project to the recovery file if it has changes and the autosave interval elapsed
+    pub fn autosave_if_due(&mut self) {
+        let interval = self.config.autosave_interval_secs;
+        if interval == 0
+            || self.pending_recovery.is_some()
+            || self.last_autosave_time.elapsed().as_secs() < interval
+        {
+            return;
+        }
+        self.last_autosave_time = std::time::Instant::now();
+
+        // Debug builds check that no edit path forgot to mark the project changed
+        if cfg!(debug_assertions) && !self.has_unsaved_changes() && self.content_differs_from_saved() {
+            self.mark_changed();
+            self.set_status("Dirty tracking missed an edit; the project is marked unsaved");
+        }
+        if !self.has_unsaved_changes() {
+            return;
+        }
+        if let Some(ref project) = self.project {
+            let path = self.project_path.as_deref();
+            if let Err(e) = write_recovery(project, path, &mut self.recovery_lock) {
+                self.set_status(format!("Autosave failed: {}", e));
+            }
+        }
+    }
+
+    /// Slice the spritesheet in `sheet_import` into the rotations of a part state,
+    /// returning how many rotations were filled
+    pub fn import_spritesheet(
+        &mut self,
+        char_name: &str,
+        part_name: &str,
+        state_name: &str,
+    ) -> Result<usize, String> {
+        self.mark_changed();
+        let settings = &self.sheet_import;
+        let project = self.project.as_mut().ok_or("No project loaded")?;
+        let mode = project
+            .get_part(char_name, part_name)
+            .and_then(|p| p.states.iter().find(|s| s.name == state_name))
+            .map(|s| s.rotation_mode.clone())
+            .ok_or("State not found")?;
+
+        let angles = mode.angles_from(settings.start_angle, settings.clockwise);
+        let cells =
+            slice_spritesheet(&settings.path, &settings.grid, angles.len(), settings.auto_trim)?;
+
+        let mut hashes = Vec::new();
+        for (angle, cell) in angles.into_iter().zip(cells) {
+            if let Some(base64_data) = cell {
+                hashes.push((angle, project.images.insert_base64(&base64_data)?));
+            }
+        }
+
+        let mut filled = 0;
+        if let Some(state_obj) = project
+            .get_part_mut(char_name, part_name)
+            .and_then(|p| p.states.iter_mut().find(|s| s.name == state_name))
+        {
+            for (angle, hash) in hashes {
+                if let Some(rotation) = state_obj.rotations.get_mut(&angle) {
+                    rotation.image = Some(hash);
+                    // The sheet is not a per-rotation source file, so drop any old link
+                    rotation.source_path = None;
+                    rotation.generated = None;
+                    filled += 1;
+                }
+            }
+        }
+
+        self.frame_thumbnail_cache.clear();
+        Ok(filled)
+    }
+
+    /// Open the file browser, starting next to the project for project files and
+    /// in the most recently used folder for everything else
+    pub fn open_file_browser(&mut self, purpose: BrowsePurpose) {
+        let project_dir = self
+            .project_path
+            .as_ref()
+            .and_then(|p| p.parent())
+            .map(|p| p.to_path_buf());
+        let recent_dir = self.config.recent_folders.first().map(PathBuf::from);
+        let start_dir = match purpose {
+            BrowsePurpose::OpenProject
+            | BrowsePurpose::SaveProjectAs { .. }
+            | BrowsePurpose::ExportAnimation { .. }
+            | BrowsePurpose::ExportAllAnimations { .. } => project_dir.or(recent_dir),
+            _ => recent_dir.or(project_dir),
+        }
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(home_dir);
+
+        let mut browser = FileBrowser::new(purpose, start_dir);
+        if let BrowsePurpose::SaveProjectAs { .. } = browser.purpose {
+            if let Some(name) = self.project_path.as_ref().and_then(|p| p.file_name()) {
+                browser.file_name = name.to_string_lossy().to_string();
+            }
+        }
+        self.file_browser = Some(browser);
+    }
+
+    /// Use an image file as the reference for the current frame, scaled to fit the canvas
+    pub fn set_frame_reference(&mut self, path: &str) {
+        let Some(char_name) = self.active_character.clone() else {
+            return;
+        };
+        let current_anim = self.current_animation;
+        let current_frame_idx = self.current_frame;
+
+        self.mark_changed();
+        if let Some(ref mut project) = self.project {
+            if !project.reference_thumbnails.contains_key(path) {
+                if let Ok((thumbnail, _original_size)) = create_reference_thumbnail(path, 256) {
+                    project
+                        .reference_thumbnails
+                        .insert(path.to_string(), thumbnail);
+                }
+            }
+
+            let canvas_size = project
+                .get_character(&char_name)
+                .map(|c| c.canvas_size)
+                .unwrap_or((64, 64));
+            let scale = fs::read(path)
+                .ok()
+                .and_then(|bytes| image::load_from_memory(&bytes).ok())
+                .map(|img| calculate_fit_scale((img.width(), img.height()), canvas_size))
+                .unwrap_or(1.0);
+
+            if let Some(character) = project.get_character_mut(&char_name) {
+                if let Some(anim) = character.animations.get_mut(current_anim) {
+                    if let Some(frame) = anim.frames.get_mut(current_frame_idx) {
+                        frame.reference = Some(FrameReference::new(path.to_string(), scale));
+                    }
+                }
+            }
+
+            self.reference_texture_cache.remove(path);
+            self.set_status(format!("Loaded reference: {}", path));
+        }
+    }
 
+    /// Import an image file into one rotation of the part state selected in the character editor
+    pub fn import_rotation_image(&mut self, angle: Angle, path: &str) -> Result<(), String> {
+        let (char_name, part_name, state_name) = self.editor_part_state()?;
+
+        let base64_data = import_image_as_base64(path)?;
+        self.mark_changed();
+        let project = self.project.as_mut().ok_or("No project loaded")?;
+        let hash = project.images.insert_base64(&base64_data)?;
+        let rotation = project
+            .get_part_mut(&char_name, &part_name)
+            .and_then(|p| p.states.iter_mut().find(|s| s.name == state_name))
+            .and_then(|s| s.rotations.get_mut(&angle))
+            .ok_or("Rotation not found")?;
+        rotation.image = Some(hash);
+        rotation.source_path = Some(path.to_string());
+        rotation.generated = None;
         Ok(())
     }
 
+    /// Character, part and state selected in the character editor
+    fn editor_part_state(&self) -> Result<(String, String, String), String> {
+        let ActiveTab::CharacterEditor(ref char_name) = self.active_tab else {
+            return Err("No character open in the editor".to_string());
+        };
+        let part_name = self.editor_selected_part.clone().ok_or("No part selected")?;
+        let state_name = self
+            .editor_selected_state
+            .clone()
+            .unwrap_or_else(|| "default".to_string());
+        Ok((char_name.clone(), part_name, state_name))
+    }
+
+    /// Preview the selected state's empty and generated rotations, each made by rotating
+    /// the nearest drawn rotation. Returns how many rotations were generated.
+    pub fn preview_generated_rotations(&mut self) -> Result<usize, String> {
+        let (char_name, part_name, state_name) = self.editor_part_state()?;
+        let project = self.project.as_ref().ok_or("No project loaded")?;
+        let state = project
+            .get_part(&char_name, &part_name)
+            .and_then(|p| p.states.iter().find(|s| s.name == state_name))
+            .ok_or("State not found")?;
+        let plan = state.generation_plan();
+        if plan.is_empty() {
+            return Err("Draw or import at least one rotation first".to_string());
+        }
+
+        let mut rotations = Vec::new();
+        for (angle, source_angle) in plan {
+            let Some(source_image) = state
+                .rotations
+                .get(&source_angle)
+                .and_then(|r| r.image.clone())
+            else {
+                continue;
+            };
+            let source = project.images.decoded(&source_image)?;
+            rotations.push(RotationPreview {
+                angle,
+                source_angle,
+                image: rotsprite(&source, angle.degrees() - source_angle.degrees()),
+                source_image,
+            });
+        }
+
+        let count = rotations.len();
+        self.clear_rotation_previews();
+        self.rotation_previews = Some(RotationPreviews {
+            char_name,
+            part_name,
+            state_name,
+            rotations,
+        });
+        Ok(count)
+    }
+
+    /// Store the previewed rotations in their state, flagged as generated.
+    /// Rotations drawn since the preview was made are left alone.
+    pub fn accept_generated_rotations(&mut self) -> Result<usize, String> {
+        let previews = self.rotation_previews.take().ok_or("No generated rotations to accept")?;
+        self.clear_rotation_previews();
+        self.mark_changed();
+        let project = self.project.as_mut().ok_or("No project loaded")?;
+
+        let mut hashes = Vec::new();
+        for preview in &previews.rotations {
+            hashes.push(project.images.insert_png(&encode_png(&preview.image)?));
+        }
+        let state = project
+            .get_part_mut(&previews.char_name, &previews.part_name)
+            .and_then(|p| p.states.iter_mut().find(|s| s.name == previews.state_name))
+            .ok_or("State not found")?;
+
+        let mut accepted = 0;
+        for (preview, hash) in previews.rotations.into_iter().zip(hashes) {
+            let Some(rotation) = state.rotations.get_mut(&preview.angle) else {
+                continue;
+            };
+            if rotation.image.is_some() && rotation.generated.is_none() {
+                continue;
+            }
+            rotation.image = Some(hash);
+            rotation.source_path = None;
+            rotation.generated = Some(GeneratedRotation {
+                source_angle: preview.source_angle,
+                source_image: preview.source_image,
+            });
+            accepted += 1;
+        }
+
+        self.frame_thumbnail_cache.clear();
+        Ok(accepted)
+    }
+
+    /// Change the rotation mode of the state open in the character editor. Returns how
+    /// many drawn rotations were kept outside the new mode.
+    pub fn set_editor_rotation_mode(&mut self, mode: RotationMode) -> Result<usize, String> {
+        if mode.angles().is_empty() {
+            return Err("Enter at least one angle".to_string());
+        }
+        let (char_name, part_name, state_name) = self.editor_part_state()?;
+        self.clear_rotation_previews();
+        let project = self.project_mut().ok_or("No project loaded")?;
+        let state = project
+            .get_part_mut(&char_name, &part_name)
+            .and_then(|p| p.states.iter_mut().find(|s| s.name == state_name))
+            .ok_or("State not found")?;
+        Ok(state.set_rotation_mode(mode))
+    }
+
+    /// Drop any previewed rotations and their textures
+    pub fn clear_rotation_previews(&mut self) {
+        self.rotation_previews = None;
+        self.texture_cache
+            .retain(|key, _| !key.starts_with(ROTATION_PREVIEW_TEXTURE_PREFIX));
+    }
+
+    /// Re-import linked source files, pointing their rotations at the new art.
+    /// Returns how many rotations changed, plus the path and error of each file that failed.
+    pub fn reimport_linked_art(&mut self, paths: &[String]) -> (usize, Vec<(String, String)>) {
+        let mut changed = 0;
+        let mut errors = Vec::new();
+        let Some(ref mut project) = self.project else {
+            return (changed, errors);
+        };
+
+        for path in paths {
+            let hash = import_image_as_base64(path)
+                .and_then(|base64_data| project.images.insert_base64(&base64_data));
+            match hash {
+                Ok(hash) => changed += project.relink_image(path, &hash),
+                Err(e) => errors.push((path.clone(), e)),
+            }
+        }
+
+        if changed > 0 {
+            self.mark_changed();
+            // Thumbnails are keyed by frame layout, not art, so they must be rebuilt too
+            self.texture_cache.clear();
+            self.frame_thumbnail_cache.clear();
+        }
+        (changed, errors)
+    }
+
+    /// Re-import every linked source file whose content differs from the stored image
+    pub fn reimport_all_changed(&mut self) {
+        let paths: Vec<String> = self
+            .project
+            .as_ref()
+            .map(|p| p.linked_source_paths().into_iter().collect())
+            .unwrap_or_default();
+        let (changed, errors) = self.reimport_linked_art(&paths);
+
+        if let Some((path, error)) = errors.first() {
+            self.set_status(format!(
+                "Reimported {} rotation(s), {} file(s) failed ({}: {})",
+                changed,
+                errors.len(),
+                path,
+                error
+            ));
+        } else if changed > 0 {
+            self.set_status(format!("Reimported {} rotation(s)", changed));
+        } else {
+            self.set_status("Linked art is up to date");
+        }
+    }
+
+    /// Poll linked source files and re-import the ones modified since they were last seen
+    pub fn watch_linked_art(&mut self) {
+        const POLL_INTERVAL_MS: u128 = 1000;
+        if self.last_linked_art_check.elapsed().as_millis() < POLL_INTERVAL_MS {
+            return;
+        }
+        self.last_linked_art_check = std::time::Instant::now();
+
+        let Some(ref project) = self.project else {
+            return;
+        };
+        let mut modified = Vec::new();
+        for path in project.linked_source_paths() {
+            let Ok(mtime) = fs::metadata(&path).and_then(|m| m.modified()) else {
+                continue;
+            };
+            match self.linked_art_mtimes.get(&path) {
+                Some(seen) if *seen != mtime => modified.push((path, mtime)),
+                Some(_) => {}
+                // First sighting: remember it without re-importing
+                None => {
+                    self.linked_art_mtimes.insert(path, mtime);
+                }
+            }
+        }
+        if modified.is_empty() {
+            return;
+        }
+
+        let paths: Vec<String> = modified.iter().map(|(path, _)| path.clone()).collect();
+        let (changed, errors) = self.reimport_linked_art(&paths);
+        for (path, mtime) in modified {
+            // Files that failed (e.g. still being written) are retried on the next poll
+            if !errors.iter().any(|(failed, _)| *failed == path) {
+                self.linked_art_mtimes.insert(path, mtime);
+            }
+        }
+        if changed > 0 {
+            let names: Vec<String> = paths
+                .iter()
+                .map(|p| {
+                    std::path::Path::new(p)
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_else(|| p.clone())
+                })
+                .collect();
+            self.set_status(format!("Reloaded {}", names.join(", ")));
+        }
+    }
+
+    /// Remove this instance's recovery file once its changes are saved or discarded
+    pub fn clear_recovery_file(&mut self) {
+        clear_recovery(&mut self.recovery_lock);
+    }
+
     pub fn new_project(&mut self) {
         let project = Project::new("Untitled");
-        self.last_saved_json = project.to_json().ok();
+        self.last_saved_json = saved_json(&project);
+        self.saved_revision = Some(self.revision);
         self.last_saved_time = None; // New project hasn't been saved yet
         self.project = Some(project);
         self.project_path = None;
+        self.undo_step = None;
+        self.redo_step = None;
         self.current_animation = 0;
         self.current_frame = 0;
         self.selected_part_id = None;
         self.active_character = None;
         self.needs_zoom_fit = true;
+        self.clear_recovery_file();
     }
 
     pub fn close_project(&mut self) {
         self.project = None;
         self.project_path = None;
+        self.undo_step = None;
+        self.redo_step = None;
         self.last_saved_json = None;
+        self.saved_revision = None;
         self.last_saved_time = None;
+        self.bundle_migration_declined = false;
+        self.show_migration_report_dialog = false;
+        self.show_validation_dialog = false;
+        self.validation_issues.clear();
+        self.usage_query = None;
+        self.usages.clear();
         self.current_animation = 0;
         self.current_frame = 0;
         self.selected_part_id = None;
         self.active_character = None;
         self.active_tab = ActiveTab::Canvas;
+        self.rotation_previews = None;
         self.texture_cache.clear();
         self.frame_thumbnail_cache.clear();
+        self.clear_recovery_file();
+    }
+
+    /// Record an edit to the project; call this from every path that changes it
+    pub fn mark_changed(&mut self) {
+        self.revision += 1;
+    }
+
+    /// Make an edit that Edit > Undo takes back in one step. The edit returns a status
+    /// message; if it fails the project is left as it was.
+    pub fn apply_undoable(
+        &mut self,
+        label: &str,
+        edit: impl FnOnce(&mut Project) -> Result<String, String>,
+    ) {
+        let Some(before) = self.project.clone() else {
+            return;
+        };
+        let Some(project) = self.project.as_mut() else {
+            return;
+        };
+        match edit(project) {
+            Ok(message) => {
+                self.mark_changed();
+                self.undo_step = Some(UndoStep {
+                    label: label.to_string(),
+                    project: before,
+                    revision: self.revision,
+                });
+                self.redo_step = None;
+                self.frame_thumbnail_cache.clear();
+                self.set_status(message);
+            }
+            Err(e) => {
+                self.project = Some(before);
+                self.set_status(e);
+            }
+        }
+    }
+
+    /// What Edit > Undo would take back, if nothing was edited since
+    pub fn undo_label(&self) -> Option<&str> {
+        let step = self.undo_step.as_ref().filter(|s| s.revision == self.revision)?;
+        Some(&step.label)
+    }
+
+    /// What Edit > Redo would apply again, if nothing was edited since the undo
+    pub fn redo_label(&self) -> Option<&str> {
+        let step = self.redo_step.as_ref().filter(|s| s.revision == self.revision)?;
+        Some(&step.label)
+    }
+
+    pub fn undo(&mut self) {
+        if self.undo_label().is_none() {
+            return;
+        }
+        if let Some(step) = self.undo_step.take() {
+            self.set_status(format!("Undid {}", step.label));
+            self.redo_step = self.swap_undo_step(step);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if self.redo_label().is_none() {
+            return;
+        }
+        if let Some(step) = self.redo_step.take() {
+            self.set_status(format!("Redid {}", step.label));
+            self.undo_step = self.swap_undo_step(step);
+        }
+    }
+
+    /// Put a step's project back, returning the step that reverses it
+    fn swap_undo_step(&mut self, step: UndoStep) -> Option<UndoStep> {
+        let current = self.project.replace(step.project)?;
+        self.mark_changed();
+        self.frame_thumbnail_cache.clear();
+        Some(UndoStep {
+            label: step.label,
+            project: current,
+            revision: self.revision,
+        })
     }
 
+    /// Mutable access to the project for an edit, marking it changed
+    pub fn project_mut(&mut self) -> Option<&mut Project> {
+        self.mark_changed();
+        self.project.as_mut()
+    }
+
+    /// Cheap enough to call every frame: compares revisions, not project contents
     pub fn has_unsaved_changes(&self) -> bool {
+        match (&self.project, self.saved_revision) {
+            (Some(_), Some(saved)) => saved != self.revision,
+            (Some(_), None) => true, // Project exists but never saved
+            _ => false,
+        }
+    }
+
+    /// Compare the project with the last saved JSON. Serializes the whole project,
+    /// so it is only used to check the revision tracking, never per frame.
+    fn content_differs_from_saved(&self) -> bool {
         match (&self.project, &self.last_saved_json) {
             (Some(project), Some(saved_json)) => {
                 project.to_json().ok().as_ref() != Some(saved_json)
             }
-            (Some(_), None) => true, // Project exists but never saved
+            (Some(_), None) => true,
             _ => false,
         }
     }
@@ -416,7 +1212,7 @@ impl AppState {
 
     pub fn active_character_mut(&mut self) -> Option<&mut Character> {
         let char_name = self.active_character.clone()?;
-        self.project.as_mut()?.get_character_mut(&char_name)
+        self.project_mut()?.get_character_mut(&char_name)
     }
 
     pub fn current_animation(&self) -> Option<&Animation> {
@@ -428,6 +1224,35 @@ impl AppState {
         self.active_character_mut()?.animations.get_mut(anim_idx)
     }
 
+    /// Frames the timeline's range operations act on: the shift-click range, or the current frame
+    pub fn selected_frames(&self) -> Range<usize> {
+        match self.frame_selection {
+            Some((start, end)) => start..end + 1,
+            None => self.current_frame..self.current_frame + 1,
+        }
+    }
+
+    /// Edit the current animation with access to the project's placement id counter
+    pub fn edit_current_animation<R>(
+        &mut self,
+        edit: impl FnOnce(&mut Animation, &mut u64) -> R,
+    ) -> Option<R> {
+        let anim_idx = self.current_animation;
+        self.mark_changed();
+        let char_name = self.active_character.as_ref()?;
+        let project = self.project.as_mut()?;
+        let animation = project
+            .characters
+            .iter_mut()
+            .find(|c| &c.name == char_name)?
+            .animations
+            .get_mut(anim_idx)?;
+        let result = edit(animation, &mut project.next_part_id);
+        // Thumbnails depend on frame order through nested animation timing
+        self.frame_thumbnail_cache.clear();
+        Some(result)
+    }
+
     pub fn total_frames(&self) -> usize {
         self.current_animation().map(|a| a.frames.len()).unwrap_or(1)
     }
@@ -452,3 +1277,30 @@ impl AppState {
         }
     }
 }
+
+/// `base`, or `base 2`, `base 3`... if a layer in the frame already has that name
+fn unique_layer_name(frame: &Frame, base: &str) -> String {
+    let existing_names: std::collections::HashSet<&str> =
+        frame.placed_parts.iter().map(|p| p.layer_name.as_str()).collect();
+    if !existing_names.contains(base) {
+        return base.to_string();
+    }
+    let mut n = 2;
+    loop {
+        let candidate = format!("{} {}", base, n);
+        if !existing_names.contains(candidate.as_str()) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// JSON of a project as saved, for the debug check of the revision tracking. Release
+/// builds skip serializing the whole project (art included) on every open and save.
+fn saved_json(project: &Project) -> Option<String> {
+    if cfg!(debug_assertions) {
+        project.to_json().ok()
+    } else {
+        None
+    }
+}
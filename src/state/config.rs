@@ -2,7 +2,10 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::file::DEFAULT_IMPORT_PATTERN;
+
 pub const MAX_RECENT_PROJECTS: usize = 10;
+pub const MAX_RECENT_FOLDERS: usize = 8;
 
 /// Default margin for panel content (in virtual units, scaled by ui_scale)
 pub const DEFAULT_PANEL_MARGIN: f32 = 8.0;
@@ -11,12 +14,38 @@ pub fn default_ui_scale() -> f32 {
     1.0
 }
 
+/// Autosave interval choices offered in the File menu (label, seconds; 0 = off)
+pub const AUTOSAVE_INTERVALS: [(&str, u64); 5] = [
+    ("Off", 0),
+    ("Every 30 seconds", 30),
+    ("Every minute", 60),
+    ("Every 5 minutes", 300),
+    ("Every 10 minutes", 600),
+];
+
+pub fn default_autosave_interval_secs() -> u64 {
+    60
+}
+
+pub fn default_import_pattern() -> String {
+    DEFAULT_IMPORT_PATTERN.to_string()
+}
+
 /// App configuration stored on disk
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub recent_projects: Vec<String>,
     #[serde(default = "default_ui_scale")]
     pub ui_scale: f32,
+    /// Seconds between autosaves to the recovery file (0 disables autosave)
+    #[serde(default = "default_autosave_interval_secs")]
+    pub autosave_interval_secs: u64,
+    /// Filename pattern for folder imports, e.g. `{part}_{state}_{angle}`
+    #[serde(default = "default_import_pattern")]
+    pub import_pattern: String,
+    /// Folders recently picked in the file browser, most recent first
+    #[serde(default)]
+    pub recent_folders: Vec<String>,
 }
 
 impl Default for AppConfig {
@@ -24,6 +53,9 @@ impl Default for AppConfig {
         Self {
             recent_projects: Vec::new(),
             ui_scale: 1.0,
+            autosave_interval_secs: default_autosave_interval_secs(),
+            import_pattern: default_import_pattern(),
+            recent_folders: Vec::new(),
         }
     }
 }
@@ -78,6 +110,13 @@ impl AppConfig {
         self.save();
     }
 
+    pub fn add_recent_folder(&mut self, folder: &str) {
+        self.recent_folders.retain(|f| f != folder);
+        self.recent_folders.insert(0, folder.to_string());
+        self.recent_folders.truncate(MAX_RECENT_FOLDERS);
+        self.save();
+    }
+
     pub fn remove_recent(&mut self, path: &str) {
         self.recent_projects.retain(|p| p != path);
         self.save();
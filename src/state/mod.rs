@@ -3,5 +3,8 @@ mod config;
 mod types;
 
 pub use app_state::AppState;
-pub use config::DEFAULT_PANEL_MARGIN;
-pub use types::{ActiveTab, ContextMenuTarget, GalleryDrag, PendingAction, ZOOM_LEVELS};
+pub use config::{AppConfig, AUTOSAVE_INTERVALS, DEFAULT_PANEL_MARGIN};
+pub use types::{
+    ActiveTab, BrowsePurpose, ContextMenuTarget, GalleryDrag, PendingAction, RetargetSettings,
+    StateReplaceSettings, ZOOM_LEVELS,
+};
@@ -1,3 +1,10 @@
+use std::sync::Arc;
+
+use crate::export::Exporter;
+use crate::file::{BrowseMode, FileFilter};
+use crate::imaging::SheetGrid;
+use crate::model::{Angle, PartMap, Project, ReplaceScope};
+
 /// Zoom levels available in the application
 pub const ZOOM_LEVELS: [f32; 14] = [
     0.25, 0.5, 1.0, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 24.0, 32.0, 64.0, 128.0,
@@ -20,6 +27,101 @@ pub enum PendingAction {
     Exit,
 }
 
+/// What a path picked in the in-app file browser is used for
+#[derive(Clone, Debug)]
+pub enum BrowsePurpose {
+    OpenProject,
+    SaveProjectAs { then: Option<PendingAction> }, // Action to resume after saving
+    ExportAnimation { exporter: Arc<dyn Exporter> },
+    ExportAllAnimations { exporter: Arc<dyn Exporter> },
+    ReferenceImage,
+    RotationImage { angle: Angle }, // Rotation wheel slot in the character editor
+    ImportDialogImage,            // Fills the path field of the Import Rotation Image dialog
+    FolderImport,
+    Spritesheet,
+}
+
+const PROJECT_FILTERS: &[FileFilter] = &[FileFilter {
+    name: "Pixel Sprite Studio Project",
+    extensions: &["pssb", "pss", "json"],
+}];
+const SAVE_PROJECT_FILTERS: &[FileFilter] = &[
+    FileFilter {
+        name: "Pixel Sprite Studio Bundle",
+        extensions: &["pssb"],
+    },
+    FileFilter {
+        name: "Pixel Sprite Studio Project (single file)",
+        extensions: &["pss"],
+    },
+];
+const IMAGE_FILTERS: &[FileFilter] = &[
+    FileFilter {
+        name: "PNG Images",
+        extensions: &["png"],
+    },
+    FileFilter {
+        name: "All Images",
+        extensions: &["png", "jpg", "jpeg"],
+    },
+];
+
+impl BrowsePurpose {
+    pub fn mode(&self) -> BrowseMode {
+        match self {
+            BrowsePurpose::SaveProjectAs { .. } | BrowsePurpose::ExportAnimation { .. } => {
+                BrowseMode::Save
+            }
+            BrowsePurpose::ExportAllAnimations { .. } | BrowsePurpose::FolderImport => {
+                BrowseMode::Folder
+            }
+            _ => BrowseMode::Open,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            BrowsePurpose::OpenProject => "Open Project",
+            BrowsePurpose::SaveProjectAs { .. } => "Save Project As",
+            BrowsePurpose::ExportAnimation { .. } => "Export Animation",
+            BrowsePurpose::ExportAllAnimations { .. } => "Export All Animations To Folder",
+            BrowsePurpose::ReferenceImage => "Load Reference Image",
+            BrowsePurpose::RotationImage { .. } | BrowsePurpose::ImportDialogImage => {
+                "Import Rotation Image"
+            }
+            BrowsePurpose::FolderImport => "Choose Folder To Import",
+            BrowsePurpose::Spritesheet => "Choose Spritesheet",
+        }
+    }
+
+    pub fn filters(&self) -> &'static [FileFilter] {
+        match self {
+            BrowsePurpose::OpenProject => PROJECT_FILTERS,
+            BrowsePurpose::SaveProjectAs { .. } => SAVE_PROJECT_FILTERS,
+            BrowsePurpose::ExportAnimation { exporter } => exporter.filters(),
+            BrowsePurpose::ExportAllAnimations { .. } | BrowsePurpose::FolderImport => &[],
+            BrowsePurpose::ReferenceImage
+            | BrowsePurpose::RotationImage { .. }
+            | BrowsePurpose::ImportDialogImage
+            | BrowsePurpose::Spritesheet => IMAGE_FILTERS,
+        }
+    }
+
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            BrowsePurpose::SaveProjectAs { .. } => "project.pssb",
+            BrowsePurpose::ExportAnimation { exporter } => exporter.default_file_name(),
+            _ => "",
+        }
+    }
+
+    /// Whether the picked file is an image worth showing thumbnails for
+    pub fn shows_thumbnails(&self) -> bool {
+        self.filters().iter().any(|f| f.extensions.contains(&"png"))
+            && self.mode() == BrowseMode::Open
+    }
+}
+
 #[derive(Clone)]
 pub struct DraggedPart {
     pub character_id: u64,
@@ -30,7 +132,7 @@ pub struct DraggedPart {
 #[derive(Clone)]
 pub struct GalleryDrag {
     pub character_id: u64,
-    pub character_name: String, // Kept for texture cache keys
+    pub image_hash: Option<String>, // Used for texture cache keys
     pub part_name: String,
     pub state_name: String,
 }
@@ -39,7 +141,77 @@ pub struct GalleryDrag {
 pub enum ContextMenuTarget {
     Character { char_name: String },
     Part { char_name: String, part_name: String },
+    State { char_name: String, part_name: String, state_name: String },
     Animation { char_name: String, anim_index: usize, anim_name: String },
-    Frame { char_name: String, anim_index: usize, frame_index: usize },
+    Frame { char_name: String, anim_index: usize, frame_index: usize, frame_count: usize },
     Layer { layer_id: u64, layer_name: String },
 }
+
+/// Settings of the spritesheet slicing dialog
+#[derive(Clone, Debug, Default)]
+pub struct SheetImportSettings {
+    pub path: String,
+    pub grid: SheetGrid,
+    pub start_angle: Angle,  // Angle of the first cell
+    pub clockwise: bool,     // Direction the following cells go around
+    pub auto_trim: bool,     // Crop each cell to its opaque pixels
+}
+
+/// Settings of the "Copy animation to character" dialog
+#[derive(Clone, Debug, Default)]
+pub struct RetargetSettings {
+    pub source_character: String,
+    pub animation_name: String,
+    pub target_character: Option<String>,
+    pub part_map: PartMap,       // Source part -> target part (None drops it)
+    pub scale_positions: bool,   // Scale positions by the ratio of the canvas sizes
+}
+
+/// Settings of the "Find and Replace States" dialog
+#[derive(Clone, Debug, Default)]
+pub struct StateReplaceSettings {
+    pub character: Option<String>,
+    pub part: Option<String>,
+    pub find_state: Option<String>,
+    pub find_rotation: Option<Angle>,    // Only layers at this angle
+    pub replace_state: Option<String>,
+    pub replace_rotation: Option<Angle>, // Also turn the layers to this angle
+    pub scope: ReplaceScope,
+}
+
+/// The project as it was before an edit that Edit > Undo takes back in one step.
+/// Other edits aren't recorded, so the step is only offered until the project changes.
+#[derive(Clone, Debug)]
+pub struct UndoStep {
+    pub label: String,
+    pub project: Project,
+    pub revision: u64, // Revision right after the edit
+}
+
+/// Rotations generated for a part state, shown in the rotation wheel until accepted
+#[derive(Clone, Debug)]
+pub struct RotationPreviews {
+    pub char_name: String,
+    pub part_name: String,
+    pub state_name: String,
+    pub rotations: Vec<RotationPreview>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RotationPreview {
+    pub angle: Angle,
+    pub source_angle: Angle,
+    pub source_image: String, // Hash of the source image the preview was made from
+    pub image: image::RgbaImage,
+}
+
+impl RotationPreviews {
+    /// Whether these previews belong to the given part state
+    pub fn is_for(&self, char_name: &str, part_name: &str, state_name: &str) -> bool {
+        self.char_name == char_name && self.part_name == part_name && self.state_name == state_name
+    }
+
+    pub fn get(&self, angle: Angle) -> Option<&RotationPreview> {
+        self.rotations.iter().find(|p| p.angle == angle)
+    }
+}
@@ -1,9 +1,12 @@
 use bevy_egui::egui;
+use std::sync::Arc;
 
+use crate::export::composite_frame;
 use crate::imaging::{
-    decode_base64_to_texture, decode_base64_to_yellow_texture, is_pixel_opaque,
-    load_reference_texture,
+    effects_layer, image_texture_key, is_pixel_opaque, load_reference_texture, rgba_to_texture,
+    rgba_to_yellow_texture,
 };
+use crate::model::{mirrored_key, Angle};
 use crate::state::ActiveTab;
 use crate::state::AppState;
 use crate::ui::widgets::{calculate_fit_zoom, scaled_font, scaled_margin};
@@ -14,14 +17,64 @@ struct PlacedPartRenderInfo {
     part_name: String,
     layer_name: String,
     character_id: u64,
-    character_name: String, // Used for texture cache keys
+    character_name: String,
     state_name: String,
-    rotation: u16,
+    rotation: Angle,
     position: (f32, f32),
-    image_data: Option<String>,
+    image_hash: Option<String>, // Used for texture cache keys
+    image: Option<Arc<image::RgbaImage>>, // Decoded pixels from the project's image cache
     visible: bool,
 }
 
+impl PlacedPartRenderInfo {
+    fn texture_key(&self) -> Option<String> {
+        self.image_hash.as_deref().map(image_texture_key)
+    }
+}
+
+/// Keep the outline and shadow texture of the current frame cached under `key`,
+/// or drop it when the character has no effects enabled
+fn refresh_effects_texture(ctx: &egui::Context, state: &mut AppState, key: &str) {
+    let content_hash = state
+        .current_animation()
+        .and_then(|a| a.frames.get(state.current_frame))
+        .zip(state.project.as_ref())
+        .map(|(frame, project)| project.frame_content_hash(frame));
+    let active = state
+        .active_character_ref()
+        .is_some_and(|c| c.effects.is_active());
+    let (Some(content_hash), true) = (content_hash, active) else {
+        state.frame_thumbnail_cache.remove(key);
+        return;
+    };
+    let is_current = state
+        .frame_thumbnail_cache
+        .get(key)
+        .is_some_and(|(_, hash)| *hash == content_hash);
+    if is_current {
+        return;
+    }
+
+    let layer = match (&state.project, state.active_character_ref(), state.current_animation()) {
+        (Some(project), Some(character), Some(animation)) => composite_frame(
+            project,
+            animation,
+            state.current_frame,
+            character.canvas_size,
+            state.preview_outfit.as_deref(),
+        )
+        .ok()
+        .and_then(|frame| effects_layer(&frame, &character.effects)),
+        _ => None,
+    };
+    if let Some(layer) = layer {
+        let texture = rgba_to_texture(ctx, key, &layer);
+        state
+            .frame_thumbnail_cache
+            .insert(key.to_string(), (texture, content_hash));
+    }
+}
+
 pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
     // Reference image render info
     struct ReferenceRenderInfo {
@@ -58,42 +111,53 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
                 thumbnail: project.reference_thumbnails.get(&r.file_path).cloned(),
             });
 
+        // Sprites as worn in the previewed outfit; parts inside nested animations
+        // carry the id of the layer that holds them so they select and drag as one
         let parts: Vec<PlacedPartRenderInfo> = active_char
             .and_then(|name| project.get_character(name))
             .and_then(|c| c.animations.get(state.current_animation))
-            .and_then(|anim| anim.frames.get(state.current_frame))
-            .map(|frame| {
-                frame
-                    .placed_parts
-                    .iter()
-                    .map(|p| {
+            .map(|anim| {
+                project
+                    .frame_sprites(anim, state.current_frame, state.preview_outfit.as_deref())
+                    .into_iter()
+                    .map(|sprite| {
+                        let p = sprite.placed;
                         // Look up character by ID (stable across renames)
                         let character = project.get_character_by_id(p.character_id);
+                        // Mirrored rotations get their own flipped texture
+                        let image_hash = sprite
+                            .rotation
+                            .and_then(|r| r.image.as_deref())
+                            .map(|hash| {
+                                if sprite.mirrored {
+                                    mirrored_key(hash)
+                                } else {
+                                    hash.to_string()
+                                }
+                            });
+                        let image = project.sprite_pixels(&sprite).and_then(|r| r.ok());
 
-                        // Look up image data for this part
-                        let image_data = character
-                            .and_then(|c| c.get_part(&p.part_name))
-                            .and_then(|part| part.states.iter().find(|s| s.name == p.state_name))
-                            .and_then(|s| s.rotations.get(&p.rotation))
-                            .and_then(|r| r.image_data.clone());
-
-                        // Get character name for texture cache keys
                         let character_name = character.map(|c| c.name.clone()).unwrap_or_default();
+                        let (part_name, state_name) = project
+                            .placed_state(p)
+                            .map(|(part, s)| (part.name.clone(), s.name.clone()))
+                            .unwrap_or_default();
 
                         PlacedPartRenderInfo {
-                            id: p.id,
-                            part_name: p.part_name.clone(),
+                            id: sprite.owner.id,
                             layer_name: if p.layer_name.is_empty() {
-                                p.part_name.clone()
+                                part_name.clone()
                             } else {
                                 p.layer_name.clone()
                             },
+                            part_name,
                             character_id: p.character_id,
                             character_name,
-                            state_name: p.state_name.clone(),
+                            state_name,
                             rotation: p.rotation,
-                            position: p.position,
-                            image_data,
+                            position: sprite.position,
+                            image_hash,
+                            image,
                             visible: p.visible,
                         }
                     })
@@ -300,6 +364,24 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
         egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 100, 120)),
     );
 
+    // Outline and shadow of the composited frame, under the parts
+    let effects_key = format!(
+        "effects/{}/{}/{}/{}",
+        char_name,
+        state.current_animation,
+        state.current_frame,
+        state.preview_outfit.as_deref().unwrap_or("")
+    );
+    refresh_effects_texture(ui.ctx(), state, &effects_key);
+    if let Some((texture, _)) = state.frame_thumbnail_cache.get(&effects_key) {
+        painter.image(
+            texture.id(),
+            canvas_rect,
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        );
+    }
+
     // Draw placed parts (in list order - later items drawn on top)
     let show_labels = state.show_labels;
     // Pre-calculate canvas origin in pixels for sprite positioning
@@ -321,22 +403,17 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
         let is_selected = state.selected_part_id == Some(part_info.id);
 
         // Try to get or create texture for this part
-        let texture_key = format!(
-            "{}/{}/{}/{}",
-            part_info.character_name, part_info.part_name, part_info.state_name, part_info.rotation
-        );
+        let texture_key = part_info.texture_key().unwrap_or_default();
 
         let mut rendered_texture = false;
         let mut image_size = (16.0_f32, 16.0_f32);
         let mut part_rect = egui::Rect::NOTHING;
 
-        if let Some(ref base64_data) = part_info.image_data {
+        if let Some(ref image) = part_info.image {
             // Check if texture is already cached
             if !state.texture_cache.contains_key(&texture_key) {
-                // Decode and create texture
-                if let Ok(texture) = decode_base64_to_texture(ui.ctx(), &texture_key, base64_data) {
-                    state.texture_cache.insert(texture_key.clone(), texture);
-                }
+                let texture = rgba_to_texture(ui.ctx(), &texture_key, image);
+                state.texture_cache.insert(texture_key.clone(), texture);
             }
 
             if let Some(texture) = state.texture_cache.get(&texture_key) {
@@ -408,12 +485,9 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
                 // Get or create yellow silhouette texture
                 let yellow_key = format!("{}_yellow", texture_key);
                 if !state.texture_cache.contains_key(&yellow_key) {
-                    if let Some(ref base64_data) = part_info.image_data {
-                        if let Ok(yellow_tex) =
-                            decode_base64_to_yellow_texture(ui.ctx(), &yellow_key, base64_data)
-                        {
-                            state.texture_cache.insert(yellow_key.clone(), yellow_tex);
-                        }
+                    if let Some(ref image) = part_info.image {
+                        let yellow_tex = rgba_to_yellow_texture(ui.ctx(), &yellow_key, image);
+                        state.texture_cache.insert(yellow_key.clone(), yellow_tex);
                     }
                 }
 
@@ -561,6 +635,9 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
         let canvas_delta_y = delta.y / effective_zoom;
 
         // Update reference position in project
+        if delta != egui::Vec2::ZERO {
+            state.mark_changed();
+        }
         if let Some(ref mut project) = state.project {
             if let Some(ref cn) = state.active_character {
                 if let Some(character) = project.get_character_mut(cn) {
@@ -615,13 +692,10 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
                 // Place the part (centered on drop position)
                 if let Some(gallery_drag) = state.gallery_drag.take() {
                     // Get sprite size to center it
-                    let texture_key = format!(
-                        "gallery/{}/{}",
-                        gallery_drag.character_name, gallery_drag.part_name
-                    );
-                    let sprite_size = state
-                        .texture_cache
-                        .get(&texture_key)
+                    let sprite_size = gallery_drag
+                        .image_hash
+                        .as_deref()
+                        .and_then(|hash| state.texture_cache.get(&image_texture_key(hash)))
                         .map(|t| t.size_vec2())
                         .unwrap_or(egui::vec2(16.0, 16.0));
 
@@ -662,13 +736,10 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
                 let hit_origin_pixels_y = canvas_origin_y * ppp;
                 let screen_x = (hit_origin_pixels_x + part_info.position.0 * state.zoom_level) / ppp;
                 let screen_y = (hit_origin_pixels_y + part_info.position.1 * state.zoom_level) / ppp;
-                let part_size = if let Some(texture) = state.texture_cache.get(&format!(
-                    "{}/{}/{}/{}",
-                    part_info.character_name,
-                    part_info.part_name,
-                    part_info.state_name,
-                    part_info.rotation
-                )) {
+                let part_size = if let Some(texture) = part_info
+                    .texture_key()
+                    .and_then(|key| state.texture_cache.get(&key))
+                {
                     texture.size_vec2() * state.zoom_level / ppp
                 } else {
                     egui::vec2(16.0, 16.0) * state.zoom_level / ppp
@@ -695,7 +766,7 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
     if should_check_selection {
         if let Some(pos) = response.interact_pointer_pos() {
             // Collect all parts whose bounding boxes contain the click (top to bottom)
-            let mut candidates: Vec<(u64, Option<&String>, f32, f32, egui::Vec2)> = Vec::new();
+            let mut candidates: Vec<(u64, Option<&image::RgbaImage>, f32, f32, egui::Vec2)> = Vec::new();
 
             for part_info in placed_parts.iter().rev() {
                 // Skip invisible layers - they shouldn't be selectable
@@ -710,13 +781,10 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
                 let screen_y =
                     (click_origin_pixels_y + part_info.position.1 * state.zoom_level) / ppp;
                 // Use cached texture size if available, otherwise default 16x16
-                let part_size = if let Some(texture) = state.texture_cache.get(&format!(
-                    "{}/{}/{}/{}",
-                    part_info.character_name,
-                    part_info.part_name,
-                    part_info.state_name,
-                    part_info.rotation
-                )) {
+                let part_size = if let Some(texture) = part_info
+                    .texture_key()
+                    .and_then(|key| state.texture_cache.get(&key))
+                {
                     texture.size_vec2() * state.zoom_level / ppp
                 } else {
                     egui::vec2(16.0, 16.0) * state.zoom_level / ppp
@@ -725,7 +793,7 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
                 if part_rect.contains(pos) {
                     candidates.push((
                         part_info.id,
-                        part_info.image_data.as_ref(),
+                        part_info.image.as_deref(),
                         screen_x,
                         screen_y,
                         part_size,
@@ -737,7 +805,7 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
             let mut clicked_part = None;
             let mut topmost_fallback = None;
 
-            for (id, image_data, screen_x, screen_y, _part_size) in &candidates {
+            for (id, image, screen_x, screen_y, _part_size) in &candidates {
                 // Remember the topmost as fallback
                 if topmost_fallback.is_none() {
                     topmost_fallback = Some(*id);
@@ -748,8 +816,8 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
                 let pixel_y = ((pos.y - screen_y) * ppp / state.zoom_level) as u32;
 
                 // Check if pixel is opaque
-                if let Some(data) = image_data {
-                    if is_pixel_opaque(data, pixel_x, pixel_y) {
+                if let Some(image) = image {
+                    if is_pixel_opaque(image, pixel_x, pixel_y) {
                         clicked_part = Some(*id);
                         break;
                     }
@@ -792,13 +860,10 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
                     (dbl_origin_pixels_x + part_info.position.0 * state.zoom_level) / ppp;
                 let screen_y =
                     (dbl_origin_pixels_y + part_info.position.1 * state.zoom_level) / ppp;
-                let part_size = if let Some(texture) = state.texture_cache.get(&format!(
-                    "{}/{}/{}/{}",
-                    part_info.character_name,
-                    part_info.part_name,
-                    part_info.state_name,
-                    part_info.rotation
-                )) {
+                let part_size = if let Some(texture) = part_info
+                    .texture_key()
+                    .and_then(|key| state.texture_cache.get(&key))
+                {
                     texture.size_vec2() * state.zoom_level / ppp
                 } else {
                     egui::vec2(16.0, 16.0) * state.zoom_level / ppp
@@ -810,8 +875,8 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
                     let pixel_x = ((pos.x - screen_x) * ppp / state.zoom_level) as u32;
                     let pixel_y = ((pos.y - screen_y) * ppp / state.zoom_level) as u32;
 
-                    let is_hit = if let Some(data) = &part_info.image_data {
-                        is_pixel_opaque(data, pixel_x, pixel_y)
+                    let is_hit = if let Some(image) = &part_info.image {
+                        is_pixel_opaque(image, pixel_x, pixel_y)
                     } else {
                         true
                     };
@@ -975,11 +1040,11 @@ pub fn render_canvas(ui: &mut egui::Ui, state: &mut AppState) {
             );
 
             // Try to draw the thumbnail
-            let texture_key = format!(
-                "gallery/{}/{}",
-                gallery_drag.character_name, gallery_drag.part_name
-            );
-            if let Some(texture) = state.texture_cache.get(&texture_key) {
+            let texture = gallery_drag
+                .image_hash
+                .as_deref()
+                .and_then(|hash| state.texture_cache.get(&image_texture_key(hash)));
+            if let Some(texture) = texture {
                 let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
                 painter.image(texture.id(), drag_rect.shrink(2.0), uv, egui::Color32::WHITE);
             } else {
@@ -1,5 +1,8 @@
 use bevy_egui::egui;
 
+use crate::model::{
+    Angle, Outfit, OutfitSwap, OutlineCorners, Project, RotationMode, UsageQuery,
+};
 use crate::state::{ActiveTab, ContextMenuTarget};
 use crate::state::AppState;
 use crate::ui::rotation_wheel::render_rotation_wheel;
@@ -11,7 +14,7 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
     }
 
     // Get character data
-    let (parts, selected_part_states, selected_state_rotations) = {
+    let (parts, library_choices, selected_part_states, selected_state_rotations) = {
         let Some(ref project) = state.project else {
             ui.label("No project loaded");
             return;
@@ -21,27 +24,46 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
             return;
         };
 
-        let parts: Vec<String> = character.parts.iter().map(|p| p.name.clone()).collect();
+        // (name, linked from the library, users of the library part with this name)
+        let parts: Vec<(String, bool, usize)> = project
+            .character_parts(character)
+            .map(|p| {
+                let users = if project.library_part(&p.name).is_some() {
+                    project.library_part_users(&p.name)
+                } else {
+                    0
+                };
+                (p.name.clone(), character.uses_library_part(&p.name), users)
+            })
+            .collect();
+
+        // Library parts this character doesn't use yet
+        let library_choices: Vec<String> = project
+            .part_library
+            .iter()
+            .filter(|p| !character.library_parts.contains(&p.name))
+            .map(|p| p.name.clone())
+            .collect();
 
         let selected_part_states: Vec<(String, bool)> = state
             .editor_selected_part
             .as_ref()
-            .and_then(|pn| character.get_part(pn))
+            .and_then(|pn| project.resolve_part(character, pn))
             .map(|p| {
                 p.states
                     .iter()
                     .map(|s| {
-                        let has_images = s.rotations.values().any(|r| r.image_data.is_some());
+                        let has_images = s.rotations.values().any(|r| r.image.is_some());
                         (s.name.clone(), has_images)
                     })
                     .collect()
             })
             .unwrap_or_default();
 
-        let selected_state_rotations: Vec<(u16, bool)> = state
+        let selected_state_rotations: Vec<(Angle, bool)> = state
             .editor_selected_part
             .as_ref()
-            .and_then(|pn| character.get_part(pn))
+            .and_then(|pn| project.resolve_part(character, pn))
             .and_then(|p| {
                 let state_name = state
                     .editor_selected_state
@@ -52,12 +74,12 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
             .map(|s| {
                 s.rotations
                     .iter()
-                    .map(|(angle, r)| (*angle, r.image_data.is_some()))
+                    .map(|(angle, r)| (*angle, r.image.is_some()))
                     .collect()
             })
             .unwrap_or_default();
 
-        (parts, selected_part_states, selected_state_rotations)
+        (parts, library_choices, selected_part_states, selected_state_rotations)
     };
 
     // Character settings
@@ -72,6 +94,7 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
 
     // Handle rename if name changed
     if new_name != char_name && !new_name.is_empty() {
+        state.mark_changed();
         if let Some(ref mut project) = state.project {
             // Check if new name doesn't conflict with existing character
             let name_exists = project.characters.iter().any(|c| c.name == new_name);
@@ -91,19 +114,33 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
 
     // Frame size
     ui.horizontal(|ui| {
-        ui.label("Frame size:");
+        let mut size_changed = false;
         if let Some(ref mut project) = state.project {
             if let Some(character) = project.get_character_mut(char_name) {
                 let mut w = character.canvas_size.0 as i32;
                 let mut h = character.canvas_size.1 as i32;
-                ui.add(egui::DragValue::new(&mut w).speed(1).range(8..=512));
+                size_changed |= ui
+                    .add(egui::DragValue::new(&mut w).speed(1).range(8..=512))
+                    .changed();
                 ui.label("x");
-                ui.add(egui::DragValue::new(&mut h).speed(1).range(8..=512));
+                size_changed |= ui
+                    .add(egui::DragValue::new(&mut h).speed(1).range(8..=512))
+                    .changed();
                 character.canvas_size = (w.max(8) as u32, h.max(8) as u32);
             }
         }
+        if size_changed {
+            state.mark_changed();
+        }
     });
 
+    egui::CollapsingHeader::new("Outfits")
+        .id_salt(("outfits", char_name))
+        .show(ui, |ui| render_outfit_editor(ui, state, char_name));
+    egui::CollapsingHeader::new("Effects")
+        .id_salt(("effects", char_name))
+        .show(ui, |ui| render_effects_editor(ui, state, char_name));
+
     ui.separator();
 
     // Three-column layout: 20% / 20% / 60%
@@ -122,9 +159,20 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
                 if parts.is_empty() {
                     ui.label("(No parts)");
                 }
-                for part_name in &parts {
+                for (part_name, is_shared, library_users) in &parts {
                     let is_selected = state.editor_selected_part.as_ref() == Some(part_name);
-                    let response = ui.selectable_label(is_selected, part_name);
+                    let response = if *is_shared {
+                        ui.selectable_label(
+                            is_selected,
+                            egui::RichText::new(format!("{} (shared)", part_name)).italics(),
+                        )
+                        .on_hover_text(format!(
+                            "From the part library, used by {} character(s). Edits apply to all of them.",
+                            library_users
+                        ))
+                    } else {
+                        ui.selectable_label(is_selected, part_name)
+                    };
                     if response.clicked() {
                         state.editor_selected_part = Some(part_name.clone());
                         state.editor_selected_state = None;
@@ -141,7 +189,50 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
                             state.dialog_needs_focus = true;
                             ui.close_menu();
                         }
-                        if ui.button("Delete").clicked() {
+                        if ui.button("Find Usages").clicked() {
+                            state.find_usages(UsageQuery::part(char_name, part_name));
+                            ui.close_menu();
+                        }
+                        if *is_shared {
+                            if ui
+                                .button("Make Local Copy")
+                                .on_hover_text("Stop sharing: this character gets its own copy")
+                                .clicked()
+                            {
+                                apply_library_edit(
+                                    state,
+                                    format!("'{}' is now local to {}", part_name, char_name),
+                                    |p| p.make_local_copy(char_name, part_name),
+                                );
+                                ui.close_menu();
+                            }
+                        } else if *library_users > 0 {
+                            if ui
+                                .button("Use Library Version")
+                                .on_hover_text("Replace this character's copy with the shared part")
+                                .clicked()
+                            {
+                                apply_library_edit(
+                                    state,
+                                    format!("{} now uses the shared '{}'", char_name, part_name),
+                                    |p| p.link_library_part(char_name, part_name),
+                                );
+                                ui.close_menu();
+                            }
+                        } else if ui
+                            .button("Share to Library")
+                            .on_hover_text("Move this part to the project library so other characters can use it")
+                            .clicked()
+                        {
+                            apply_library_edit(
+                                state,
+                                format!("Shared '{}' to the part library", part_name),
+                                |p| p.share_part(char_name, part_name),
+                            );
+                            ui.close_menu();
+                        }
+                        let delete_label = if *is_shared { "Remove" } else { "Delete" };
+                        if ui.button(delete_label).clicked() {
                             state.context_menu_target = Some(ContextMenuTarget::Part {
                                 char_name: char_name.to_string(),
                                 part_name: part_name.clone(),
@@ -158,6 +249,22 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
                     state.new_part_name.clear();
                     state.dialog_needs_focus = true;
                 }
+                ui.add_enabled_ui(!library_choices.is_empty(), |ui| {
+                    ui.menu_button("+ From Library", |ui| {
+                        for part_name in &library_choices {
+                            if ui.button(part_name).clicked() {
+                                apply_library_edit(
+                                    state,
+                                    format!("Added shared part '{}'", part_name),
+                                    |p| p.link_library_part(char_name, part_name),
+                                );
+                                state.editor_selected_part = Some(part_name.clone());
+                                state.editor_selected_state = None;
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                });
             },
         );
 
@@ -186,9 +293,35 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
                             egui::RichText::new(state_name)
                         };
 
-                        if ui.selectable_label(is_selected, label).clicked() {
+                        let response = ui.selectable_label(is_selected, label);
+                        if response.clicked() {
                             state.editor_selected_state = Some(state_name.clone());
                         }
+                        response.context_menu(|ui| {
+                            let part_name = state.editor_selected_part.clone().unwrap_or_default();
+                            let target = ContextMenuTarget::State {
+                                char_name: char_name.to_string(),
+                                part_name: part_name.clone(),
+                                state_name: state_name.clone(),
+                            };
+                            if ui.button("Rename...").clicked() {
+                                state.context_menu_target = Some(target.clone());
+                                state.rename_new_name = state_name.clone();
+                                state.show_rename_dialog = true;
+                                state.dialog_needs_focus = true;
+                                ui.close_menu();
+                            }
+                            if ui.button("Find Usages").clicked() {
+                                let query = UsageQuery::state(char_name, &part_name, state_name);
+                                state.find_usages(query);
+                                ui.close_menu();
+                            }
+                            if ui.button("Delete").clicked() {
+                                state.context_menu_target = Some(target);
+                                state.show_delete_confirm_dialog = true;
+                                ui.close_menu();
+                            }
+                        });
                     }
 
                     ui.separator();
@@ -214,7 +347,17 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
                 ui.separator();
 
                 if state.editor_selected_part.is_some() {
+                    render_rotation_mode(ui, state, char_name);
                     render_rotation_wheel(ui, state, char_name, &selected_state_rotations);
+                    ui.add_space(8.0);
+                    if ui
+                        .button("Import Spritesheet...")
+                        .on_hover_text("Slice a sheet of directions into this state's rotations")
+                        .clicked()
+                    {
+                        state.show_sheet_import_dialog = true;
+                    }
+                    render_rotation_generation(ui, state, char_name);
                 } else {
                     ui.label("Select a part and state");
                 }
@@ -222,3 +365,399 @@ pub fn render_character_editor(ui: &mut egui::Ui, state: &mut AppState, char_nam
         );
     });
 }
+
+/// Edit a character's outfits: which ones exist, which get exported, and what each swaps
+fn render_outfit_editor(ui: &mut egui::Ui, state: &mut AppState, char_name: &str) {
+    let (outfits, part_states, selected_swaps) = {
+        let Some(ref project) = state.project else {
+            return;
+        };
+        let Some(character) = project.get_character(char_name) else {
+            return;
+        };
+
+        // (name, exported)
+        let outfits: Vec<(String, bool)> = character
+            .outfits
+            .iter()
+            .map(|o| (o.name.clone(), character.export_outfits.contains(&o.name)))
+            .collect();
+
+        // (part name, its state names)
+        let part_states: Vec<(String, Vec<String>)> = project
+            .character_parts(character)
+            .map(|p| (p.name.clone(), p.states.iter().map(|s| s.name.clone()).collect()))
+            .collect();
+
+        let selected_swaps = state
+            .editor_selected_outfit
+            .as_ref()
+            .and_then(|name| character.get_outfit(name))
+            .map(|o| o.swaps.clone());
+
+        (outfits, part_states, selected_swaps)
+    };
+
+    let mut add_outfit = false;
+    let mut delete_outfit = false;
+    let mut rename_to: Option<String> = None;
+    let mut set_exported: Option<bool> = None;
+    let mut swap_change: Option<(String, Option<OutfitSwap>)> = None;
+
+    ui.horizontal_wrapped(|ui| {
+        for (name, exported) in &outfits {
+            let is_selected = state.editor_selected_outfit.as_ref() == Some(name);
+            let label = if *exported {
+                egui::RichText::new(name).strong()
+            } else {
+                egui::RichText::new(name)
+            };
+            if ui.selectable_label(is_selected, label).clicked() {
+                state.editor_selected_outfit = Some(name.clone());
+            }
+        }
+        if ui.button("+ Add Outfit").clicked() {
+            add_outfit = true;
+        }
+    });
+
+    if let (Some(outfit_name), Some(swaps)) = (state.editor_selected_outfit.clone(), selected_swaps) {
+        let exported = outfits.iter().any(|(n, e)| *n == outfit_name && *e);
+        ui.horizontal(|ui| {
+            ui.label("Name:");
+            let mut name = outfit_name.clone();
+            if ui.text_edit_singleline(&mut name).changed() {
+                rename_to = Some(name);
+            }
+            let mut export = exported;
+            if ui
+                .checkbox(&mut export, "Export")
+                .on_hover_text("Export All writes a set of sheets in this outfit")
+                .changed()
+            {
+                set_exported = Some(export);
+            }
+            if ui.button("Delete").clicked() {
+                delete_outfit = true;
+            }
+        });
+
+        egui::Grid::new(("outfit_swaps", char_name))
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (part_name, states) in &part_states {
+                    ui.label(part_name);
+                    let current = swaps.get(part_name);
+                    let selected_text = match current {
+                        None => "(unchanged)".to_string(),
+                        Some(OutfitSwap::State(s)) => format!("State: {}", s),
+                        Some(OutfitSwap::Part(p)) => format!("Part: {}", p),
+                    };
+                    egui::ComboBox::from_id_salt(("outfit_swap", part_name))
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            if ui.selectable_label(current.is_none(), "(unchanged)").clicked() {
+                                swap_change = Some((part_name.clone(), None));
+                            }
+                            for state_name in states {
+                                let swap = OutfitSwap::State(state_name.clone());
+                                let label = format!("State: {}", state_name);
+                                if ui.selectable_label(current == Some(&swap), label).clicked() {
+                                    swap_change = Some((part_name.clone(), Some(swap)));
+                                }
+                            }
+                            for (other_part, _) in part_states.iter().filter(|(n, _)| n != part_name) {
+                                let swap = OutfitSwap::Part(other_part.clone());
+                                let label = format!("Part: {}", other_part);
+                                if ui.selectable_label(current == Some(&swap), label).clicked() {
+                                    swap_change = Some((part_name.clone(), Some(swap)));
+                                }
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
+    } else if !outfits.is_empty() {
+        ui.label("Select an outfit to edit its swaps");
+    }
+
+    let edited = add_outfit
+        || delete_outfit
+        || rename_to.is_some()
+        || set_exported.is_some()
+        || swap_change.is_some();
+    if !edited {
+        return;
+    }
+    state.mark_changed();
+
+    let Some(ref mut project) = state.project else {
+        return;
+    };
+    let Some(character) = project.get_character_mut(char_name) else {
+        return;
+    };
+    let selected = state.editor_selected_outfit.clone();
+
+    if add_outfit {
+        let mut n = character.outfits.len() + 1;
+        while character.get_outfit(&format!("outfit {}", n)).is_some() {
+            n += 1;
+        }
+        let name = format!("outfit {}", n);
+        character.outfits.push(Outfit::new(name.as_str()));
+        state.editor_selected_outfit = Some(name);
+    }
+
+    let Some(selected) = selected else {
+        return;
+    };
+
+    if let Some((part_name, swap)) = swap_change {
+        if let Some(outfit) = character.get_outfit_mut(&selected) {
+            match swap {
+                Some(swap) => {
+                    outfit.swaps.insert(part_name, swap);
+                }
+                None => {
+                    outfit.swaps.remove(&part_name);
+                }
+            }
+        }
+        state.frame_thumbnail_cache.clear();
+    }
+
+    if let Some(export) = set_exported {
+        character.export_outfits.retain(|n| *n != selected);
+        if export {
+            character.export_outfits.push(selected.clone());
+        }
+    }
+
+    if let Some(new_name) = rename_to {
+        if !new_name.is_empty() && character.get_outfit(&new_name).is_none() {
+            if let Some(outfit) = character.get_outfit_mut(&selected) {
+                outfit.name = new_name.clone();
+            }
+            for name in &mut character.export_outfits {
+                if *name == selected {
+                    *name = new_name.clone();
+                }
+            }
+            if state.preview_outfit.as_ref() == Some(&selected) {
+                state.preview_outfit = Some(new_name.clone());
+            }
+            state.editor_selected_outfit = Some(new_name);
+        }
+    }
+
+    if delete_outfit {
+        character.outfits.retain(|o| o.name != selected);
+        character.export_outfits.retain(|n| *n != selected);
+        if state.preview_outfit.as_ref() == Some(&selected) {
+            state.preview_outfit = None;
+            state.frame_thumbnail_cache.clear();
+        }
+        state.editor_selected_outfit = None;
+        state.set_status(format!("Deleted outfit '{}'", selected));
+    }
+}
+
+/// Edit the outline and drop shadow applied to every composited frame of a character
+fn render_effects_editor(ui: &mut egui::Ui, state: &mut AppState, char_name: &str) {
+    let Some(mut effects) = state
+        .project
+        .as_ref()
+        .and_then(|p| p.get_character(char_name))
+        .map(|c| c.effects.clone())
+    else {
+        return;
+    };
+
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui.checkbox(&mut effects.outline.enabled, "Outline").changed();
+        changed |= ui.color_edit_button_srgb(&mut effects.outline.color).changed();
+        ui.label("Thickness:");
+        changed |= ui
+            .add(egui::DragValue::new(&mut effects.outline.thickness).range(1..=8))
+            .changed();
+        let corners_label = |corners: OutlineCorners| match corners {
+            OutlineCorners::Square => "Square corners",
+            OutlineCorners::Rounded => "Rounded corners",
+        };
+        egui::ComboBox::from_id_salt(("outline_corners", char_name))
+            .selected_text(corners_label(effects.outline.corners))
+            .show_ui(ui, |ui| {
+                for corners in [OutlineCorners::Square, OutlineCorners::Rounded] {
+                    let label = corners_label(corners);
+                    changed |= ui
+                        .selectable_value(&mut effects.outline.corners, corners, label)
+                        .changed();
+                }
+            });
+    });
+    ui.horizontal(|ui| {
+        changed |= ui.checkbox(&mut effects.shadow.enabled, "Drop shadow").changed();
+        changed |= ui.color_edit_button_srgb(&mut effects.shadow.color).changed();
+        ui.label("Offset:");
+        changed |= ui
+            .add(egui::DragValue::new(&mut effects.shadow.offset.0).range(-16..=16))
+            .changed();
+        changed |= ui
+            .add(egui::DragValue::new(&mut effects.shadow.offset.1).range(-16..=16))
+            .changed();
+        ui.label("Opacity:");
+        changed |= ui
+            .add(egui::Slider::new(&mut effects.shadow.opacity, 0.0..=1.0))
+            .changed();
+    });
+
+    if changed {
+        if let Some(character) = state
+            .project_mut()
+            .and_then(|p| p.get_character_mut(char_name))
+        {
+            character.effects = effects;
+        }
+        state.frame_thumbnail_cache.clear();
+    }
+}
+
+/// Choose how many directions the selected state is drawn in, or list its angles
+fn render_rotation_mode(ui: &mut egui::Ui, state: &mut AppState, char_name: &str) {
+    let part_name = state.editor_selected_part.clone().unwrap_or_default();
+    let state_name = state
+        .editor_selected_state
+        .clone()
+        .unwrap_or_else(|| "default".to_string());
+    let Some((mode, angles)) = state
+        .project
+        .as_ref()
+        .and_then(|project| project.get_part(char_name, &part_name))
+        .and_then(|p| p.states.iter().find(|s| s.name == state_name))
+        .map(|s| (s.rotation_mode.clone(), s.angles()))
+    else {
+        return;
+    };
+
+    let mut chosen = None;
+    ui.horizontal(|ui| {
+        ui.label("Directions:");
+        egui::ComboBox::from_id_salt("rotation_mode")
+            .selected_text(mode.label())
+            .show_ui(ui, |ui| {
+                for preset in RotationMode::PRESETS {
+                    if ui.selectable_label(mode == preset, preset.label()).clicked() {
+                        chosen = Some(preset);
+                    }
+                }
+                let is_custom = matches!(mode, RotationMode::Custom(_));
+                if ui.selectable_label(is_custom, "Custom").clicked() && !is_custom {
+                    chosen = Some(RotationMode::custom(angles.clone()));
+                }
+            });
+    });
+
+    // Custom angles are typed as a list and applied together
+    if matches!(mode, RotationMode::Custom(_)) {
+        let key = format!("{}/{}/{}", char_name, part_name, state_name);
+        let mut text = match state.custom_angles_edit {
+            Some((ref edit_key, ref text)) if *edit_key == key => text.clone(),
+            _ => angles.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", "),
+        };
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut text)
+                    .hint_text("0, 30, 150")
+                    .desired_width(200.0),
+            );
+            if response.changed() {
+                state.custom_angles_edit = Some((key, text.clone()));
+            }
+            if ui.button("Apply").clicked() {
+                match Angle::parse_list(&text) {
+                    Ok(list) => chosen = Some(RotationMode::custom(list)),
+                    Err(e) => state.set_status(e),
+                }
+            }
+        });
+    }
+
+    let Some(new_mode) = chosen.filter(|m| *m != mode) else {
+        return;
+    };
+    state.custom_angles_edit = None;
+    let label = new_mode.label();
+    match state.set_editor_rotation_mode(new_mode) {
+        Ok(0) => state.set_status(format!("{} / {}: {}", part_name, state_name, label)),
+        Ok(kept) => state.set_status(format!(
+            "Kept {} drawn rotation(s) that are not among the new angles",
+            kept
+        )),
+        Err(e) => state.set_status(e),
+    }
+}
+
+/// Buttons to generate missing rotations with RotSprite and accept or discard the previews
+fn render_rotation_generation(ui: &mut egui::Ui, state: &mut AppState, char_name: &str) {
+    let part_name = state.editor_selected_part.clone().unwrap_or_default();
+    let state_name = state
+        .editor_selected_state
+        .clone()
+        .unwrap_or_else(|| "default".to_string());
+    let preview_count = state
+        .rotation_previews
+        .as_ref()
+        .filter(|p| p.is_for(char_name, &part_name, &state_name))
+        .map(|p| p.rotations.len());
+
+    ui.horizontal(|ui| {
+        match preview_count {
+            None => {
+                if ui
+                    .button("Generate Missing Rotations")
+                    .on_hover_text(
+                        "Fill empty rotations by rotating the nearest drawn one (RotSprite); \
+                         generated rotations are made again from their current source",
+                    )
+                    .clicked()
+                {
+                    match state.preview_generated_rotations() {
+                        Ok(count) => {
+                            state.set_status(format!("Generated {} rotation(s) to review", count))
+                        }
+                        Err(e) => state.set_status(e),
+                    }
+                }
+            }
+            Some(count) => {
+                if ui.button(format!("Accept {} Generated", count)).clicked() {
+                    match state.accept_generated_rotations() {
+                        Ok(accepted) => {
+                            state.set_status(format!("Added {} generated rotation(s)", accepted))
+                        }
+                        Err(e) => state.set_status(e),
+                    }
+                }
+                if ui.button("Discard").clicked() {
+                    state.clear_rotation_previews();
+                }
+            }
+        }
+    });
+}
+
+/// Run a part library edit and report the outcome in the status bar
+fn apply_library_edit(
+    state: &mut AppState,
+    success_message: String,
+    edit: impl FnOnce(&mut Project) -> Result<(), String>,
+) {
+    let result = state.project_mut().map(edit);
+    match result {
+        Some(Ok(())) => state.set_status(success_message),
+        Some(Err(e)) => state.set_status(e),
+        None => {}
+    }
+}
@@ -1,18 +1,640 @@
 use bevy_egui::egui;
 
-use crate::file::{pick_file, pick_image_file, pick_save_file};
-use crate::imaging::import_image_as_base64;
-use crate::model::{Animation, Character, Part, RotationMode, State};
-use crate::state::{ActiveTab, ContextMenuTarget, PendingAction};
+use crate::export::SheetStatus;
+use crate::file::{apply_folder_import, bundle_path_for, plan_folder_import, NamingPattern};
+use crate::imaging::{import_image_as_base64, load_image_file_texture};
+use crate::model::{
+    Angle, Animation, Character, IssueLocation, Part, ReplaceScope, RotationMode, State,
+    UsageQuery,
+};
+use crate::state::{ActiveTab, BrowsePurpose, ContextMenuTarget, PendingAction};
 use crate::state::AppState;
 use crate::ui::widgets::format_relative_time;
 
+/// Progress of the background export, one row per spritesheet, with a cancel button
+fn render_export_progress(ctx: &egui::Context, state: &mut AppState) {
+    let Some(ref job) = state.export_job else {
+        return;
+    };
+    let sheets = job.sheets();
+    let cancelling = job.is_cancelled();
+    let mut cancel = false;
+
+    egui::Window::new("Exporting")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-16.0, -40.0])
+        .show(ctx, |ui| {
+            egui::Grid::new("export_progress").num_columns(2).show(ui, |ui| {
+                for sheet in &sheets {
+                    ui.label(&sheet.label);
+                    let fraction = sheet.frames_done as f32 / sheet.frame_count.max(1) as f32;
+                    let text = match sheet.status {
+                        SheetStatus::Waiting => "Waiting".to_string(),
+                        SheetStatus::Rendering => {
+                            format!("{} / {} frames", sheet.frames_done, sheet.frame_count)
+                        }
+                        SheetStatus::Done => "Done".to_string(),
+                        SheetStatus::Failed(ref e) => format!("Failed: {}", e),
+                        SheetStatus::Cancelled => "Cancelled".to_string(),
+                    };
+                    ui.add(
+                        egui::ProgressBar::new(fraction)
+                            .desired_width(200.0)
+                            .text(text),
+                    );
+                    ui.end_row();
+                }
+            });
+            ui.add_space(6.0);
+            if ui
+                .add_enabled(!cancelling, egui::Button::new("Cancel"))
+                .clicked()
+            {
+                cancel = true;
+            }
+        });
+
+    if cancel {
+        job.cancel();
+    }
+    // Keep the bars moving while the worker runs
+    ctx.request_repaint_after(std::time::Duration::from_millis(100));
+}
+
+/// Problems found by Validate Project; clicking one shows it, fix buttons repair it
+fn render_validation_dialog(ctx: &egui::Context, state: &mut AppState) {
+    let mut go_to = None;
+    let mut fix = None;
+    let mut open = true;
+    egui::Window::new("Validate Project")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(520.0)
+        .show(ctx, |ui| {
+            if state.validation_issues.is_empty() {
+                ui.label("No problems found.");
+            } else {
+                ui.label(format!(
+                    "{} problem(s) found. Click one to show it.",
+                    state.validation_issues.len()
+                ));
+            }
+            ui.add_space(5.0);
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .show(ui, |ui| {
+                    for (i, issue) in state.validation_issues.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if let Some(ref issue_fix) = issue.fix {
+                                if ui.small_button(issue_fix.label()).clicked() {
+                                    fix = Some(i);
+                                }
+                            }
+                            if ui.link(&issue.message).clicked() {
+                                go_to = Some(issue.location.clone());
+                            }
+                        });
+                    }
+                });
+            ui.add_space(10.0);
+            if ui.button("Check Again").clicked() {
+                state.validate_project();
+            }
+        });
+
+    if !open {
+        state.show_validation_dialog = false;
+        state.validation_issues.clear();
+    }
+    if let Some(location) = go_to {
+        state.go_to_issue(&location);
+    }
+    if let Some(index) = fix {
+        state.fix_issue(index);
+    }
+}
+
+/// Layers found by Find Usages; clicking one shows it on the canvas
+fn render_usages_dialog(ctx: &egui::Context, state: &mut AppState) {
+    let Some(ref query) = state.usage_query else {
+        return;
+    };
+    let mut go_to = None;
+    let mut refresh = false;
+    let mut open = true;
+    egui::Window::new(format!("Usages of {}", query.describe()))
+        .id(egui::Id::new("find_usages"))
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            if state.usages.is_empty() {
+                ui.label("No layer uses it.");
+            } else {
+                ui.label(format!(
+                    "{} layer(s) use it. Click one to show it.",
+                    state.usages.len()
+                ));
+            }
+            ui.add_space(5.0);
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .show(ui, |ui| {
+                    for usage in &state.usages {
+                        if ui.link(&usage.description).clicked() {
+                            go_to = Some(usage.location.clone());
+                        }
+                    }
+                });
+            ui.add_space(10.0);
+            if ui.button("Search Again").clicked() {
+                refresh = true;
+            }
+        });
+
+    if !open {
+        state.usage_query = None;
+        state.usages.clear();
+    }
+    if let Some(location) = go_to {
+        state.go_to_issue(&location);
+    }
+    if let Some(query) = state.usage_query.clone().filter(|_| refresh) {
+        state.find_usages(query);
+    }
+}
+
+/// Window for Edit > Find and Replace States: pick a part state (and optionally an angle),
+/// where to look and what to switch it to, preview the layers, then replace as one undo step
+fn render_replace_states_dialog(ctx: &egui::Context, state: &mut AppState) {
+    let Some(ref project) = state.project else {
+        state.show_replace_states_dialog = false;
+        return;
+    };
+    let before = state.replace_states.clone();
+
+    let characters: Vec<String> = project.characters.iter().map(|c| c.name.clone()).collect();
+    let character = before.character.as_ref().and_then(|name| project.get_character(name));
+    let parts: Vec<String> = character
+        .map(|c| project.character_parts(c).map(|p| p.name.clone()).collect())
+        .unwrap_or_default();
+    let part = character
+        .zip(before.part.as_ref())
+        .and_then(|(c, name)| project.resolve_part(c, name));
+    let states: Vec<String> = part
+        .map(|p| p.states.iter().map(|s| s.name.clone()).collect())
+        .unwrap_or_default();
+    let angles_of = |state_name: &Option<String>| -> Vec<Angle> {
+        part.zip(state_name.as_ref())
+            .and_then(|(p, name)| p.get_state(name))
+            .map(|s| s.angles())
+            .unwrap_or_default()
+    };
+    let find_angles = angles_of(&before.find_state);
+    let replace_angles = angles_of(&before.replace_state);
+
+    let query = match (&before.character, &before.part, &before.find_state) {
+        (Some(character), Some(part), Some(find_state)) => Some(match before.find_rotation {
+            Some(angle) => UsageQuery::rotation(character, part, find_state, angle),
+            None => UsageQuery::state(character, part, find_state),
+        }),
+        _ => None,
+    };
+    let matches = query
+        .as_ref()
+        .map(|q| project.find_usages_in(q, &before.scope))
+        .unwrap_or_default();
+    // Layers the new state can't show at their angle are left alone
+    let skipped = match (&query, &before.replace_state) {
+        (Some(q), Some(new_state)) => {
+            project.replace_state_skips(q, &before.scope, new_state, before.replace_rotation)
+        }
+        _ => 0,
+    };
+    // Animations with matches anywhere, with how many, to pick from
+    let mut animations: Vec<((String, usize), String, usize)> = Vec::new();
+    for usage in query.as_ref().map(|q| project.find_usages(q)).unwrap_or_default() {
+        let IssueLocation::Layer { character, animation, .. } = usage.location else {
+            continue;
+        };
+        let key = (character, animation);
+        match animations.iter_mut().find(|(k, _, _)| *k == key) {
+            Some((_, _, count)) => *count += 1,
+            None => {
+                let name = project
+                    .get_character(&key.0)
+                    .and_then(|c| c.animations.get(key.1))
+                    .map(|a| a.name.clone())
+                    .unwrap_or_default();
+                let label = format!("{} / {}", key.0, name);
+                animations.push((key, label, 1));
+            }
+        }
+    }
+
+    let angle_text = |angle: &Option<Angle>, none: &str| match angle {
+        Some(angle) => format!("{}°", angle),
+        None => none.to_string(),
+    };
+    let mut go_to = None;
+    let mut replace = false;
+    let mut open = true;
+    let settings = &mut state.replace_states;
+
+    egui::Window::new("Find and Replace States")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(460.0)
+        .show(ctx, |ui| {
+            egui::Grid::new("replace_states_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Character:");
+                egui::ComboBox::from_id_salt("replace_character")
+                    .selected_text(settings.character.as_deref().unwrap_or("(choose)"))
+                    .show_ui(ui, |ui| {
+                        for name in &characters {
+                            ui.selectable_value(&mut settings.character, Some(name.clone()), name);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Part:");
+                egui::ComboBox::from_id_salt("replace_part")
+                    .selected_text(settings.part.as_deref().unwrap_or("(choose)"))
+                    .show_ui(ui, |ui| {
+                        for name in &parts {
+                            ui.selectable_value(&mut settings.part, Some(name.clone()), name);
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Find state:");
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("replace_find_state")
+                        .selected_text(settings.find_state.as_deref().unwrap_or("(choose)"))
+                        .show_ui(ui, |ui| {
+                            for name in &states {
+                                let value = Some(name.clone());
+                                ui.selectable_value(&mut settings.find_state, value, name);
+                            }
+                        });
+                    egui::ComboBox::from_id_salt("replace_find_angle")
+                        .selected_text(angle_text(&settings.find_rotation, "any angle"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut settings.find_rotation, None, "any angle");
+                            for angle in &find_angles {
+                                let text = angle_text(&Some(*angle), "");
+                                let value = Some(*angle);
+                                ui.selectable_value(&mut settings.find_rotation, value, text);
+                            }
+                        });
+                });
+                ui.end_row();
+
+                ui.label("Replace with:");
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt("replace_with_state")
+                        .selected_text(settings.replace_state.as_deref().unwrap_or("(choose)"))
+                        .show_ui(ui, |ui| {
+                            for name in &states {
+                                let value = Some(name.clone());
+                                ui.selectable_value(&mut settings.replace_state, value, name);
+                            }
+                        });
+                    egui::ComboBox::from_id_salt("replace_with_angle")
+                        .selected_text(angle_text(&settings.replace_rotation, "same angle"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut settings.replace_rotation, None, "same angle");
+                            for angle in &replace_angles {
+                                let text = angle_text(&Some(*angle), "");
+                                let value = Some(*angle);
+                                ui.selectable_value(&mut settings.replace_rotation, value, text);
+                            }
+                        });
+                });
+                ui.end_row();
+            });
+
+            ui.add_space(4.0);
+            let mut selected_only = matches!(settings.scope, ReplaceScope::Animations(_));
+            ui.horizontal(|ui| {
+                ui.label("Search in:");
+                ui.radio_value(&mut selected_only, false, "Whole project");
+                ui.radio_value(&mut selected_only, true, "Selected animations");
+            });
+            match (&mut settings.scope, selected_only) {
+                (ReplaceScope::Animations(_), false) => settings.scope = ReplaceScope::Project,
+                (ReplaceScope::Project, true) => {
+                    settings.scope = ReplaceScope::Animations(Vec::new());
+                }
+                (ReplaceScope::Animations(selected), true) => {
+                    if animations.is_empty() {
+                        ui.label("No animation uses it.");
+                    }
+                    for (key, label, count) in &animations {
+                        let mut checked = selected.contains(key);
+                        if ui.checkbox(&mut checked, format!("{} ({})", label, count)).changed() {
+                            if checked {
+                                selected.push(key.clone());
+                            } else {
+                                selected.retain(|k| k != key);
+                            }
+                        }
+                    }
+                }
+                (ReplaceScope::Project, false) => {}
+            }
+
+            ui.separator();
+            if query.is_some() {
+                ui.label(format!("{} layer(s) match. Click one to show it.", matches.len()));
+                if skipped > 0 {
+                    let warning = egui::Color32::from_rgb(255, 200, 100);
+                    let text = format!(
+                        "{} of them will be skipped: the new state has no rotation at their angle",
+                        skipped
+                    );
+                    ui.colored_label(warning, text);
+                }
+            } else {
+                ui.label("Choose a character, part and state to find.");
+            }
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for usage in &matches {
+                        if ui.link(&usage.description).clicked() {
+                            go_to = Some(usage.location.clone());
+                        }
+                    }
+                });
+
+            ui.add_space(8.0);
+            let changes_something = settings.replace_state.is_some()
+                && (settings.replace_state != settings.find_state
+                    || settings.replace_rotation.is_some());
+            if ui
+                .add_enabled(
+                    matches.len() > skipped && changes_something,
+                    egui::Button::new(format!("Replace {} layer(s)", matches.len() - skipped)),
+                )
+                .clicked()
+            {
+                replace = true;
+            }
+        });
+
+    // Choices further down depend on the ones above them
+    let settings = &mut state.replace_states;
+    if settings.character != before.character {
+        settings.part = None;
+    }
+    if settings.part != before.part {
+        settings.find_state = None;
+        settings.replace_state = None;
+    }
+    if settings.find_state != before.find_state {
+        settings.find_rotation = None;
+    }
+    if settings.replace_state != before.replace_state {
+        settings.replace_rotation = None;
+    }
+
+    if let (true, Some(query), Some(new_state)) = (replace, query, before.replace_state.clone()) {
+        let (scope, rotation) = (before.scope.clone(), before.replace_rotation);
+        state.apply_undoable("Replace States", |project| {
+            let (count, skipped) = project.replace_state(&query, &scope, &new_state, rotation)?;
+            let mut message = format!(
+                "Replaced {} with {} in {} layer(s)",
+                query.describe(),
+                new_state,
+                count
+            );
+            if skipped > 0 {
+                message.push_str(&format!(
+                    ", skipped {} without a rotation at their angle",
+                    skipped
+                ));
+            }
+            Ok(message)
+        });
+    }
+    if let Some(location) = go_to {
+        state.go_to_issue(&location);
+    }
+    if !open {
+        state.show_replace_states_dialog = false;
+    }
+}
+
+/// Window for "Copy to Character...": pick a target, map parts, review what is missing
+fn render_retarget_dialog(ctx: &egui::Context, state: &mut AppState) {
+    let Some(ref project) = state.project else {
+        state.show_retarget_dialog = false;
+        return;
+    };
+    let settings = &state.retarget;
+    let source = settings.source_character.clone();
+    let animation = settings.animation_name.clone();
+
+    let targets: Vec<String> = project
+        .characters
+        .iter()
+        .filter(|c| c.name != source)
+        .map(|c| c.name.clone())
+        .collect();
+    let target_parts: Vec<String> = settings
+        .target_character
+        .as_ref()
+        .and_then(|name| project.get_character(name))
+        .map(|c| project.character_parts(c).map(|p| p.name.clone()).collect())
+        .unwrap_or_default();
+    let canvas_sizes = settings
+        .target_character
+        .as_ref()
+        .and_then(|name| project.get_character(name))
+        .zip(project.get_character(&source))
+        .map(|(target, source)| (source.canvas_size, target.canvas_size));
+    let issues = settings.target_character.as_ref().map(|target| {
+        project.retarget_issues(&source, &animation, target, &settings.part_map)
+    });
+
+    let mut picked_target: Option<String> = None;
+    let mut copy = false;
+    let mut close = false;
+
+    egui::Window::new("Copy Animation to Character")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(format!("Copying: {} / {}", source, animation));
+            ui.separator();
+
+            if targets.is_empty() {
+                ui.label("There is no other character to copy to.");
+            }
+            ui.horizontal(|ui| {
+                ui.label("To character:");
+                egui::ComboBox::from_id_salt("retarget_target")
+                    .selected_text(
+                        state
+                            .retarget
+                            .target_character
+                            .as_deref()
+                            .unwrap_or("(choose)"),
+                    )
+                    .show_ui(ui, |ui| {
+                        for name in &targets {
+                            let selected = state.retarget.target_character.as_ref() == Some(name);
+                            if ui.selectable_label(selected, name).clicked() {
+                                picked_target = Some(name.clone());
+                            }
+                        }
+                    });
+            });
+
+            if state.retarget.target_character.is_some() {
+                ui.add_space(4.0);
+                ui.label("Parts:");
+                egui::Grid::new("retarget_part_map")
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for (source_part, target_part) in state.retarget.part_map.iter_mut() {
+                            ui.label(source_part.as_str());
+                            ui.label("→");
+                            egui::ComboBox::from_id_salt(("retarget_part", source_part.as_str()))
+                                .selected_text(target_part.as_deref().unwrap_or("(leave out)"))
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(target_part, None, "(leave out)");
+                                    for name in &target_parts {
+                                        ui.selectable_value(
+                                            target_part,
+                                            Some(name.clone()),
+                                            name.as_str(),
+                                        );
+                                    }
+                                });
+                            ui.end_row();
+                        }
+                    });
+
+                if let Some((from, to)) = canvas_sizes {
+                    if from != to {
+                        ui.checkbox(
+                            &mut state.retarget.scale_positions,
+                            format!(
+                                "Scale positions ({}x{} → {}x{})",
+                                from.0, from.1, to.0, to.1
+                            ),
+                        );
+                    }
+                }
+
+                if let Some(ref issues) = issues {
+                    let warning = egui::Color32::from_rgb(255, 200, 100);
+                    for part in &issues.missing_parts {
+                        ui.colored_label(warning, format!("'{}' will be left out", part));
+                    }
+                    for (part, state_name) in &issues.missing_states {
+                        ui.colored_label(
+                            warning,
+                            format!("'{}' has no state '{}'", part, state_name),
+                        );
+                    }
+                }
+            }
+
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        state.retarget.target_character.is_some(),
+                        egui::Button::new("Copy"),
+                    )
+                    .clicked()
+                {
+                    copy = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    if let Some(target) = picked_target {
+        state.retarget.part_map = project.auto_part_map(&source, &animation, &target);
+        state.retarget.target_character = Some(target);
+    }
+
+    if copy {
+        state.mark_changed();
+        if let (Some(ref mut project), Some(target)) =
+            (&mut state.project, state.retarget.target_character.clone())
+        {
+            let result = project.retarget_animation(
+                &source,
+                &animation,
+                &target,
+                &state.retarget.part_map,
+                state.retarget.scale_positions,
+            );
+            match result {
+                Ok((name, issues)) if issues.is_empty() => {
+                    state.set_status(format!("Copied '{}' to {} as '{}'", animation, target, name));
+                    close = true;
+                }
+                Ok((name, issues)) => {
+                    state.set_status(format!(
+                        "Copied '{}' to {} as '{}' ({} missing part(s), {} missing state(s))",
+                        animation,
+                        target,
+                        name,
+                        issues.missing_parts.len(),
+                        issues.missing_states.len()
+                    ));
+                    close = true;
+                }
+                Err(e) => state.set_status(format!("Copy failed: {}", e)),
+            }
+        }
+    }
+    if close {
+        state.show_retarget_dialog = false;
+        state.retarget = Default::default();
+    }
+}
+
+/// Carry out an action that was held back by the unsaved-changes prompt
+pub fn perform_pending_action(state: &mut AppState, action: PendingAction, saved: bool) {
+    match action {
+        PendingAction::CloseProject => {
+            state.close_project();
+            state.set_status(if saved {
+                "Project saved and closed"
+            } else {
+                "Project closed without saving"
+            });
+        }
+        PendingAction::NewProject => {
+            state.new_project();
+            state.set_status("Created new project");
+        }
+        PendingAction::OpenProject => state.open_file_browser(BrowsePurpose::OpenProject),
+        PendingAction::Exit => {
+            state.clear_recovery_file();
+            std::process::exit(0);
+        }
+    }
+}
+
 pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
     // Rename dialog
     if state.show_rename_dialog {
         let title = match &state.context_menu_target {
             Some(ContextMenuTarget::Character { .. }) => "Rename Character",
             Some(ContextMenuTarget::Part { .. }) => "Rename Part",
+            Some(ContextMenuTarget::State { .. }) => "Rename State",
             Some(ContextMenuTarget::Animation { .. }) => "Rename Animation",
             Some(ContextMenuTarget::Frame { .. })
             | Some(ContextMenuTarget::Layer { .. })
@@ -35,6 +657,7 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                     if ui.button("Rename").clicked() && !state.rename_new_name.is_empty() {
                         let new_name = state.rename_new_name.clone();
                         if let Some(target) = state.context_menu_target.take() {
+                            state.mark_changed();
                             match target {
                                 ContextMenuTarget::Character { char_name } => {
                                     if let Some(ref mut project) = state.project {
@@ -70,18 +693,45 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                                     part_name,
                                 } => {
                                     if let Some(ref mut project) = state.project {
-                                        if let Some(character) = project.get_character_mut(&char_name)
-                                        {
-                                            if let Some(part) =
-                                                character.parts.iter_mut().find(|p| p.name == part_name)
-                                            {
-                                                part.name = new_name.clone();
+                                        // A shared part is renamed for every character using it;
+                                        // placements keep pointing at it by id
+                                        let is_shared = project
+                                            .get_character(&char_name)
+                                            .is_some_and(|c| c.uses_library_part(&part_name));
+                                        match project.rename_part(&char_name, &part_name, &new_name) {
+                                            Ok(()) => {
                                                 state.editor_selected_part = Some(new_name.clone());
+                                                state.frame_thumbnail_cache.clear();
                                                 state.set_status(format!(
-                                                    "Renamed part to '{}'",
+                                                    "Renamed {}part to '{}'",
+                                                    if is_shared { "shared " } else { "" },
                                                     new_name
                                                 ));
                                             }
+                                            Err(e) => state.set_status(e),
+                                        }
+                                    }
+                                }
+                                ContextMenuTarget::State {
+                                    char_name,
+                                    part_name,
+                                    state_name,
+                                } => {
+                                    if let Some(ref mut project) = state.project {
+                                        match project.rename_state(
+                                            &char_name,
+                                            &part_name,
+                                            &state_name,
+                                            &new_name,
+                                        ) {
+                                            Ok(()) => {
+                                                state.editor_selected_state = Some(new_name.clone());
+                                                state.set_status(format!(
+                                                    "Renamed state to '{}'",
+                                                    new_name
+                                                ));
+                                            }
+                                            Err(e) => state.set_status(e),
                                         }
                                     }
                                 }
@@ -132,9 +782,17 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
             Some(ContextMenuTarget::Part { part_name, .. }) => {
                 ("Delete Part?", "part", part_name.clone())
             }
+            Some(ContextMenuTarget::State { state_name, .. }) => {
+                ("Delete State?", "state", state_name.clone())
+            }
             Some(ContextMenuTarget::Animation { anim_name, .. }) => {
                 ("Delete Animation?", "animation", anim_name.clone())
             }
+            Some(ContextMenuTarget::Frame { frame_index, frame_count, .. }) if *frame_count > 1 => (
+                "Delete Frames?",
+                "frames",
+                format!("{}-{}", frame_index + 1, frame_index + frame_count),
+            ),
             Some(ContextMenuTarget::Frame { frame_index, .. }) => {
                 ("Delete Frame?", "frame", format!("{}", frame_index + 1))
             }
@@ -143,6 +801,36 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
             }
             None => ("Delete?", "item", String::new()),
         };
+        // Layers drawing a deleted part or state would break: count them and offer to
+        // move them to another state first
+        let usage_query = match &state.context_menu_target {
+            Some(ContextMenuTarget::Part { char_name, part_name }) => {
+                Some(UsageQuery::part(char_name, part_name))
+            }
+            Some(ContextMenuTarget::State { char_name, part_name, state_name }) => {
+                Some(UsageQuery::state(char_name, part_name, state_name))
+            }
+            _ => None,
+        };
+        let (usage_count, replacements) = usage_query
+            .as_ref()
+            .zip(state.project.as_ref())
+            .map(|(query, project)| {
+                let replacements: Vec<(String, String)> = project
+                    .get_character(&query.character)
+                    .into_iter()
+                    .flat_map(|c| project.character_parts(c))
+                    // A state is replaced by a sibling, a part by any other part's state
+                    .filter(|part| (part.name == query.part) == query.state.is_some())
+                    .flat_map(|part| {
+                        part.states.iter().map(|s| (part.name.clone(), s.name.clone()))
+                    })
+                    .filter(|(_, s)| query.state.as_ref() != Some(s))
+                    .collect();
+                (project.find_usages(query).len(), replacements)
+            })
+            .unwrap_or_default();
+        let mut show_usages = false;
         egui::Window::new(title)
             .collapsible(false)
             .resizable(false)
@@ -153,10 +841,51 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                     ui.label(egui::RichText::new(&item_name).strong());
                     ui.label("?");
                 });
+                if usage_count > 0 {
+                    ui.add_space(5.0);
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} layer(s) use it.", usage_count));
+                        if ui.link("Show usages").clicked() {
+                            show_usages = true;
+                        }
+                    });
+                    let describe = |r: &Option<(String, String)>| match r {
+                        Some((part, state)) => format!("{} / {}", part, state),
+                        None => "Nothing (leave them broken)".to_string(),
+                    };
+                    ui.horizontal(|ui| {
+                        ui.label("Replace with:");
+                        egui::ComboBox::from_id_salt("delete_replacement")
+                            .selected_text(describe(&state.delete_replacement))
+                            .show_ui(ui, |ui| {
+                                let choice = &mut state.delete_replacement;
+                                ui.selectable_value(choice, None, describe(&None));
+                                for replacement in replacements {
+                                    let replacement = Some(replacement);
+                                    let label = describe(&replacement);
+                                    ui.selectable_value(choice, replacement, label);
+                                }
+                            });
+                    });
+                }
                 ui.add_space(10.0);
                 ui.horizontal(|ui| {
                     if ui.button("Delete").clicked() {
                         if let Some(target) = state.context_menu_target.take() {
+                            state.mark_changed();
+                            // Move the layers over before the part or state goes away
+                            let replacement = state.delete_replacement.take();
+                            let moved = replacement.as_ref().zip(usage_query.as_ref()).map(
+                                |((part, new_state), query)| {
+                                    let project = state.project.as_mut().ok_or("No project")?;
+                                    project.replace_usages(query, part, new_state)
+                                },
+                            );
+                            if let Some(Err(e)) = moved {
+                                state.set_status(e);
+                                state.show_delete_confirm_dialog = false;
+                                return;
+                            }
                             match target {
                                 ContextMenuTarget::Character { char_name } => {
                                     if let Some(ref mut project) = state.project {
@@ -185,15 +914,50 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                                     part_name,
                                 } => {
                                     if let Some(ref mut project) = state.project {
-                                        if let Some(character) = project.get_character_mut(&char_name)
+                                        let is_shared = project
+                                            .get_character(&char_name)
+                                            .is_some_and(|c| c.uses_library_part(&part_name));
+                                        if is_shared {
+                                            // Shared parts are unlinked; other characters keep them
+                                            if project.unlink_library_part(&char_name, &part_name) {
+                                                state.set_status(format!(
+                                                    "Removed shared part '{}' (no longer used)",
+                                                    part_name
+                                                ));
+                                            } else {
+                                                state.set_status(format!(
+                                                    "Unlinked shared part '{}'",
+                                                    part_name
+                                                ));
+                                            }
+                                        } else if let Some(character) =
+                                            project.get_character_mut(&char_name)
                                         {
                                             character.parts.retain(|p| p.name != part_name);
-                                            if state.editor_selected_part.as_ref() == Some(&part_name)
-                                            {
-                                                state.editor_selected_part = None;
-                                            }
                                             state.set_status(format!("Deleted part '{}'", part_name));
                                         }
+                                        if state.editor_selected_part.as_ref() == Some(&part_name) {
+                                            state.editor_selected_part = None;
+                                        }
+                                    }
+                                }
+                                ContextMenuTarget::State {
+                                    char_name,
+                                    part_name,
+                                    state_name,
+                                } => {
+                                    if let Some(ref mut project) = state.project {
+                                        if let Some(part) = project.get_part_mut(&char_name, &part_name)
+                                        {
+                                            part.states.retain(|s| s.name != state_name);
+                                            state.set_status(format!(
+                                                "Deleted state '{}'",
+                                                state_name
+                                            ));
+                                        }
+                                        if state.editor_selected_state.as_ref() == Some(&state_name) {
+                                            state.editor_selected_state = None;
+                                        }
                                     }
                                 }
                                 ContextMenuTarget::Animation {
@@ -227,6 +991,7 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                                     char_name,
                                     anim_index,
                                     frame_index,
+                                    frame_count,
                                 } => {
                                     if let Some(ref mut project) = state.project {
                                         if let Some(character) = project.get_character_mut(&char_name)
@@ -237,16 +1002,21 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                                                 if frame_index < anim.frames.len()
                                                     && anim.frames.len() > 1
                                                 {
-                                                    anim.frames.remove(frame_index);
+                                                    let removed = anim.delete_frames(
+                                                        frame_index..frame_index + frame_count,
+                                                    );
                                                     // Adjust current frame index
                                                     if state.current_frame >= anim.frames.len() {
                                                         state.current_frame = anim.frames.len() - 1;
                                                     }
+                                                    state.frame_selection = None;
                                                     state.selected_part_id = None;
-                                                    state.set_status(format!(
-                                                        "Deleted frame {}",
-                                                        frame_index + 1
-                                                    ));
+                                                    state.frame_thumbnail_cache.clear();
+                                                    state.set_status(if removed == 1 {
+                                                        format!("Deleted frame {}", frame_index + 1)
+                                                    } else {
+                                                        format!("Deleted {} frames", removed)
+                                                    });
                                                 } else if anim.frames.len() == 1 {
                                                     state.set_status("Cannot delete the only frame");
                                                 }
@@ -288,15 +1058,26 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                                     }
                                 }
                             }
+                            if let (Some(Ok(count)), Some((part, new_state))) = (moved, replacement) {
+                                state.frame_thumbnail_cache.clear();
+                                state.set_status(format!(
+                                    "Deleted {} '{}'; {} layer(s) now use {} / {}",
+                                    item_type, item_name, count, part, new_state
+                                ));
+                            }
                         }
                         state.show_delete_confirm_dialog = false;
                     }
                     if ui.button("Cancel").clicked() {
                         state.show_delete_confirm_dialog = false;
                         state.context_menu_target = None;
+                        state.delete_replacement = None;
                     }
                 });
             });
+        if let Some(query) = usage_query.filter(|_| show_usages) {
+            state.find_usages(query);
+        }
     }
 
     // Unsaved changes confirmation dialog
@@ -343,93 +1124,26 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
 
                 ui.horizontal(|ui| {
                     if ui.button("Save").clicked() {
-                        // Try to save first
-                        let save_ok = if state.project_path.is_some() {
+                        if state.project_path.is_some() {
                             match state.save_project() {
                                 Ok(()) => {
                                     state.set_status("Project saved");
-                                    true
-                                }
-                                Err(e) => {
-                                    state.set_status(format!("Save failed: {}", e));
-                                    false
+                                    perform_pending_action(state, pending.clone(), true);
                                 }
+                                Err(e) => state.set_status(format!("Save failed: {}", e)),
                             }
                         } else {
-                            // Need to Save As first
-                            if let Some(path) = pick_save_file() {
-                                let path_str = path.to_string_lossy().to_string();
-                                match state.save_project_as(&path_str) {
-                                    Ok(()) => {
-                                        state.set_status("Project saved");
-                                        true
-                                    }
-                                    Err(e) => {
-                                        state.set_status(format!("Save failed: {}", e));
-                                        false
-                                    }
-                                }
-                            } else {
-                                false
-                            }
-                        };
-
-                        if save_ok {
-                            // Perform the pending action
-                            match pending {
-                                PendingAction::CloseProject => {
-                                    state.close_project();
-                                    state.set_status("Project saved and closed");
-                                }
-                                PendingAction::NewProject => {
-                                    state.new_project();
-                                    state.set_status("Created new project");
-                                }
-                                PendingAction::OpenProject => {
-                                    if let Some(path) = pick_file() {
-                                        let path_str = path.to_string_lossy().to_string();
-                                        match state.load_project(&path_str) {
-                                            Ok(()) => {
-                                                state.set_status(format!("Loaded {}", path_str))
-                                            }
-                                            Err(e) => {
-                                                state.set_status(format!("Load failed: {}", e))
-                                            }
-                                        }
-                                    }
-                                }
-                                PendingAction::Exit => {
-                                    std::process::exit(0);
-                                }
-                            }
-                            state.pending_action = None;
+                            // Need to Save As first; the action resumes once the browser saves
+                            state.open_file_browser(BrowsePurpose::SaveProjectAs {
+                                then: Some(pending.clone()),
+                            });
                         }
+                        state.pending_action = None;
                     }
 
                     if ui.button(continue_btn).clicked() {
                         // Perform action without saving
-                        match pending {
-                            PendingAction::CloseProject => {
-                                state.close_project();
-                                state.set_status("Project closed without saving");
-                            }
-                            PendingAction::NewProject => {
-                                state.new_project();
-                                state.set_status("Created new project");
-                            }
-                            PendingAction::OpenProject => {
-                                if let Some(path) = pick_file() {
-                                    let path_str = path.to_string_lossy().to_string();
-                                    match state.load_project(&path_str) {
-                                        Ok(()) => state.set_status(format!("Loaded {}", path_str)),
-                                        Err(e) => state.set_status(format!("Load failed: {}", e)),
-                                    }
-                                }
-                            }
-                            PendingAction::Exit => {
-                                std::process::exit(0);
-                            }
-                        }
+                        perform_pending_action(state, pending.clone(), false);
                         state.pending_action = None;
                     }
 
@@ -440,6 +1154,129 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
             });
     }
 
+    // Convert single-file project to bundle format (offered on save)
+    if state.show_bundle_migration_dialog {
+        let bundle_path = state
+            .project_path
+            .as_ref()
+            .map(|p| bundle_path_for(p).to_string_lossy().to_string())
+            .unwrap_or_default();
+        egui::Window::new("Convert to Bundle Format?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("This project uses the single-file .pss format, which embeds all art as text.");
+                ui.label("The bundle format stores project.json plus one PNG file per image,");
+                ui.label("so it loads faster and diffs cleanly in version control.");
+                ui.add_space(10.0);
+                ui.label("The project will be saved to:");
+                ui.label(egui::RichText::new(&bundle_path).strong());
+                ui.label("The original .pss file is left untouched.");
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Convert and Save").clicked() {
+                        match state.migrate_to_bundle() {
+                            Ok(path) => {
+                                state.set_status(format!("Saved as bundle to {}", path.display()))
+                            }
+                            Err(e) => state.set_status(format!("Conversion failed: {}", e)),
+                        }
+                        state.show_bundle_migration_dialog = false;
+                    }
+                    if ui.button("Keep Single File").clicked() {
+                        state.bundle_migration_declined = true;
+                        match state.save_project() {
+                            Ok(()) => state.set_status("Project saved"),
+                            Err(e) => state.set_status(format!("Save failed: {}", e)),
+                        }
+                        state.show_bundle_migration_dialog = false;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        state.show_bundle_migration_dialog = false;
+                    }
+                });
+            });
+    }
+
+    // Offer to restore unsaved work autosaved before a crash
+    let recovery_info = state.pending_recovery.as_ref().map(|recovery| {
+        let source = recovery
+            .project_path
+            .clone()
+            .unwrap_or_else(|| "an unsaved project".to_string());
+        let age = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|now| now.as_secs().saturating_sub(recovery.saved_at))
+            .unwrap_or(0);
+        (source, age)
+    });
+    if let Some((source, age)) = recovery_info {
+        egui::Window::new("Recover Unsaved Work?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("Pixel Sprite Studio did not close cleanly last time.");
+                ui.label(format!(
+                    "Unsaved changes to {} were autosaved {}.",
+                    source,
+                    format_relative_time(std::time::Duration::from_secs(age))
+                ));
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        match state.restore_recovery() {
+                            Ok(()) => state.set_status("Restored autosaved project"),
+                            Err(e) => {
+                                state.discard_recovery();
+                                state.set_status(format!("Restore failed: {}", e));
+                            }
+                        }
+                    }
+                    if ui.button("Discard").clicked() {
+                        state.discard_recovery();
+                        state.set_status("Discarded autosaved changes");
+                    }
+                });
+            });
+    }
+
+    // Report of changes made while upgrading an older project
+    if state.show_migration_report_dialog {
+        let report = state
+            .project
+            .as_ref()
+            .and_then(|p| p.migration_report.clone())
+            .unwrap_or_default();
+        egui::Window::new("Project Upgraded")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "This project was upgraded from schema version {} to {}:",
+                    report.from_version, report.to_version
+                ));
+                ui.add_space(5.0);
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for change in &report.changes {
+                            ui.label(format!("• {}", change));
+                        }
+                    });
+                ui.add_space(10.0);
+                ui.label("Save the project to keep the upgraded format.");
+                ui.add_space(10.0);
+                if ui.button("OK").clicked() {
+                    state.show_migration_report_dialog = false;
+                }
+            });
+    }
+
     // New Animation dialog
     if state.show_new_animation_dialog {
         egui::Window::new("New Animation")
@@ -457,6 +1294,7 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                 ui.horizontal(|ui| {
                     if ui.button("Create").clicked() && !state.new_animation_name.is_empty() {
                         let active_char = state.active_character.clone();
+                        state.mark_changed();
                         if let Some(ref mut project) = state.project {
                             if let Some(ref char_name) = active_char {
                                 if let Some(character) = project.get_character_mut(char_name) {
@@ -497,11 +1335,16 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                 ui.separator();
 
                 ui.label("Enter path to PNG image:");
-                let response = ui.text_edit_singleline(&mut state.import_image_path);
-                if state.dialog_needs_focus {
-                    response.request_focus();
-                    state.dialog_needs_focus = false;
-                }
+                ui.horizontal(|ui| {
+                    let response = ui.text_edit_singleline(&mut state.import_image_path);
+                    if state.dialog_needs_focus {
+                        response.request_focus();
+                        state.dialog_needs_focus = false;
+                    }
+                    if ui.button("Browse...").clicked() {
+                        state.open_file_browser(BrowsePurpose::ImportDialogImage);
+                    }
+                });
 
                 ui.horizontal(|ui| {
                     if ui.button("Import").clicked() && !state.import_image_path.is_empty() {
@@ -509,6 +1352,7 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                         let rotation_angle = state.selected_rotation_for_import;
                         match import_image_as_base64(&path) {
                             Ok(base64_data) => {
+                                state.mark_changed();
                                 if let (
                                     Some(ref char_name),
                                     Some(ref part_name),
@@ -519,10 +1363,8 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                                     &state.selected_state_for_import,
                                 ) {
                                     if let Some(ref mut project) = state.project {
-                                        if let Some(character) =
-                                            project.get_character_mut(char_name)
-                                        {
-                                            if let Some(part) = character.get_part_mut(part_name) {
+                                        if let Ok(hash) = project.images.insert_base64(&base64_data) {
+                                            if let Some(part) = project.get_part_mut(char_name, part_name) {
                                                 if let Some(state_obj) = part
                                                     .states
                                                     .iter_mut()
@@ -531,13 +1373,13 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                                                     if let Some(rotation) =
                                                         state_obj.rotations.get_mut(&rotation_angle)
                                                     {
-                                                        rotation.image_data = Some(base64_data);
+                                                        rotation.image = Some(hash);
+                                                        rotation.source_path = Some(path);
+                                                        rotation.generated = None;
                                                         state.set_status(format!(
                                                             "Image imported for {}° rotation",
                                                             rotation_angle
                                                         ));
-                                                        // Clear texture cache so it gets reloaded
-                                                        state.texture_cache.clear();
                                                     }
                                                 }
                                             }
@@ -582,6 +1424,7 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                 });
                 ui.horizontal(|ui| {
                     if ui.button("Create").clicked() && !state.new_character_name.is_empty() {
+                        state.mark_changed();
                         if let Some(ref mut project) = state.project {
                             let char_id = project.next_char_id();
                             let character = Character::new(char_id, &state.new_character_name);
@@ -605,6 +1448,342 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
             });
     }
 
+    // Import Folder dialog
+    if state.show_folder_import_dialog {
+        let target_character = match &state.active_tab {
+            ActiveTab::CharacterEditor(name) => Some(name.clone()),
+            ActiveTab::Canvas => state.active_character.clone(),
+        };
+        egui::Window::new("Import Folder")
+            .collapsible(false)
+            .resizable(false)
+            .min_width(420.0)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                let Some(char_name) = target_character else {
+                    ui.label("Select a character to import into first.");
+                    if ui.button("Close").clicked() {
+                        state.show_folder_import_dialog = false;
+                    }
+                    return;
+                };
+                ui.label(format!("Importing into: {}", char_name));
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Folder:");
+                    if ui
+                        .add(
+                            egui::TextEdit::singleline(&mut state.folder_import_path)
+                                .desired_width(260.0),
+                        )
+                        .changed()
+                    {
+                        state.folder_import_plan = None;
+                    }
+                    if ui.button("Browse...").clicked() {
+                        state.open_file_browser(BrowsePurpose::FolderImport);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Pattern:");
+                    if ui
+                        .add(
+                            egui::TextEdit::singleline(&mut state.config.import_pattern)
+                                .desired_width(260.0),
+                        )
+                        .changed()
+                    {
+                        state.folder_import_plan = None;
+                    }
+                });
+                ui.label(
+                    egui::RichText::new("Placeholders: {part}, {state} (optional), {angle}")
+                        .small()
+                        .weak(),
+                );
+                ui.add_space(5.0);
+
+                if ui.button("Preview").clicked() {
+                    let plan = NamingPattern::parse(&state.config.import_pattern).and_then(
+                        |pattern| {
+                            let project = state.project.as_ref().ok_or("No project loaded")?;
+                            plan_folder_import(
+                                std::path::Path::new(&state.folder_import_path),
+                                &pattern,
+                                project,
+                                &char_name,
+                            )
+                        },
+                    );
+                    match plan {
+                        Ok(plan) => {
+                            state.config.save();
+                            state.folder_import_plan = Some(plan);
+                        }
+                        Err(e) => state.set_status(format!("Preview failed: {}", e)),
+                    }
+                }
+
+                if let Some(ref plan) = state.folder_import_plan {
+                    ui.separator();
+                    ui.label(format!(
+                        "{} image(s): {} new part(s), {} new state(s), {} overwrite(s)",
+                        plan.entries.len(),
+                        plan.new_parts.len(),
+                        plan.new_states.len(),
+                        plan.overwrite_count()
+                    ));
+                    egui::ScrollArea::vertical()
+                        .max_height(250.0)
+                        .show(ui, |ui| {
+                            for ((part_name, state_name), mode) in &plan.states {
+                                let angles: Vec<String> = plan
+                                    .entries
+                                    .iter()
+                                    .filter(|e| e.part == *part_name && e.state == *state_name)
+                                    .map(|e| {
+                                        if e.overwrites {
+                                            format!("{}° (overwrite)", e.angle)
+                                        } else {
+                                            format!("{}°", e.angle)
+                                        }
+                                    })
+                                    .collect();
+                                let is_new = plan
+                                    .new_states
+                                    .iter()
+                                    .any(|(p, s)| p == part_name && s == state_name);
+                                let mode_label = mode.label();
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{} / {}{} ({})",
+                                        part_name,
+                                        state_name,
+                                        if is_new { " [new]" } else { "" },
+                                        mode_label
+                                    ))
+                                    .strong(),
+                                );
+                                ui.label(format!("    {}", angles.join(", ")));
+                            }
+                            for skipped in &plan.skipped {
+                                ui.label(
+                                    egui::RichText::new(format!("Skipped: {}", skipped)).weak(),
+                                );
+                            }
+                        });
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let can_import = state
+                        .folder_import_plan
+                        .as_ref()
+                        .is_some_and(|p| !p.entries.is_empty());
+                    if ui
+                        .add_enabled(can_import, egui::Button::new("Import"))
+                        .clicked()
+                    {
+                        state.mark_changed();
+                        let result = match (&state.folder_import_plan, &mut state.project) {
+                            (Some(plan), Some(project)) => {
+                                Some(apply_folder_import(project, &char_name, plan))
+                            }
+                            _ => None,
+                        };
+                        match result {
+                            Some(Ok(count)) => {
+                                state.set_status(format!("Imported {} rotation(s)", count));
+                                state.frame_thumbnail_cache.clear();
+                                state.show_folder_import_dialog = false;
+                                state.folder_import_plan = None;
+                            }
+                            Some(Err(e)) => state.set_status(format!("Import failed: {}", e)),
+                            None => {}
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        state.show_folder_import_dialog = false;
+                        state.folder_import_plan = None;
+                    }
+                });
+            });
+    }
+
+    // Slice a spritesheet into the rotations of the selected part state
+    if state.show_sheet_import_dialog {
+        let target = match (&state.active_tab, &state.editor_selected_part) {
+            (ActiveTab::CharacterEditor(char_name), Some(part_name)) => {
+                let project = state.project.as_ref();
+                let part = project.and_then(|p| p.get_part(char_name, part_name));
+                let state_obj = part.and_then(|p| match &state.editor_selected_state {
+                    Some(name) => p.states.iter().find(|s| &s.name == name),
+                    None => p.states.first(),
+                });
+                state_obj.map(|s| {
+                    (char_name.clone(), part_name.clone(), s.name.clone(), s.rotation_mode.clone())
+                })
+            }
+            _ => None,
+        };
+
+        egui::Window::new("Import Spritesheet")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                let Some((char_name, part_name, state_name, mode)) = target else {
+                    ui.label("Select a part state in the character editor first.");
+                    if ui.button("Close").clicked() {
+                        state.show_sheet_import_dialog = false;
+                    }
+                    return;
+                };
+                ui.label(format!("Importing to: {} / {} / {}", char_name, part_name, state_name));
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Sheet:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut state.sheet_import.path)
+                            .desired_width(260.0),
+                    );
+                    if ui.button("Browse...").clicked() {
+                        state.open_file_browser(BrowsePurpose::Spritesheet);
+                    }
+                });
+
+                let settings = &mut state.sheet_import;
+                egui::Grid::new("sheet_grid_settings").show(ui, |ui| {
+                    ui.label("Cell size:");
+                    ui.add(egui::DragValue::new(&mut settings.grid.cell_size.0).range(1..=2048));
+                    ui.add(egui::DragValue::new(&mut settings.grid.cell_size.1).range(1..=2048));
+                    ui.end_row();
+                    ui.label("Offset:");
+                    ui.add(egui::DragValue::new(&mut settings.grid.offset.0).range(0..=4096));
+                    ui.add(egui::DragValue::new(&mut settings.grid.offset.1).range(0..=4096));
+                    ui.end_row();
+                    ui.label("Spacing:");
+                    ui.add(egui::DragValue::new(&mut settings.grid.spacing.0).range(0..=512));
+                    ui.add(egui::DragValue::new(&mut settings.grid.spacing.1).range(0..=512));
+                    ui.end_row();
+                    ui.label("Columns:");
+                    ui.add(egui::DragValue::new(&mut settings.grid.columns).range(0..=64))
+                        .on_hover_text("0 = as many as fit");
+                    ui.end_row();
+                });
+
+                let angles = mode.angles();
+                if !angles.contains(&settings.start_angle) {
+                    settings.start_angle = angles[0];
+                }
+                ui.horizontal(|ui| {
+                    ui.label("First cell:");
+                    egui::ComboBox::from_id_salt("sheet_start_angle")
+                        .selected_text(format!("{}°", settings.start_angle))
+                        .show_ui(ui, |ui| {
+                            for angle in &angles {
+                                ui.selectable_value(
+                                    &mut settings.start_angle,
+                                    *angle,
+                                    format!("{}°", angle),
+                                );
+                            }
+                        });
+                    ui.checkbox(&mut settings.clockwise, "Clockwise");
+                    ui.checkbox(&mut settings.auto_trim, "Auto-trim cells");
+                });
+
+                // Preview the sheet with the cell grid and the angle each cell maps to
+                let sheet_path = state.sheet_import.path.clone();
+                let texture_key = format!("sheet/{}", sheet_path);
+                if !sheet_path.is_empty() && !state.texture_cache.contains_key(&texture_key) {
+                    if let Ok(texture) = load_image_file_texture(ui.ctx(), &sheet_path) {
+                        state.texture_cache.insert(texture_key.clone(), texture);
+                    }
+                }
+                if let Some(texture) = state.texture_cache.get(&texture_key) {
+                    let sheet_size = texture.size_vec2();
+                    let scale = (400.0 / sheet_size.x).min(200.0 / sheet_size.y).min(8.0);
+                    let (rect, _) =
+                        ui.allocate_exact_size(sheet_size * scale, egui::Sense::hover());
+                    let painter = ui.painter_at(rect);
+                    painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(40, 40, 40));
+                    painter.image(
+                        texture.id(),
+                        rect,
+                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                        egui::Color32::WHITE,
+                    );
+
+                    let settings = &state.sheet_import;
+                    let sheet_px = (sheet_size.x as u32, sheet_size.y as u32);
+                    let ordered = mode.angles_from(settings.start_angle, settings.clockwise);
+                    for (index, angle) in ordered.iter().enumerate() {
+                        let Some((x, y)) = settings.grid.cell_origin(index, sheet_px) else {
+                            break;
+                        };
+                        let cell_rect = egui::Rect::from_min_size(
+                            rect.min + egui::vec2(x as f32, y as f32) * scale,
+                            egui::vec2(
+                                settings.grid.cell_size.0 as f32,
+                                settings.grid.cell_size.1 as f32,
+                            ) * scale,
+                        );
+                        painter.rect_stroke(
+                            cell_rect,
+                            0.0,
+                            egui::Stroke::new(1.0, egui::Color32::YELLOW),
+                        );
+                        painter.text(
+                            cell_rect.left_top() + egui::vec2(2.0, 1.0),
+                            egui::Align2::LEFT_TOP,
+                            format!("{}°", angle),
+                            egui::FontId::proportional(10.0),
+                            egui::Color32::YELLOW,
+                        );
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!sheet_path.is_empty(), egui::Button::new("Import"))
+                        .clicked()
+                    {
+                        match state.import_spritesheet(&char_name, &part_name, &state_name) {
+                            Ok(count) => {
+                                state.set_status(format!("Imported {} rotation(s) from sheet", count));
+                                state.texture_cache.remove(&texture_key);
+                                state.show_sheet_import_dialog = false;
+                            }
+                            Err(e) => state.set_status(format!("Import failed: {}", e)),
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        state.texture_cache.remove(&texture_key);
+                        state.show_sheet_import_dialog = false;
+                    }
+                });
+            });
+    }
+
+    // Copy an animation to another character, remapping its parts
+    if state.show_retarget_dialog {
+        render_retarget_dialog(ctx, state);
+    }
+
+    render_export_progress(ctx, state);
+
+    if state.show_validation_dialog {
+        render_validation_dialog(ctx, state);
+    }
+    render_usages_dialog(ctx, state);
+    if state.show_replace_states_dialog {
+        render_replace_states_dialog(ctx, state);
+    }
+
     // Clone Character dialog
     if state.show_clone_character_dialog {
         egui::Window::new("Clone Character")
@@ -654,6 +1833,7 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                             .add_enabled(name_valid, egui::Button::new("Clone"))
                             .clicked()
                         {
+                            state.mark_changed();
                             if let Some(ref mut project) = state.project {
                                 if let Some(original) = project.get_character(&source_name) {
                                     let mut cloned = original.clone();
@@ -714,9 +1894,11 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                 ui.horizontal(|ui| {
                     if ui.button("Create").clicked() && !state.new_part_name.is_empty() {
                         if let ActiveTab::CharacterEditor(ref char_name) = state.active_tab.clone() {
+                            state.mark_changed();
                             if let Some(ref mut project) = state.project {
+                                let part_id = project.new_part_id();
                                 if let Some(character) = project.get_character_mut(&char_name) {
-                                    let part = Part::new(&state.new_part_name);
+                                    let part = Part::new(part_id, &state.new_part_name);
                                     character.add_part(part);
                                     state.editor_selected_part = Some(state.new_part_name.clone());
                                     state.editor_selected_state = None;
@@ -757,19 +1939,18 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                     if ui.button("Create").clicked() && !state.new_state_name.is_empty() {
                         if let ActiveTab::CharacterEditor(ref char_name) = state.active_tab.clone() {
                             if let Some(ref part_name) = state.editor_selected_part.clone() {
+                                state.mark_changed();
                                 if let Some(ref mut project) = state.project {
-                                    if let Some(character) = project.get_character_mut(&char_name) {
-                                        if let Some(part) = character.get_part_mut(&part_name) {
-                                            let new_state =
-                                                State::new(&state.new_state_name, RotationMode::Deg45);
-                                            part.add_state(new_state);
-                                            state.editor_selected_state =
-                                                Some(state.new_state_name.clone());
-                                            state.set_status(format!(
-                                                "Created state: {}",
-                                                state.new_state_name
-                                            ));
-                                        }
+                                    if let Some(part) = project.get_part_mut(&char_name, &part_name) {
+                                        let new_state =
+                                            State::new(&state.new_state_name, RotationMode::Deg45);
+                                        part.add_state(new_state);
+                                        state.editor_selected_state =
+                                            Some(state.new_state_name.clone());
+                                        state.set_status(format!(
+                                            "Created state: {}",
+                                            state.new_state_name
+                                        ));
                                     }
                                 }
                             }
@@ -782,48 +1963,4 @@ pub fn render_dialogs(ctx: &egui::Context, state: &mut AppState) {
                 });
             });
     }
-
-    // Handle pending rotation import with file picker
-    if let Some(angle) = state.pending_rotation_import {
-        if let Some(path) = pick_image_file() {
-            if let ActiveTab::CharacterEditor(ref char_name) = state.active_tab.clone() {
-                let part_name = state.editor_selected_part.clone();
-                let state_name = state
-                    .editor_selected_state
-                    .clone()
-                    .or_else(|| Some("default".to_string()));
-
-                match import_image_as_base64(path.to_str().unwrap_or("")) {
-                    Ok(base64_data) => {
-                        if let (Some(ref pn), Some(ref sn)) = (part_name, state_name) {
-                            if let Some(ref mut project) = state.project {
-                                if let Some(character) = project.get_character_mut(&char_name) {
-                                    if let Some(part) = character.get_part_mut(pn) {
-                                        if let Some(state_obj) =
-                                            part.states.iter_mut().find(|s| &s.name == sn)
-                                        {
-                                            if let Some(rotation) =
-                                                state_obj.rotations.get_mut(&angle)
-                                            {
-                                                rotation.image_data = Some(base64_data);
-                                                state.set_status(format!(
-                                                    "Imported image for {}°",
-                                                    angle
-                                                ));
-                                                state.texture_cache.clear();
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        state.set_status(format!("Import failed: {}", e));
-                    }
-                }
-            }
-        }
-        state.pending_rotation_import = None;
-    }
 }
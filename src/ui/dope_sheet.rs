@@ -0,0 +1,299 @@
+use bevy_egui::egui;
+use std::ops::Range;
+
+use crate::model::{Angle, PlacedPart, Track};
+use crate::state::AppState;
+use crate::ui::widgets::{scaled_font, scaled_margin};
+
+/// A change made to one track of the dope sheet, applied after drawing
+enum TrackEdit {
+    State(u64),
+    Rotation(Angle),
+    Visible(bool),
+}
+
+/// A stable, readable color for a state name
+fn state_color(name: &str) -> egui::Color32 {
+    let hash = name
+        .bytes()
+        .fold(7u32, |h, b| h.wrapping_mul(31).wrapping_add(b as u32));
+    let hue = (hash % 360) as f32 / 360.0;
+    egui::ecolor::Hsva::new(hue, 0.45, 0.5, 1.0).into()
+}
+
+/// Id and angles of the state a track shows at a frame
+fn state_at(track: &Track, frame: usize) -> Option<(u64, &[Angle])> {
+    let cell = track.cells.get(frame)?.as_ref()?;
+    let (id, _, angles) = track
+        .states
+        .iter()
+        .find(|(_, name, _)| *name == cell.state)?;
+    Some((*id, angles.as_slice()))
+}
+
+/// Which frame column a screen x position falls in
+fn column_at(x: f32, first_column: f32, cell_width: f32, frame_count: usize) -> usize {
+    let column = ((x - first_column) / cell_width).floor().max(0.0) as usize;
+    column.min(frame_count.saturating_sub(1))
+}
+
+/// The timeline as one row per layer and one column per frame. Marked cells are where a
+/// layer appears or changes state, angle or visibility. Right-click a cell to change it
+/// (or the selected frames); drag along a row to carry a cell's state across frames.
+pub fn render_dope_sheet(ui: &mut egui::Ui, state: &mut AppState) {
+    let tracks = match (&state.project, state.current_animation()) {
+        (Some(project), Some(animation)) => project.dope_sheet(animation),
+        _ => return,
+    };
+    let frame_count = state.total_frames();
+    if tracks.is_empty() {
+        ui.label("No layers in this animation yet");
+        return;
+    }
+
+    let scale = state.config.ui_scale;
+    let name_width = scaled_margin(110.0, scale);
+    let cell_width = scaled_margin(56.0, scale);
+    let row_height = scaled_margin(20.0, scale);
+    let font = egui::FontId::proportional(scaled_font(10.0, scale));
+    let selection = state.selected_frames();
+    let has_selection = state.frame_selection.is_some();
+    let mut edit: Option<(usize, Range<usize>, TrackEdit)> = None;
+
+    egui::ScrollArea::both().show(ui, |ui| {
+        let size = egui::vec2(
+            name_width + cell_width * frame_count as f32,
+            row_height * (tracks.len() + 1) as f32,
+        );
+        let (grid, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let painter = ui.painter_at(grid);
+        let first_column = grid.left() + name_width;
+        let cell_rect = |row: usize, frame: usize| {
+            egui::Rect::from_min_size(
+                egui::pos2(
+                    first_column + cell_width * frame as f32,
+                    grid.top() + row_height * row as f32,
+                ),
+                egui::vec2(cell_width, row_height),
+            )
+        };
+
+        // Header: frame numbers, click to go to a frame
+        for frame in 0..frame_count {
+            let rect = cell_rect(0, frame);
+            let id = ui.id().with(("dope_frame", frame));
+            let response = ui.interact(rect, id, egui::Sense::click());
+            let bg_color = if frame == state.current_frame {
+                egui::Color32::from_rgb(80, 120, 180)
+            } else if has_selection && selection.contains(&frame) {
+                egui::Color32::from_rgb(60, 85, 120)
+            } else {
+                egui::Color32::from_rgb(40, 40, 50)
+            };
+            painter.rect_filled(rect.shrink(1.0), 2.0, bg_color);
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                (frame + 1).to_string(),
+                font.clone(),
+                egui::Color32::WHITE,
+            );
+            if response.clicked() {
+                state.current_frame = frame;
+                state.frame_selection = None;
+                state.playback_time = 0.0;
+            }
+        }
+
+        let drag = state.dope_sheet_drag.clone();
+        let pointer_column = ui
+            .input(|i| i.pointer.interact_pos())
+            .map(|pos| column_at(pos.x, first_column, cell_width, frame_count));
+
+        for (index, track) in tracks.iter().enumerate() {
+            let row = index + 1;
+            let name_rect = egui::Rect::from_min_size(
+                egui::pos2(grid.left(), grid.top() + row_height * row as f32),
+                egui::vec2(name_width, row_height),
+            );
+            painter.with_clip_rect(name_rect).text(
+                name_rect.left_center() + egui::vec2(4.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                &track.layer_name,
+                font.clone(),
+                ui.visuals().text_color(),
+            );
+
+            // Frames a drag along this row covers
+            let dragged = drag
+                .as_ref()
+                .filter(|(layer, _)| *layer == track.layer_name)
+                .and_then(|(_, from)| {
+                    let to = pointer_column?;
+                    Some((*from).min(to)..(*from).max(to) + 1)
+                });
+
+            for frame in 0..frame_count {
+                let rect = cell_rect(row, frame);
+                let id = ui.id().with(("dope_cell", index, frame));
+                let response = ui.interact(rect, id, egui::Sense::click_and_drag());
+                let Some(cell) = track.cells[frame].as_ref() else {
+                    let empty = egui::Color32::from_rgb(30, 30, 36);
+                    painter.rect_filled(rect.shrink(1.0), 0.0, empty);
+                    continue;
+                };
+
+                let mut color = state_color(&cell.state);
+                if !cell.visible {
+                    color = color.gamma_multiply(0.35);
+                }
+                painter.rect_filled(rect.shrink2(egui::vec2(0.0, 1.0)), 0.0, color);
+                if track.changes_at(frame) {
+                    let key = egui::Stroke::new(2.0, egui::Color32::WHITE);
+                    painter.vline(rect.left() + 1.0, rect.y_range(), key);
+                    let label = if cell.visible {
+                        format!("{} {}°", cell.state, cell.rotation)
+                    } else {
+                        format!("{} (hidden)", cell.state)
+                    };
+                    painter.with_clip_rect(rect.shrink(1.0)).text(
+                        rect.left_center() + egui::vec2(4.0, 0.0),
+                        egui::Align2::LEFT_CENTER,
+                        label,
+                        font.clone(),
+                        egui::Color32::WHITE,
+                    );
+                }
+                if frame == state.current_frame {
+                    let current = egui::Stroke::new(1.0, egui::Color32::from_rgb(100, 160, 255));
+                    painter.rect_stroke(rect, 0.0, current);
+                }
+                if dragged.as_ref().is_some_and(|range| range.contains(&frame)) {
+                    let target = egui::Stroke::new(2.0, egui::Color32::YELLOW);
+                    painter.rect_stroke(rect.shrink(1.0), 0.0, target);
+                }
+
+                let hidden = if cell.visible { "" } else { ", hidden" };
+                let response = response.on_hover_text(format!(
+                    "{} / frame {}: {}, {}°{}",
+                    track.layer_name,
+                    frame + 1,
+                    cell.state,
+                    cell.rotation,
+                    hidden
+                ));
+                if response.clicked() {
+                    state.current_frame = frame;
+                    state.frame_selection = None;
+                    state.playback_time = 0.0;
+                    state.selected_part_id = Some(cell.layer_id);
+                }
+                if response.drag_started() && track.part_id.is_some() {
+                    state.dope_sheet_drag = Some((track.layer_name.clone(), frame));
+                }
+
+                // Right-click edits the selected frames if the cell is among them
+                let range = if has_selection && selection.contains(&frame) {
+                    selection.clone()
+                } else {
+                    frame..frame + 1
+                };
+                response.context_menu(|ui| {
+                    if range.len() > 1 {
+                        ui.label(format!("Frames {}-{}", range.start + 1, range.end));
+                    }
+                    if track.part_id.is_some() {
+                        ui.menu_button("State", |ui| {
+                            for (state_id, name, _) in &track.states {
+                                if ui.selectable_label(*name == cell.state, name).clicked() {
+                                    let state_edit = TrackEdit::State(*state_id);
+                                    edit = Some((index, range.clone(), state_edit));
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    }
+                    let angles = state_at(track, frame).map(|(_, a)| a).unwrap_or_default();
+                    ui.add_enabled_ui(!angles.is_empty(), |ui| {
+                        ui.menu_button("Rotation", |ui| {
+                            for &angle in angles {
+                                let label = format!("{}°", angle);
+                                if ui.selectable_label(angle == cell.rotation, label).clicked() {
+                                    edit = Some((index, range.clone(), TrackEdit::Rotation(angle)));
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                    let toggle = if cell.visible { "Hide" } else { "Show" };
+                    if ui.button(toggle).clicked() {
+                        edit = Some((index, range.clone(), TrackEdit::Visible(!cell.visible)));
+                        ui.close_menu();
+                    }
+                });
+            }
+
+            // Dropping a drag copies the state it started on across the covered frames
+            if let Some(range) = dragged {
+                if ui.input(|i| i.pointer.any_released()) {
+                    let from = drag.as_ref().map(|(_, from)| *from).unwrap_or(range.start);
+                    if let Some((state_id, _)) = state_at(track, from).filter(|_| range.len() > 1) {
+                        edit = Some((index, range, TrackEdit::State(state_id)));
+                    }
+                }
+            }
+        }
+
+        if ui.input(|i| i.pointer.any_released()) {
+            state.dope_sheet_drag = None;
+        }
+    });
+
+    let Some((index, range, track_edit)) = edit else {
+        return;
+    };
+    let track = &tracks[index];
+    // A layer that switched parts partway keeps its own states
+    let on_track_part =
+        |placed: &PlacedPart| placed.nested.is_none() && Some(placed.part_id) == track.part_id;
+    let edited = state
+        .edit_current_animation(|anim, _| {
+            anim.edit_track(
+                &track.layer_name,
+                range.clone(),
+                |placed| match track_edit {
+                    TrackEdit::State(state_id) => {
+                        let changes = on_track_part(placed) && placed.state_id != state_id;
+                        if changes {
+                            placed.state_id = state_id;
+                        }
+                        changes
+                    }
+                    TrackEdit::Rotation(angle) => {
+                        // Frames whose state has no such angle are left alone
+                        let has_angle = on_track_part(placed)
+                            && track.states.iter().any(|(id, _, angles)| {
+                                *id == placed.state_id && angles.contains(&angle)
+                            });
+                        let changes = has_angle && placed.rotation != angle;
+                        if changes {
+                            placed.rotation = angle;
+                        }
+                        changes
+                    }
+                    TrackEdit::Visible(visible) => {
+                        let changes = placed.visible != visible;
+                        placed.visible = visible;
+                        changes
+                    }
+                },
+            )
+        })
+        .unwrap_or(0);
+    state.set_status(format!(
+        "Changed '{}' in {} frame{}",
+        track.layer_name,
+        edited,
+        if edited == 1 { "" } else { "s" }
+    ));
+}
@@ -0,0 +1,306 @@
+use bevy_egui::egui;
+use std::path::{Path, PathBuf};
+
+use crate::export::{export_all_animations, export_current_animation};
+use crate::file::{home_dir, BrowseMode, FileBrowser};
+use crate::imaging::load_image_file_texture;
+use crate::state::{AppState, BrowsePurpose};
+use crate::ui::dialogs::perform_pending_action;
+
+const THUMBNAIL_SIZE: f32 = 32.0;
+/// Images decoded per frame, so opening a large folder doesn't stall the UI
+const THUMBNAILS_PER_FRAME: usize = 2;
+
+fn thumbnail_key(path: &Path) -> String {
+    format!("browse/{}", path.display())
+}
+
+/// Renders the built-in file browser used by every open, save, import and export action
+pub fn render_file_browser(ctx: &egui::Context, state: &mut AppState) {
+    let Some(mut browser) = state.file_browser.take() else {
+        return;
+    };
+    let mode = browser.mode();
+    let show_thumbnails = browser.purpose.shows_thumbnails();
+    let mut open = true;
+    let mut cancelled = false;
+    let mut picked: Option<PathBuf> = None;
+    let mut thumbnails_loaded = 0;
+
+    egui::Window::new(browser.purpose.title())
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_size([560.0, 420.0])
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            // Toolbar: navigation and editable path
+            ui.horizontal(|ui| {
+                if ui.button("Up").clicked() {
+                    browser.go_up();
+                }
+                if ui.button("Home").clicked() {
+                    browser.navigate(home_dir());
+                }
+                if ui.button("Refresh").clicked() {
+                    browser.refresh();
+                }
+                ui.add_enabled_ui(!state.config.recent_folders.is_empty(), |ui| {
+                    egui::ComboBox::from_id_salt("file_browser_recent")
+                        .selected_text("Recent")
+                        .show_ui(ui, |ui| {
+                            for folder in &state.config.recent_folders {
+                                if ui.selectable_label(false, folder).clicked() {
+                                    browser.navigate(PathBuf::from(folder));
+                                }
+                            }
+                        });
+                });
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut browser.dir_input).desired_width(f32::INFINITY),
+                );
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    browser.navigate(PathBuf::from(browser.dir_input.trim()));
+                }
+            });
+
+            if let Some(ref error) = browser.error {
+                ui.colored_label(egui::Color32::from_rgb(255, 100, 100), error);
+            }
+            ui.separator();
+
+            // Directory listing
+            let entries: Vec<_> = browser.visible_entries().cloned().collect();
+            let mut enter_dir: Option<PathBuf> = None;
+            egui::ScrollArea::vertical()
+                .auto_shrink([false, false])
+                .max_height((ui.available_height() - 70.0).max(100.0))
+                .show(ui, |ui| {
+                    if entries.is_empty() {
+                        ui.label(egui::RichText::new("(empty)").color(egui::Color32::GRAY));
+                    }
+                    for entry in &entries {
+                        let is_folder = entry.is_dir && !entry.is_bundle();
+                        let label = if is_folder {
+                            format!("📁 {}", entry.name)
+                        } else {
+                            entry.name.clone()
+                        };
+                        let selected = browser.file_name == entry.name;
+
+                        let response = ui
+                            .horizontal(|ui| {
+                                if show_thumbnails && !is_folder {
+                                    render_thumbnail(
+                                        ui,
+                                        state,
+                                        &mut browser,
+                                        &entry.path,
+                                        &mut thumbnails_loaded,
+                                    );
+                                }
+                                ui.selectable_label(selected, label)
+                            })
+                            .inner;
+
+                        if response.double_clicked() {
+                            if is_folder {
+                                enter_dir = Some(entry.path.clone());
+                            } else {
+                                browser.file_name = entry.name.clone();
+                                picked = browser.confirm();
+                            }
+                        } else if response.clicked() {
+                            if is_folder && mode != BrowseMode::Folder {
+                                enter_dir = Some(entry.path.clone());
+                            } else {
+                                browser.file_name = entry.name.clone();
+                                browser.confirm_overwrite = None;
+                            }
+                        }
+                    }
+                });
+            if let Some(dir) = enter_dir {
+                if mode == BrowseMode::Folder {
+                    browser.file_name.clear();
+                }
+                browser.navigate(dir);
+            }
+
+            ui.separator();
+
+            // File name and filter
+            ui.horizontal(|ui| {
+                ui.label(if mode == BrowseMode::Folder {
+                    "Folder:"
+                } else {
+                    "File name:"
+                });
+                let response =
+                    ui.add(egui::TextEdit::singleline(&mut browser.file_name).desired_width(260.0));
+                if response.changed() {
+                    browser.confirm_overwrite = None;
+                }
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    picked = browser.confirm();
+                }
+
+                let filters = browser.purpose.filters();
+                if filters.len() > 1 {
+                    egui::ComboBox::from_id_salt("file_browser_filter")
+                        .selected_text(filters[browser.filter_index].name)
+                        .show_ui(ui, |ui| {
+                            for (i, filter) in filters.iter().enumerate() {
+                                ui.selectable_value(&mut browser.filter_index, i, filter.name);
+                            }
+                        });
+                } else if let Some(filter) = filters.first() {
+                    ui.label(filter.name);
+                }
+            });
+
+            if let Some(ref path) = browser.confirm_overwrite {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!("{} already exists. Replace it?", name),
+                );
+            }
+
+            ui.horizontal(|ui| {
+                let confirm_text = match (mode, browser.confirm_overwrite.is_some()) {
+                    (_, true) => "Replace",
+                    (BrowseMode::Open, _) => "Open",
+                    (BrowseMode::Save, _) => "Save",
+                    (BrowseMode::Folder, _) => "Select Folder",
+                };
+                if ui
+                    .add_enabled(
+                        browser.target_path().is_some(),
+                        egui::Button::new(confirm_text),
+                    )
+                    .clicked()
+                {
+                    picked = browser.confirm();
+                }
+                if ui.button("Cancel").clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+
+    if picked.is_none() && open && !cancelled {
+        state.file_browser = Some(browser);
+        return;
+    }
+
+    // Closing: drop the thumbnails, remember the folder and hand over the result
+    state
+        .texture_cache
+        .retain(|key, _| !key.starts_with("browse/"));
+    if let Some(path) = picked {
+        let folder = if mode == BrowseMode::Folder {
+            path.clone()
+        } else {
+            browser.dir.clone()
+        };
+        state.config.add_recent_folder(&folder.to_string_lossy());
+        complete_browse(state, browser.purpose, path);
+    }
+}
+
+/// Draw a small preview of an image file, decoding a few per frame
+fn render_thumbnail(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    browser: &mut FileBrowser,
+    path: &Path,
+    thumbnails_loaded: &mut usize,
+) {
+    let key = thumbnail_key(path);
+    if !state.texture_cache.contains_key(&key) && !browser.failed_thumbnails.contains(path) {
+        if *thumbnails_loaded < THUMBNAILS_PER_FRAME {
+            *thumbnails_loaded += 1;
+            match load_image_file_texture(ui.ctx(), &path.to_string_lossy()) {
+                Ok(texture) => {
+                    state.texture_cache.insert(key.clone(), texture);
+                }
+                Err(_) => {
+                    browser.failed_thumbnails.insert(path.to_path_buf());
+                }
+            }
+        } else {
+            ui.ctx().request_repaint();
+        }
+    }
+
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(THUMBNAIL_SIZE, THUMBNAIL_SIZE),
+        egui::Sense::hover(),
+    );
+    if let Some(texture) = state.texture_cache.get(&key) {
+        let size = texture.size_vec2();
+        let scale = (THUMBNAIL_SIZE / size.x).min(THUMBNAIL_SIZE / size.y);
+        let image_rect = egui::Rect::from_center_size(rect.center(), size * scale);
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        ui.painter()
+            .image(texture.id(), image_rect, uv, egui::Color32::WHITE);
+    } else {
+        ui.painter()
+            .rect_filled(rect, 2.0, egui::Color32::from_gray(40));
+    }
+}
+
+/// Hand the picked path to whatever opened the browser
+fn complete_browse(state: &mut AppState, purpose: BrowsePurpose, path: PathBuf) {
+    let path_str = path.to_string_lossy().to_string();
+    match purpose {
+        BrowsePurpose::OpenProject => {
+            if let Err(e) = state.load_project(&path_str) {
+                state.set_status(format!("Load failed: {}", e));
+            }
+        }
+        BrowsePurpose::SaveProjectAs { then } => match state.save_project_as(&path_str) {
+            Ok(()) => {
+                state.set_status(format!("Saved to {}", path_str));
+                if let Some(action) = then {
+                    perform_pending_action(state, action, true);
+                }
+            }
+            Err(e) => state.set_status(format!("Save failed: {}", e)),
+        },
+        BrowsePurpose::ExportAnimation { .. } | BrowsePurpose::ExportAllAnimations { .. }
+            if state.export_job.is_some() =>
+        {
+            state.set_status("An export is already running");
+        }
+        BrowsePurpose::ExportAnimation { exporter } => {
+            match export_current_animation(state, exporter, &path_str) {
+                Ok(job) => state.export_job = Some(job),
+                Err(e) => state.set_status(format!("Export failed: {}", e)),
+            }
+        }
+        BrowsePurpose::ExportAllAnimations { exporter } => {
+            match export_all_animations(state, exporter, &path_str) {
+                Ok(job) => state.export_job = Some(job),
+                Err(e) => state.set_status(format!("Export failed: {}", e)),
+            }
+        }
+        BrowsePurpose::ReferenceImage => state.set_frame_reference(&path_str),
+        BrowsePurpose::RotationImage { angle } => {
+            match state.import_rotation_image(angle, &path_str) {
+                Ok(()) => state.set_status(format!("Imported image for {}°", angle)),
+                Err(e) => state.set_status(format!("Import failed: {}", e)),
+            }
+        }
+        BrowsePurpose::ImportDialogImage => state.import_image_path = path_str,
+        BrowsePurpose::FolderImport => {
+            state.folder_import_path = path_str;
+            state.folder_import_plan = None;
+        }
+        BrowsePurpose::Spritesheet => state.sheet_import.path = path_str,
+    }
+}
@@ -1,6 +1,8 @@
 mod canvas;
 mod character_editor;
 mod dialogs;
+mod dope_sheet;
+mod file_browser;
 mod rotation_wheel;
 mod system;
 mod widgets;
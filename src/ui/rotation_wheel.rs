@@ -1,8 +1,9 @@
 use bevy_egui::egui;
 use std::collections::HashMap;
 
-use crate::imaging::decode_base64_to_texture;
-use crate::state::AppState;
+use crate::imaging::{image_texture_key, rgba_to_texture, rotation_preview_texture_key};
+use crate::model::{Angle, RotationMode, UsageQuery};
+use crate::state::{AppState, BrowsePurpose};
 use crate::ui::widgets::scaled_font;
 
 /// Renders a circular rotation wheel for importing/viewing rotation sprites
@@ -10,17 +11,19 @@ pub fn render_rotation_wheel(
     ui: &mut egui::Ui,
     state: &mut AppState,
     char_name: &str,
-    rotations: &[(u16, bool)],
+    rotations: &[(Angle, bool)],
 ) {
     // Push a unique ID scope for this wheel instance
-    let part_name = state.editor_selected_part.as_deref().unwrap_or("none");
-    let state_name = state.editor_selected_state.as_deref().unwrap_or("default");
+    let part_name = state.editor_selected_part.clone().unwrap_or_else(|| "none".to_string());
+    let state_name = state
+        .editor_selected_state
+        .clone()
+        .unwrap_or_else(|| "default".to_string());
     ui.push_id(format!("rot_wheel_{}_{}", part_name, state_name), |ui| {
         let available = ui.available_size();
         let wheel_size = available.x.min(500.0);
         let center_y = 250.0; // Fixed height for the wheel area
         let radius = 120.0; // Increased radius
-        let slot_size = 64.0; // Larger slots for sprites
 
         // Reserve space for the wheel
         let (response, painter) = ui.allocate_painter(
@@ -31,23 +34,48 @@ pub fn render_rotation_wheel(
         let center = response.rect.center();
 
         // Get the angles - default to 8 rotations (45 degree mode)
-        let angles: Vec<u16> = if rotations.is_empty() {
-            vec![0, 45, 90, 135, 180, 225, 270, 315]
+        let angles: Vec<Angle> = if rotations.is_empty() {
+            RotationMode::Deg45.angles()
         } else {
-            let mut sorted: Vec<u16> = rotations.iter().map(|(a, _)| *a).collect();
+            let mut sorted: Vec<Angle> = rotations.iter().map(|(a, _)| *a).collect();
             sorted.sort();
             sorted
         };
 
+        // Larger slots for sprites, shrunk so 16 or 32 directions still fit around the circle.
+        // Small slots leave the angle to the hover text.
+        let slot_spacing = std::f32::consts::TAU * radius / angles.len() as f32;
+        let slot_size = (slot_spacing - 4.0).min(64.0);
+        let show_angle_labels = slot_size >= 40.0;
+
         // Create a map of angle -> has_image for quick lookup
-        let rotation_map: HashMap<u16, bool> = rotations.iter().cloned().collect();
+        let rotation_map: HashMap<Angle, bool> = rotations.iter().cloned().collect();
+
+        // Generated rotations, and whether their source was redrawn since (angle -> stale)
+        let generated: HashMap<Angle, bool> = state
+            .project
+            .as_ref()
+            .and_then(|project| project.get_part(char_name, &part_name))
+            .and_then(|p| p.states.iter().find(|s| s.name == state_name))
+            .map(|s| {
+                s.rotations
+                    .values()
+                    .filter(|r| r.generated.is_some())
+                    .map(|r| (r.angle, s.is_generated_stale(r.angle)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let has_previews = state
+            .rotation_previews
+            .as_ref()
+            .is_some_and(|p| p.is_for(char_name, &part_name, &state_name));
 
         // Draw slots in a circle
         for angle in &angles {
             // Convert angle to radians - 0° = East (right), counterclockwise
             // 0° = E, 90° = N, 180° = W, 270° = S
             // In screen coordinates, Y increases downward, so negate sin
-            let rad = (*angle as f32).to_radians();
+            let rad = angle.degrees().to_radians();
             let slot_center = center + egui::vec2(rad.cos() * radius, -rad.sin() * radius);
 
             // Slot rectangle
@@ -63,29 +91,46 @@ pub fn render_rotation_wheel(
             };
             painter.rect_filled(slot_rect, 4.0, bg_color);
 
-            // Try to draw the sprite image if it exists
-            if has_image {
-                let texture_key = format!("{}/{}/{}/{}", char_name, part_name, state_name, angle);
+            // Generated previews are drawn faded over their slot until accepted
+            let preview = state
+                .rotation_previews
+                .as_ref()
+                .filter(|_| has_previews)
+                .and_then(|p| p.get(*angle));
+            if let Some(preview) = preview {
+                let texture_key = rotation_preview_texture_key(*angle);
+                if !state.texture_cache.contains_key(&texture_key) {
+                    let texture = rgba_to_texture(ui.ctx(), &texture_key, &preview.image);
+                    state.texture_cache.insert(texture_key.clone(), texture);
+                }
+                if let Some(texture) = state.texture_cache.get(&texture_key) {
+                    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                    let tint = egui::Color32::from_rgba_unmultiplied(255, 255, 255, 160);
+                    painter.image(texture.id(), slot_rect.shrink(2.0), uv, tint);
+                }
+            } else if has_image {
+                // Try to draw the sprite image if it exists
+                // Look up the image stored for this rotation
+                let image = state.project.as_ref().and_then(|project| {
+                    project
+                        .get_part(char_name, &part_name)
+                        .and_then(|p| p.states.iter().find(|s| s.name == state_name))
+                        .and_then(|s| s.rotations.get(angle))
+                        .and_then(|r| r.image.as_ref())
+                        .and_then(|hash| {
+                            project.images.decoded(hash).ok().map(|image| (hash.clone(), image))
+                        })
+                });
+                let texture_key = image
+                    .as_ref()
+                    .map(|(hash, _)| image_texture_key(hash))
+                    .unwrap_or_default();
 
                 // Get or create texture
-                if !state.texture_cache.contains_key(&texture_key) {
-                    // Try to load the image data
-                    if let Some(ref project) = state.project {
-                        if let Some(character) = project.get_character(char_name) {
-                            if let Some(part) = character.get_part(part_name) {
-                                if let Some(state_obj) = part.states.iter().find(|s| s.name == state_name) {
-                                    if let Some(rotation) = state_obj.rotations.get(angle) {
-                                        if let Some(ref base64_data) = rotation.image_data {
-                                            if let Ok(texture) =
-                                                decode_base64_to_texture(ui.ctx(), &texture_key, base64_data)
-                                            {
-                                                state.texture_cache.insert(texture_key.clone(), texture);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                if let Some((_, ref image)) = image {
+                    if !state.texture_cache.contains_key(&texture_key) {
+                        let texture = rgba_to_texture(ui.ctx(), &texture_key, image);
+                        state.texture_cache.insert(texture_key.clone(), texture);
                     }
                 }
 
@@ -96,25 +141,50 @@ pub fn render_rotation_wheel(
                 }
             }
 
-            painter.rect_stroke(slot_rect, 4.0, egui::Stroke::new(1.0, egui::Color32::WHITE));
+            let border = if preview.is_some() {
+                egui::Stroke::new(2.0, egui::Color32::LIGHT_BLUE)
+            } else if generated.get(angle) == Some(&true) {
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 160, 0))
+            } else {
+                egui::Stroke::new(1.0, egui::Color32::WHITE)
+            };
+            painter.rect_stroke(slot_rect, 4.0, border);
+            if generated.contains_key(angle) && preview.is_none() {
+                painter.text(
+                    slot_rect.right_top() + egui::vec2(-4.0, 2.0),
+                    egui::Align2::RIGHT_TOP,
+                    "gen",
+                    egui::FontId::proportional(scaled_font(10.0, state.config.ui_scale)),
+                    egui::Color32::LIGHT_GRAY,
+                );
+            }
 
             // Draw angle label below the slot
-            let label_pos = slot_center + egui::vec2(0.0, slot_size / 2.0 + 10.0);
-            painter.text(
-                label_pos,
-                egui::Align2::CENTER_CENTER,
-                format!("{}°", angle),
-                egui::FontId::proportional(scaled_font(12.0, state.config.ui_scale)),
-                egui::Color32::WHITE,
-            );
+            if show_angle_labels {
+                let label_pos = slot_center + egui::vec2(0.0, slot_size / 2.0 + 10.0);
+                painter.text(
+                    label_pos,
+                    egui::Align2::CENTER_CENTER,
+                    format!("{}°", angle),
+                    egui::FontId::proportional(scaled_font(12.0, state.config.ui_scale)),
+                    egui::Color32::WHITE,
+                );
+            }
 
             // Check for click on this slot
-            let slot_response =
-                ui.interact(slot_rect, ui.id().with(("rot_slot", *angle)), egui::Sense::click());
+            let slot_response = ui
+                .interact(slot_rect, ui.id().with(("rot_slot", *angle)), egui::Sense::click())
+                .on_hover_text(format!("{}°", angle));
             if slot_response.clicked() {
-                // Store angle for pending import (file picker will be called after render)
-                state.pending_rotation_import = Some(*angle);
+                state.open_file_browser(BrowsePurpose::RotationImage { angle: *angle });
             }
+            slot_response.context_menu(|ui| {
+                if ui.button("Find Usages").clicked() {
+                    let query = UsageQuery::rotation(char_name, &part_name, &state_name, *angle);
+                    state.find_usages(query);
+                    ui.close_menu();
+                }
+            });
 
             if slot_response.hovered() {
                 painter.rect_stroke(slot_rect, 4.0, egui::Stroke::new(2.0, egui::Color32::YELLOW));
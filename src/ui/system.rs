@@ -1,17 +1,19 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
-use std::fs;
+use std::ops::Range;
 use std::path::PathBuf;
 
-use crate::export::{export_all_animations, export_current_animation, render_frame_to_image};
-use crate::file::{pick_export_file, pick_export_folder, pick_open_file, pick_save_file};
-use crate::imaging::{create_reference_thumbnail, decode_base64_to_texture, calculate_fit_scale, render_frame_thumbnail};
-use crate::model::Project;
-use crate::state::{ActiveTab, ContextMenuTarget, GalleryDrag, PendingAction, DEFAULT_PANEL_MARGIN, ZOOM_LEVELS};
+use crate::export::render_frame_to_image;
+use crate::file::read_project_manifest;
+use crate::imaging::{image_texture_key, render_frame_thumbnail, rgba_to_texture};
+use crate::model::{Angle, NestedAnimation, RotationMode};
+use crate::state::{ActiveTab, BrowsePurpose, ContextMenuTarget, GalleryDrag, PendingAction, RetargetSettings, StateReplaceSettings, AUTOSAVE_INTERVALS, DEFAULT_PANEL_MARGIN, ZOOM_LEVELS};
 use crate::state::AppState;
 use crate::ui::canvas::render_canvas;
 use crate::ui::character_editor::render_character_editor;
 use crate::ui::dialogs::render_dialogs;
+use crate::ui::dope_sheet::render_dope_sheet;
+use crate::ui::file_browser::render_file_browser;
 use crate::ui::widgets::{format_relative_time, format_zoom, scaled_font, scaled_margin, tab_button};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -98,6 +100,7 @@ pub fn ui_system(mut contexts: EguiContexts, mut state: ResMut<AppState>, time:
 
     // Dialogs (rendered first so they appear on top)
     render_dialogs(ctx, &mut state);
+    render_file_browser(ctx, &mut state);
 
     // Menu bar
     let menu_font_size = scaled_font(15.0, state.config.ui_scale);
@@ -118,12 +121,8 @@ pub fn ui_system(mut contexts: EguiContexts, mut state: ResMut<AppState>, time:
                     if ui.button("Open...").clicked() {
                         if state.has_unsaved_changes() {
                             state.pending_action = Some(PendingAction::OpenProject);
-                        } else if let Some(path) = pick_open_file() {
-                            let path_str = path.to_string_lossy().to_string();
-                            match state.load_project(&path_str) {
-                                Ok(()) => state.set_status(format!("Loaded {}", path_str)),
-                                Err(e) => state.set_status(format!("Load failed: {}", e)),
-                            }
+                        } else {
+                            state.open_file_browser(BrowsePurpose::OpenProject);
                         }
                         ui.close_menu();
                     }
@@ -136,9 +135,13 @@ pub fn ui_system(mut contexts: EguiContexts, mut state: ResMut<AppState>, time:
                         .add_enabled(has_project && has_path, egui::Button::new("Save"))
                         .clicked()
                     {
-                        match state.save_project() {
-                            Ok(()) => state.set_status("Project saved"),
-                            Err(e) => state.set_status(format!("Save failed: {}", e)),
+                        if state.should_offer_bundle_migration() {
+                            state.show_bundle_migration_dialog = true;
+                        } else {
+                            match state.save_project() {
+                                Ok(()) => state.set_status("Project saved"),
+                                Err(e) => state.set_status(format!("Save failed: {}", e)),
+                            }
                         }
                         ui.close_menu();
                     }
@@ -146,13 +149,7 @@ pub fn ui_system(mut contexts: EguiContexts, mut state: ResMut<AppState>, time:
                         .add_enabled(has_project, egui::Button::new("Save As..."))
                         .clicked()
                     {
-                        if let Some(path) = pick_save_file() {
-                            let path_str = path.to_string_lossy().to_string();
-                            match state.save_project_as(&path_str) {
-                                Ok(()) => state.set_status(format!("Saved to {}", path_str)),
-                                Err(e) => state.set_status(format!("Save failed: {}", e)),
-                            }
-                        }
+                        state.open_file_browser(BrowsePurpose::SaveProjectAs { then: None });
                         ui.close_menu();
                     }
                     // Close Project
@@ -168,6 +165,24 @@ pub fn ui_system(mut contexts: EguiContexts, mut state: ResMut<AppState>, time:
                         }
                         ui.close_menu();
                     }
+                    if ui
+                        .add_enabled(has_project, egui::Button::new("Validate Project..."))
+                        .clicked()
+                    {
+                        state.validate_project();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    ui.menu_button("Autosave", |ui| {
+                        for (label, secs) in AUTOSAVE_INTERVALS {
+                            let selected = state.config.autosave_interval_secs == secs;
+                            if ui.radio(selected, label).clicked() {
+                                state.config.autosave_interval_secs = secs;
+                                state.config.save();
+                                ui.close_menu();
+                            }
+                        }
+                    });
                     ui.separator();
                     if ui.button("Exit").clicked() {
                         if state.has_unsaved_changes() {
@@ -181,10 +196,33 @@ pub fn ui_system(mut contexts: EguiContexts, mut state: ResMut<AppState>, time:
             );
 
             ui.menu_button(egui::RichText::new("Edit").size(menu_font_size), |ui| {
-                if ui.button("Undo").clicked() {
+                let undo_label = state.undo_label().map(|l| format!("Undo {}", l));
+                let undo = ui.add_enabled(
+                    undo_label.is_some(),
+                    egui::Button::new(undo_label.as_deref().unwrap_or("Undo")),
+                );
+                if undo.clicked() {
+                    state.undo();
+                    ui.close_menu();
+                }
+                let redo_label = state.redo_label().map(|l| format!("Redo {}", l));
+                let redo = ui.add_enabled(
+                    redo_label.is_some(),
+                    egui::Button::new(redo_label.as_deref().unwrap_or("Redo")),
+                );
+                if redo.clicked() {
+                    state.redo();
                     ui.close_menu();
                 }
-                if ui.button("Redo").clicked() {
+                ui.separator();
+                let find_replace = egui::Button::new("Find and Replace States...");
+                if ui.add_enabled(state.project.is_some(), find_replace).clicked()
+                {
+                    state.replace_states = StateReplaceSettings {
+                        character: state.active_character.clone(),
+                        ..Default::default()
+                    };
+                    state.show_replace_states_dialog = true;
                     ui.close_menu();
                 }
             });
@@ -284,6 +322,24 @@ pub fn ui_system(mut contexts: EguiContexts, mut state: ResMut<AppState>, time:
                         state.dialog_needs_focus = true;
                         ui.close_menu();
                     }
+                    if ui
+                        .add_enabled(has_characters, egui::Button::new("Import Folder..."))
+                        .on_hover_text("Import a folder of images named by part, state and angle")
+                        .clicked()
+                    {
+                        state.show_folder_import_dialog = true;
+                        state.folder_import_plan = None;
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    if ui
+                        .add_enabled(has_characters, egui::Button::new("Reimport All Changed"))
+                        .on_hover_text("Re-import every rotation linked to an external image file")
+                        .clicked()
+                    {
+                        state.reimport_all_changed();
+                        ui.close_menu();
+                    }
                 },
             );
 
@@ -338,48 +394,31 @@ pub fn ui_system(mut contexts: EguiContexts, mut state: ResMut<AppState>, time:
                         .current_animation()
                         .map(|a| !a.frames.is_empty())
                         .unwrap_or(false);
-                    if ui
-                        .add_enabled(
-                            has_project && has_animation,
-                            egui::Button::new("Export Current Animation..."),
-                        )
-                        .clicked()
-                    {
-                        if let Some(path) = pick_export_file() {
-                            let path_str = path.to_string_lossy().to_string();
-                            match export_current_animation(&state, &path_str) {
-                                Ok((png_path, json_path)) => {
-                                    state.set_status(format!(
-                                        "Exported to {} and {}",
-                                        png_path, json_path
-                                    ));
-                                }
-                                Err(e) => {
-                                    state.set_status(format!("Export failed: {}", e));
-                                }
+                    // One submenu per registered format
+                    for exporter in state.exporters.all().to_vec() {
+                        ui.menu_button(exporter.name(), |ui| {
+                            if ui
+                                .add_enabled(
+                                    has_project && has_animation,
+                                    egui::Button::new("Current Animation..."),
+                                )
+                                .clicked()
+                            {
+                                let exporter = exporter.clone();
+                                state.open_file_browser(BrowsePurpose::ExportAnimation { exporter });
+                                ui.close_menu();
                             }
-                        }
-                        ui.close_menu();
-                    }
-                    if ui
-                        .add_enabled(has_project, egui::Button::new("Export All Animations..."))
-                        .clicked()
-                    {
-                        if let Some(path) = pick_export_folder() {
-                            let path_str = path.to_string_lossy().to_string();
-                            match export_all_animations(&state, &path_str) {
-                                Ok(count) => {
-                                    state.set_status(format!(
-                                        "Exported {} animations to {}",
-                                        count, path_str
-                                    ));
-                                }
-                                Err(e) => {
-                                    state.set_status(format!("Export failed: {}", e));
-                                }
+                            if ui
+                                .add_enabled(has_project, egui::Button::new("All Animations..."))
+                                .clicked()
+                            {
+                                let exporter = exporter.clone();
+                                state.open_file_browser(BrowsePurpose::ExportAllAnimations {
+                                    exporter,
+                                });
+                                ui.close_menu();
                             }
-                        }
-                        ui.close_menu();
+                        });
                     }
                 },
             );
@@ -463,7 +502,7 @@ fn render_asset_browser(ui: &mut egui::Ui, state: &mut AppState) {
                     ui.label("Name:");
                     let mut name = project.name.clone();
                     if ui.text_edit_singleline(&mut name).changed() {
-                        if let Some(ref mut p) = state.project {
+                        if let Some(p) = state.project_mut() {
                             p.name = name;
                         }
                     }
@@ -495,9 +534,12 @@ fn render_asset_browser(ui: &mut egui::Ui, state: &mut AppState) {
                                 .clicked()
                             {
                                 state.active_character = Some(character.name.clone());
-                                state.editor_selected_part =
-                                    character.parts.first().map(|p| p.name.clone());
+                                state.editor_selected_part = project
+                                    .character_parts(character)
+                                    .next()
+                                    .map(|p| p.name.clone());
                                 state.editor_selected_state = None;
+                                state.preview_outfit = None;
                                 state.current_animation = 0;
                                 state.current_frame = 0;
                                 state.needs_zoom_fit = true;
@@ -596,6 +638,7 @@ fn render_asset_browser(ui: &mut egui::Ui, state: &mut AppState) {
                                     if response.clicked() {
                                         state.current_animation = i;
                                         state.current_frame = 0;
+                                        state.frame_selection = None;
                                     }
 
                                     let anim_name = anim.name.clone();
@@ -613,6 +656,15 @@ fn render_asset_browser(ui: &mut egui::Ui, state: &mut AppState) {
                                             state.dialog_needs_focus = true;
                                             ui.close_menu();
                                         }
+                                        if ui.button("Copy to Character...").clicked() {
+                                            state.retarget = RetargetSettings {
+                                                source_character: char_name_for_menu.clone(),
+                                                animation_name: anim_name.clone(),
+                                                ..Default::default()
+                                            };
+                                            state.show_retarget_dialog = true;
+                                            ui.close_menu();
+                                        }
                                         if ui.button("Delete").clicked() {
                                             state.context_menu_target =
                                                 Some(ContextMenuTarget::Animation {
@@ -645,28 +697,26 @@ fn render_asset_browser(ui: &mut egui::Ui, state: &mut AppState) {
                         ui.heading("Parts Gallery");
                         ui.label("(Drag to canvas)");
 
-                        let gallery_parts: Vec<(String, String, Option<String>)> = character
-                            .parts
-                            .iter()
+                        let gallery_parts: Vec<(String, String, Option<String>)> = project
+                            .character_parts(character)
                             .map(|p| {
-                                let thumb_data = p
+                                let thumb_hash = p
                                     .states
                                     .first()
-                                    .and_then(|s| s.rotations.get(&0))
-                                    .and_then(|r| r.image_data.clone());
+                                    .and_then(|s| s.rotations.get(&Angle::ZERO))
+                                    .and_then(|r| r.image.clone());
                                 (
                                     p.name.clone(),
                                     p.states
                                         .first()
                                         .map(|s| s.name.clone())
                                         .unwrap_or_else(|| "default".to_string()),
-                                    thumb_data,
+                                    thumb_hash,
                                 )
                             })
                             .collect();
 
                         let char_id_for_gallery = character.id;
-                        let char_name_for_gallery = active_char_name.clone();
                         let ui_scale = state.config.ui_scale;
                         let gallery_size = scaled_margin(48.0, ui_scale);
                         let gallery_spacing = scaled_margin(8.0, ui_scale);
@@ -680,19 +730,21 @@ fn render_asset_browser(ui: &mut egui::Ui, state: &mut AppState) {
                                 scaled_margin(4.0, ui_scale),
                             ])
                             .show(ui, |ui| {
-                                for (idx, (part_name, state_name, thumb_data)) in
+                                for (idx, (part_name, state_name, thumb_hash)) in
                                     gallery_parts.iter().enumerate()
                                 {
-                                    let texture_key =
-                                        format!("gallery/{}/{}", char_name_for_gallery, part_name);
-
-                                    if let Some(ref data) = thumb_data {
-                                        if !state.texture_cache.contains_key(&texture_key) {
-                                            if let Ok(tex) =
-                                                decode_base64_to_texture(ui.ctx(), &texture_key, data)
-                                            {
-                                                state.texture_cache.insert(texture_key.clone(), tex);
-                                            }
+                                    let texture_key = thumb_hash
+                                        .as_deref()
+                                        .map(image_texture_key)
+                                        .unwrap_or_default();
+
+                                    if !state.texture_cache.contains_key(&texture_key) {
+                                        if let Some(image) = thumb_hash
+                                            .as_deref()
+                                            .and_then(|h| project.images.decoded(h).ok())
+                                        {
+                                            let tex = rgba_to_texture(ui.ctx(), &texture_key, &image);
+                                            state.texture_cache.insert(texture_key.clone(), tex);
                                         }
                                     }
 
@@ -779,7 +831,7 @@ fn render_asset_browser(ui: &mut egui::Ui, state: &mut AppState) {
                                     if response.drag_started() {
                                         state.gallery_drag = Some(GalleryDrag {
                                             character_id: char_id_for_gallery,
-                                            character_name: char_name_for_gallery.clone(),
+                                            image_hash: thumb_hash.clone(),
                                             part_name: part_name.clone(),
                                             state_name: state_name.clone(),
                                         });
@@ -815,29 +867,39 @@ fn render_inspector(ui: &mut egui::Ui, state: &mut AppState) {
                 |ui| {
                     ui.set_min_height(inspector_height);
 
-                    let selected_info = state.get_selected_placed_part().map(|p| {
+                    let selected_nested = state.get_selected_placed_part().and_then(|p| {
+                        p.nested.clone().map(|nested| (p.layer_name.clone(), p.position, nested))
+                    });
+                    if let Some((layer_name, position, nested)) = selected_nested {
+                        render_nested_inspector(ui, state, &layer_name, position, nested);
+                        return;
+                    }
+
+                    let selected_placed = state.get_selected_placed_part();
+                    let selected_part = selected_placed
+                        .zip(state.project.as_ref())
+                        .and_then(|(placed, project)| project.placed_part(placed));
+                    let selected_state =
+                        selected_part.zip(selected_placed).and_then(|(part, placed)| {
+                            part.get_state_by_id(placed.state_id)
+                        });
+                    let selected_info = selected_placed.map(|p| {
                         (
                             p.character_id,
-                            p.part_name.clone(),
-                            p.state_name.clone(),
+                            selected_part.map(|part| part.name.clone()).unwrap_or_default(),
+                            selected_state.map(|s| s.name.clone()).unwrap_or_default(),
                             p.position,
                             p.rotation,
                             p.z_override,
                         )
                     });
-
-                    let available_states: Vec<String> =
-                        if let Some((character_id, ref part_name, _, _, _, _)) = selected_info {
-                            state
-                                .project
-                                .as_ref()
-                                .and_then(|p| p.get_character_by_id(character_id))
-                                .and_then(|c| c.get_part(part_name))
-                                .map(|p| p.states.iter().map(|s| s.name.clone()).collect())
-                                .unwrap_or_default()
-                        } else {
-                            vec![]
-                        };
+                    let available_states: Vec<(u64, String)> = selected_part
+                        .map(|p| p.states.iter().map(|s| (s.id, s.name.clone())).collect())
+                        .unwrap_or_default();
+                    // Angles drawn for the layer's current state
+                    let available_angles: Vec<Angle> = selected_state
+                        .map(|s| s.angles())
+                        .unwrap_or_else(|| RotationMode::default().angles());
 
                     if let Some((
                         _character_id,
@@ -857,7 +919,7 @@ fn render_inspector(ui: &mut egui::Ui, state: &mut AppState) {
                             egui::ComboBox::from_id_salt("part_state")
                                 .selected_text(&selected_state)
                                 .show_ui(ui, |ui| {
-                                    for state_name in &available_states {
+                                    for (state_id, state_name) in &available_states {
                                         if ui
                                             .selectable_value(
                                                 &mut selected_state,
@@ -868,7 +930,7 @@ fn render_inspector(ui: &mut egui::Ui, state: &mut AppState) {
                                         {
                                             if let Some(part) = state.get_selected_placed_part_mut()
                                             {
-                                                part.state_name = selected_state.clone();
+                                                part.state_id = *state_id;
                                                 state.texture_cache.clear();
                                             }
                                         }
@@ -923,7 +985,7 @@ fn render_inspector(ui: &mut egui::Ui, state: &mut AppState) {
                             egui::ComboBox::from_id_salt("part_rotation")
                                 .selected_text(format!("{}°", rot))
                                 .show_ui(ui, |ui| {
-                                    for angle in [0, 45, 90, 135, 180, 225, 270, 315] {
+                                    for angle in available_angles {
                                         if ui
                                             .selectable_value(&mut rot, angle, format!("{}°", angle))
                                             .changed()
@@ -950,6 +1012,63 @@ fn render_inspector(ui: &mut egui::Ui, state: &mut AppState) {
     render_reference_panel(ui, state);
 }
 
+/// Inspector for a layer that plays a nested animation
+fn render_nested_inspector(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    layer_name: &str,
+    position: (f32, f32),
+    mut nested: NestedAnimation,
+) {
+    let source = state
+        .project
+        .as_ref()
+        .and_then(|p| p.get_character_by_id(nested.character_id))
+        .map(|c| format!("{} / {}", c.name, nested.animation_name))
+        .unwrap_or_else(|| format!("{} (missing)", nested.animation_name));
+    ui.label(format!("Selected layer: {}", layer_name));
+    ui.label(format!("Plays: {}", source));
+    ui.separator();
+
+    let mut pos_x = position.0;
+    let mut pos_y = position.1;
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Position X:");
+        changed |= ui.add(egui::DragValue::new(&mut pos_x).speed(1.0)).changed();
+        ui.label("Y:");
+        changed |= ui.add(egui::DragValue::new(&mut pos_y).speed(1.0)).changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label("Start offset:");
+        changed |= ui
+            .add(
+                egui::DragValue::new(&mut nested.start_offset_ms)
+                    .speed(10.0)
+                    .range(0..=600_000)
+                    .suffix(" ms"),
+            )
+            .on_hover_text("Time into this animation at which the nested one starts")
+            .changed();
+    });
+    changed |= ui
+        .checkbox(&mut nested.looping, "Loop")
+        .on_hover_text("Otherwise it plays once and holds its last frame")
+        .changed();
+
+    if changed {
+        let pixel_aligned = state.pixel_aligned;
+        if let Some(part) = state.get_selected_placed_part_mut() {
+            part.position = if pixel_aligned {
+                (pos_x.round(), pos_y.round())
+            } else {
+                (pos_x, pos_y)
+            };
+            part.nested = Some(nested);
+        }
+    }
+}
+
 fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
     egui::TopBottomPanel::top("layers_section")
         .show_separator_line(true)
@@ -960,7 +1079,8 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
         .show_inside(ui, |ui| {
             ui.heading("Layers");
 
-            let layers: Vec<(u64, String, usize, bool, String, u16)> = {
+            // (id, name, index, visible, state badge, rotation badge)
+            let layers: Vec<(u64, String, usize, bool, String, String)> = {
                 if let Some(anim) = state.current_animation() {
                     if let Some(frame) = anim.frames.get(state.current_frame) {
                         frame
@@ -968,17 +1088,32 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
                             .iter()
                             .enumerate()
                             .map(|(idx, p)| {
+                                let (part_name, state_name) = state
+                                    .project
+                                    .as_ref()
+                                    .and_then(|project| project.placed_state(p))
+                                    .map(|(part, s)| (part.name.clone(), s.name.clone()))
+                                    .unwrap_or_default();
                                 (
                                     p.id,
                                     if p.layer_name.is_empty() {
-                                        p.part_name.clone()
+                                        part_name
                                     } else {
                                         p.layer_name.clone()
                                     },
                                     idx,
                                     p.visible,
-                                    p.state_name.clone(),
-                                    p.rotation,
+                                    if p.nested.is_some() {
+                                        "anim".to_string()
+                                    } else {
+                                        state_name
+                                    },
+                                    match p.nested {
+                                        Some(ref nested) => {
+                                            format!("+{}ms", nested.start_offset_ms)
+                                        }
+                                        None => format!("{}°", p.rotation),
+                                    },
                                 )
                             })
                             .collect()
@@ -1022,7 +1157,7 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
                                 scaled_margin(2.0, ui_scale),
                             ])
                             .show(ui, |ui| {
-                                for (id, name, idx, visible, state_name, rotation) in
+                                for (id, name, idx, visible, state_badge_text, rotation_badge_text) in
                                     layers.iter().rev()
                                 {
                                     let is_selected = state.selected_part_id == Some(*id);
@@ -1083,7 +1218,7 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
                                         ));
                                     state_badge.show(ui, |ui| {
                                         ui.label(
-                                            egui::RichText::new(state_name)
+                                            egui::RichText::new(state_badge_text)
                                                 .small()
                                                 .color(egui::Color32::WHITE),
                                         );
@@ -1098,7 +1233,7 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
                                         ));
                                     rot_badge.show(ui, |ui| {
                                         ui.label(
-                                            egui::RichText::new(format!("{}°", rotation))
+                                            egui::RichText::new(rotation_badge_text)
                                                 .small()
                                                 .color(egui::Color32::WHITE),
                                         );
@@ -1172,11 +1307,62 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
                     });
             }
 
+            // Animations that can play as a layer: any but the one being edited
+            let nestable: Vec<(u64, String, Vec<String>)> = state
+                .project
+                .as_ref()
+                .map(|project| {
+                    let active_id = state.active_character_ref().map(|c| c.id);
+                    project
+                        .characters
+                        .iter()
+                        .map(|c| {
+                            let animations = c
+                                .animations
+                                .iter()
+                                .enumerate()
+                                .filter(|(i, _)| {
+                                    Some(c.id) != active_id || *i != state.current_animation
+                                })
+                                .map(|(_, a)| a.name.clone())
+                                .collect();
+                            (c.id, c.name.clone(), animations)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let mut place_animation: Option<(u64, String)> = None;
+            ui.add_enabled_ui(state.current_animation().is_some(), |ui| {
+                ui.menu_button("+ Animation Layer", |ui| {
+                    for (character_id, character_name, animations) in &nestable {
+                        ui.menu_button(character_name, |ui| {
+                            if animations.is_empty() {
+                                ui.label("(No other animations)");
+                            }
+                            for animation_name in animations {
+                                if ui.button(animation_name).clicked() {
+                                    place_animation =
+                                        Some((*character_id, animation_name.clone()));
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    }
+                })
+                .response
+                .on_hover_text("Play another animation inside this frame");
+            });
+            if let Some((character_id, animation_name)) = place_animation {
+                state.place_animation_on_canvas(character_id, &animation_name);
+                state.set_status(format!("Added animation layer '{}'", animation_name));
+            }
+
             // Apply visibility toggle
             if let Some(idx) = toggle_visibility {
                 let current_anim = state.current_animation;
                 let current_frame_idx = state.current_frame;
                 if let Some(ref char_name) = state.active_character.clone() {
+                    state.mark_changed();
                     if let Some(ref mut project) = state.project {
                         if let Some(character) = project.get_character_mut(char_name) {
                             if let Some(anim) = character.animations.get_mut(current_anim) {
@@ -1196,6 +1382,7 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
             let current_frame_idx = state.current_frame;
             let active_char = state.active_character.clone();
             if let Some(idx) = move_up {
+                state.mark_changed();
                 if let Some(ref mut project) = state.project {
                     if let Some(ref char_name) = active_char {
                         if let Some(character) = project.get_character_mut(char_name) {
@@ -1211,6 +1398,7 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
                 }
             }
             if let Some(idx) = move_down {
+                state.mark_changed();
                 if let Some(ref mut project) = state.project {
                     if let Some(ref char_name) = active_char {
                         if let Some(character) = project.get_character_mut(char_name) {
@@ -1226,6 +1414,7 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
                 }
             }
             if let Some(idx) = move_to_top {
+                state.mark_changed();
                 if let Some(ref mut project) = state.project {
                     if let Some(ref char_name) = active_char {
                         if let Some(character) = project.get_character_mut(char_name) {
@@ -1243,6 +1432,7 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
                 }
             }
             if let Some(idx) = move_to_bottom {
+                state.mark_changed();
                 if let Some(ref mut project) = state.project {
                     if let Some(ref char_name) = active_char {
                         if let Some(character) = project.get_character_mut(char_name) {
@@ -1262,9 +1452,6 @@ fn render_layers_panel(ui: &mut egui::Ui, state: &mut AppState) {
 }
 
 fn render_reference_panel(ui: &mut egui::Ui, state: &mut AppState) {
-    use crate::file::pick_image_file;
-    use crate::model;
-
     egui::TopBottomPanel::top("reference_section")
         .show_separator_line(true)
         .frame(
@@ -1312,57 +1499,13 @@ fn render_reference_panel(ui: &mut egui::Ui, state: &mut AppState) {
                 }
             });
 
-            let load_ref_path = if load_clicked {
-                pick_image_file().map(|p| p.to_string_lossy().to_string())
-            } else {
-                None
-            };
-            let clear_ref = clear_clicked;
-
-            if let Some(path_str) = load_ref_path {
-                if let Some(ref mut project) = state.project {
-                    if !project.reference_thumbnails.contains_key(&path_str) {
-                        if let Ok((thumbnail, _original_size)) =
-                            create_reference_thumbnail(&path_str, 256)
-                        {
-                            project
-                                .reference_thumbnails
-                                .insert(path_str.clone(), thumbnail);
-                        }
-                    }
-
-                    if let Some(ref cn) = char_name {
-                        let canvas_size = project
-                            .get_character(cn)
-                            .map(|c| c.canvas_size)
-                            .unwrap_or((64, 64));
-
-                        let scale = if let Ok(bytes) = fs::read(&path_str) {
-                            if let Ok(img) = image::load_from_memory(&bytes) {
-                                calculate_fit_scale((img.width(), img.height()), canvas_size)
-                            } else {
-                                1.0
-                            }
-                        } else {
-                            1.0
-                        };
-
-                        if let Some(character) = project.get_character_mut(cn) {
-                            if let Some(anim) = character.animations.get_mut(current_anim) {
-                                if let Some(frame) = anim.frames.get_mut(current_frame_idx) {
-                                    frame.reference =
-                                        Some(model::FrameReference::new(path_str.clone(), scale));
-                                }
-                            }
-                        }
-                    }
-
-                    state.reference_texture_cache.remove(&path_str);
-                    state.set_status(format!("Loaded reference: {}", path_str));
-                }
+            if load_clicked {
+                state.open_file_browser(BrowsePurpose::ReferenceImage);
             }
+            let clear_ref = clear_clicked;
 
             if clear_ref {
+                state.mark_changed();
                 if let Some(ref mut project) = state.project {
                     if let Some(ref cn) = char_name {
                         if let Some(character) = project.get_character_mut(cn) {
@@ -1377,6 +1520,7 @@ fn render_reference_panel(ui: &mut egui::Ui, state: &mut AppState) {
                 state.set_status("Cleared reference image");
             }
 
+            let mut reference_changed = false;
             if let Some(ref mut project) = state.project {
                 if let Some(ref cn) = char_name {
                     if let Some(character) = project.get_character_mut(cn) {
@@ -1385,25 +1529,31 @@ fn render_reference_panel(ui: &mut egui::Ui, state: &mut AppState) {
                                 if let Some(ref mut frame_ref) = frame.reference {
                                     ui.horizontal(|ui| {
                                         ui.label("Scale:");
-                                        ui.add(
-                                            egui::DragValue::new(&mut frame_ref.scale)
-                                                .speed(0.01)
-                                                .range(0.01..=10.0),
-                                        );
+                                        reference_changed |= ui
+                                            .add(
+                                                egui::DragValue::new(&mut frame_ref.scale)
+                                                    .speed(0.01)
+                                                    .range(0.01..=10.0),
+                                            )
+                                            .changed();
                                     });
 
                                     ui.horizontal(|ui| {
                                         ui.label("Position:");
-                                        ui.add(
-                                            egui::DragValue::new(&mut frame_ref.position.0)
-                                                .prefix("X: ")
-                                                .speed(1.0),
-                                        );
-                                        ui.add(
-                                            egui::DragValue::new(&mut frame_ref.position.1)
-                                                .prefix("Y: ")
-                                                .speed(1.0),
-                                        );
+                                        reference_changed |= ui
+                                            .add(
+                                                egui::DragValue::new(&mut frame_ref.position.0)
+                                                    .prefix("X: ")
+                                                    .speed(1.0),
+                                            )
+                                            .changed();
+                                        reference_changed |= ui
+                                            .add(
+                                                egui::DragValue::new(&mut frame_ref.position.1)
+                                                    .prefix("Y: ")
+                                                    .speed(1.0),
+                                            )
+                                            .changed();
                                     });
 
                                     let display_path = if frame_ref.file_path.len() > 30 {
@@ -1438,8 +1588,13 @@ fn render_reference_panel(ui: &mut egui::Ui, state: &mut AppState) {
                 }
             }
 
+            if reference_changed {
+                state.mark_changed();
+            }
+
             if copy_to_all_clicked {
                 if let Some((scale, position)) = copy_settings {
+                    state.mark_changed();
                     if let Some(ref mut project) = state.project {
                         if let Some(ref cn) = char_name {
                             if let Some(character) = project.get_character_mut(cn) {
@@ -1467,11 +1622,60 @@ fn render_reference_panel(ui: &mut egui::Ui, state: &mut AppState) {
         });
 }
 
-fn render_timeline(ctx: &egui::Context, state: &mut AppState) {
-    use crate::file::pick_save_file;
+/// Timeline edit chosen this frame, applied once the thumbnails are drawn
+enum FrameEdit {
+    InsertBlank(usize),
+    Duplicate(Range<usize>),
+    Reverse(Range<usize>),
+    PingPong(Range<usize>),
+    SetDuration(Range<usize>, u32),
+    Move(Range<usize>, usize),
+}
 
+fn apply_frame_edit(state: &mut AppState, edit: FrameEdit) {
+    let result = state.edit_current_animation(|anim, next_id| match edit {
+        FrameEdit::InsertBlank(index) => {
+            anim.insert_frame(index);
+            (Some(index..index + 1), "Inserted blank frame".to_string())
+        }
+        FrameEdit::Duplicate(range) => {
+            let copy = anim.duplicate_frames(range, next_id);
+            let message = format!("Duplicated {} frame(s)", copy.len());
+            (Some(copy), message)
+        }
+        FrameEdit::Reverse(range) => {
+            anim.reverse_frames(range.clone());
+            (Some(range), "Reversed frames".to_string())
+        }
+        FrameEdit::PingPong(range) => {
+            let added = anim.append_ping_pong(range.clone(), next_id);
+            let message = format!("Added {} ping-pong frame(s)", added);
+            (Some(range.start..range.end + added), message)
+        }
+        FrameEdit::SetDuration(range, duration_ms) => {
+            anim.set_frames_duration(range.clone(), duration_ms);
+            let message = format!("Set {} frame(s) to {} ms", range.len(), duration_ms);
+            (Some(range), message)
+        }
+        FrameEdit::Move(range, to) => {
+            let moved = anim.move_frames(range, to);
+            (Some(moved), "Moved frames".to_string())
+        }
+    });
+    if let Some((selection, message)) = result {
+        if let Some(range) = selection.filter(|r| !r.is_empty()) {
+            state.current_frame = range.start;
+            state.frame_selection = (range.len() > 1).then(|| (range.start, range.end - 1));
+            state.selected_part_id = None;
+        }
+        state.set_status(message);
+    }
+}
+
+fn render_timeline(ctx: &egui::Context, state: &mut AppState) {
     let total_frames = state.total_frames();
-    let timeline_height = scaled_margin(120.0, state.config.ui_scale);
+    let base_height = if state.timeline_dope_sheet { 220.0 } else { 120.0 };
+    let timeline_height = scaled_margin(base_height, state.config.ui_scale);
     egui::TopBottomPanel::bottom("timeline")
         .exact_height(timeline_height)
         .show(ctx, |ui| {
@@ -1506,17 +1710,17 @@ fn render_timeline(ctx: &egui::Context, state: &mut AppState) {
                             }
                         }
                         if ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::S)) {
-                            if state.project_path.is_some() {
+                            if state.should_offer_bundle_migration() {
+                                state.show_bundle_migration_dialog = true;
+                            } else if state.project_path.is_some() {
                                 match state.save_project() {
                                     Ok(()) => state.set_status("Project saved"),
                                     Err(e) => state.set_status(format!("Save failed: {}", e)),
                                 }
-                            } else if let Some(path) = pick_save_file() {
-                                let path_str = path.to_string_lossy().to_string();
-                                match state.save_project_as(&path_str) {
-                                    Ok(()) => state.set_status(format!("Saved to {}", path_str)),
-                                    Err(e) => state.set_status(format!("Save failed: {}", e)),
-                                }
+                            } else {
+                                state.open_file_browser(BrowsePurpose::SaveProjectAs {
+                                    then: None,
+                                });
                             }
                         }
                         if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
@@ -1580,9 +1784,23 @@ fn render_timeline(ctx: &egui::Context, state: &mut AppState) {
                                 anim.fps = fps;
                             }
                         }
+
+                        ui.separator();
+                        if ui
+                            .selectable_label(state.timeline_dope_sheet, "Dope Sheet")
+                            .on_hover_text("Show one row per layer instead of frame thumbnails")
+                            .clicked()
+                        {
+                            state.timeline_dope_sheet = !state.timeline_dope_sheet;
+                        }
                     });
                 });
 
+            if state.timeline_dope_sheet {
+                render_dope_sheet(ui, state);
+                return;
+            }
+
             // Calculate thumbnail dimensions
             let available_height = ui.available_height();
             let thumb_height = (available_height - scaled_margin(8.0, state.config.ui_scale)).max(32.0);
@@ -1602,13 +1820,15 @@ fn render_timeline(ctx: &egui::Context, state: &mut AppState) {
                     let anim_idx = state.current_animation;
 
                     // Collect frame data needed for thumbnail generation
-                    let frame_data: Vec<_> = if let Some(anim) = state.current_animation() {
+                    let frame_data: Vec<_> = if let (Some(project), Some(anim)) =
+                        (&state.project, state.current_animation())
+                    {
                         (0..total_frames)
                             .map(|frame_idx| {
                                 let content_hash = anim
                                     .frames
                                     .get(frame_idx)
-                                    .map(|f| f.content_hash())
+                                    .map(|f| project.frame_content_hash(f))
                                     .unwrap_or(0);
                                 (frame_idx, content_hash)
                             })
@@ -1617,8 +1837,19 @@ fn render_timeline(ctx: &egui::Context, state: &mut AppState) {
                         vec![]
                     };
 
+                    if state
+                        .frame_selection
+                        .is_some_and(|(_, end)| end >= total_frames)
+                    {
+                        state.frame_selection = None;
+                    }
+                    let selection = state.selected_frames();
+                    let mut frame_edit: Option<FrameEdit> = None;
+                    let mut frame_rects: Vec<egui::Rect> = Vec::new();
+
                     for (frame, content_hash) in frame_data {
                         let is_current = frame == state.current_frame;
+                        let is_selected = state.frame_selection.is_some() && selection.contains(&frame);
                         let cache_key = format!(
                             "thumb/{}/{}/{}",
                             char_name.as_deref().unwrap_or(""),
@@ -1635,11 +1866,19 @@ fn render_timeline(ctx: &egui::Context, state: &mut AppState) {
 
                         // Generate thumbnail if needed
                         if needs_regen {
-                            if let (Some(project), Some(anim)) =
-                                (&state.project, state.current_animation())
-                            {
+                            if let (Some(project), Some(character), Some(anim)) = (
+                                &state.project,
+                                state.active_character_ref(),
+                                state.current_animation(),
+                            ) {
                                 if let Ok(frame_image) =
-                                    render_frame_to_image(project, anim, frame, canvas_size)
+                                    render_frame_to_image(
+                                        project,
+                                        character,
+                                        anim,
+                                        frame,
+                                        state.preview_outfit.as_deref(),
+                                    )
                                 {
                                     let texture = render_frame_thumbnail(
                                         ctx,
@@ -1657,12 +1896,15 @@ fn render_timeline(ctx: &egui::Context, state: &mut AppState) {
                         // Allocate space for the thumbnail
                         let (rect, response) = ui.allocate_exact_size(
                             egui::vec2(thumb_width, thumb_height),
-                            egui::Sense::click(),
+                            egui::Sense::click_and_drag(),
                         );
+                        frame_rects.push(rect);
 
                         // Draw background
                         let bg_color = if is_current {
                             egui::Color32::from_rgb(80, 120, 180)
+                        } else if is_selected {
+                            egui::Color32::from_rgb(60, 85, 120)
                         } else if response.hovered() {
                             egui::Color32::from_rgb(60, 60, 70)
                         } else {
@@ -1718,21 +1960,97 @@ fn render_timeline(ctx: &egui::Context, state: &mut AppState) {
                             );
                         }
 
-                        // Handle click
+                        // Click selects a frame, Shift-click a range from the current frame
                         if response.clicked() {
-                            state.current_frame = frame;
-                            state.selected_part_id = None;
+                            if ui.input(|i| i.modifiers.shift) {
+                                let anchor = state.current_frame;
+                                state.frame_selection =
+                                    Some((anchor.min(frame), anchor.max(frame)));
+                            } else {
+                                state.current_frame = frame;
+                                state.frame_selection = None;
+                                state.selected_part_id = None;
+                            }
+                        }
+
+                        // Dragging a selected frame moves the whole selection
+                        let range = if selection.contains(&frame) {
+                            selection.clone()
+                        } else {
+                            frame..frame + 1
+                        };
+                        if response.drag_started() {
+                            state.timeline_drag = Some(range.clone());
                         }
 
-                        // Context menu for delete
                         if let Some(ref cn) = char_name {
                             let cn = cn.clone();
                             response.context_menu(|ui| {
-                                if ui.button("Delete Frame").clicked() {
+                                if ui.button("Insert Blank Frame Before").clicked() {
+                                    frame_edit = Some(FrameEdit::InsertBlank(frame));
+                                    ui.close_menu();
+                                }
+                                if ui.button("Insert Blank Frame After").clicked() {
+                                    frame_edit = Some(FrameEdit::InsertBlank(frame + 1));
+                                    ui.close_menu();
+                                }
+                                ui.separator();
+                                if range.len() > 1 {
+                                    ui.label(format!(
+                                        "Frames {}-{}",
+                                        range.start + 1,
+                                        range.end
+                                    ));
+                                }
+                                if ui.button("Duplicate").clicked() {
+                                    frame_edit = Some(FrameEdit::Duplicate(range.clone()));
+                                    ui.close_menu();
+                                }
+                                if ui
+                                    .add_enabled(range.len() > 1, egui::Button::new("Reverse"))
+                                    .clicked()
+                                {
+                                    frame_edit = Some(FrameEdit::Reverse(range.clone()));
+                                    ui.close_menu();
+                                }
+                                if ui
+                                    .add_enabled(
+                                        range.len() > 2,
+                                        egui::Button::new("Append Ping-Pong"),
+                                    )
+                                    .on_hover_text("Add the frames in reverse so the range plays forward and back")
+                                    .clicked()
+                                {
+                                    frame_edit = Some(FrameEdit::PingPong(range.clone()));
+                                    ui.close_menu();
+                                }
+                                ui.horizontal(|ui| {
+                                    ui.label("Duration:");
+                                    ui.add(
+                                        egui::DragValue::new(&mut state.frame_range_duration)
+                                            .range(1..=10_000)
+                                            .suffix(" ms"),
+                                    );
+                                    if ui.button("Set").clicked() {
+                                        frame_edit = Some(FrameEdit::SetDuration(
+                                            range.clone(),
+                                            state.frame_range_duration,
+                                        ));
+                                        ui.close_menu();
+                                    }
+                                });
+                                ui.separator();
+                                let delete_label = if range.len() > 1 {
+                                    "Delete Frames"
+                                } else {
+                                    "Delete Frame"
+                                };
+                                if ui.button(delete_label).clicked() {
                                     state.context_menu_target = Some(ContextMenuTarget::Frame {
                                         char_name: cn,
                                         anim_index: anim_idx,
-                                        frame_index: frame,
+                                        frame_index: range.start,
+                                        frame_count: range.len(),
                                     });
                                     state.show_delete_confirm_dialog = true;
                                     ui.close_menu();
@@ -1741,6 +2059,39 @@ fn render_timeline(ctx: &egui::Context, state: &mut AppState) {
                         }
                     }
 
+                    // Drop marker and reorder for a frame drag
+                    if let Some(dragged) = state.timeline_drag.clone() {
+                        let pointer = ui.input(|i| i.pointer.interact_pos());
+                        let drop_index = pointer.map(|pos| {
+                            frame_rects.iter().filter(|r| r.center().x < pos.x).count()
+                        });
+                        if let Some(index) = drop_index {
+                            let x = frame_rects
+                                .get(index)
+                                .map(|r| r.left() - 2.0)
+                                .or_else(|| frame_rects.last().map(|r| r.right() + 2.0));
+                            if let (Some(x), Some(first)) = (x, frame_rects.first()) {
+                                ui.painter().vline(
+                                    x,
+                                    first.y_range(),
+                                    egui::Stroke::new(2.0, egui::Color32::YELLOW),
+                                );
+                            }
+                        }
+                        if ui.input(|i| i.pointer.any_released()) {
+                            state.timeline_drag = None;
+                            if let Some(index) = drop_index {
+                                if index < dragged.start || index > dragged.end {
+                                    frame_edit = Some(FrameEdit::Move(dragged, index));
+                                }
+                            }
+                        }
+                    }
+
+                    if let Some(edit) = frame_edit {
+                        apply_frame_edit(state, edit);
+                    }
+
                     // Add frame buttons
                     let mut add_blank = false;
                     let mut add_copy = false;
@@ -1841,6 +2192,38 @@ fn render_central_panel(ui: &mut egui::Ui, state: &mut AppState, ctx: &egui::Con
                                 ui.checkbox(&mut state.show_grid, "Grid");
                                 ui.checkbox(&mut state.show_labels, "Labels");
 
+                                let outfit_names: Vec<String> = state
+                                    .active_character
+                                    .as_ref()
+                                    .and_then(|name| state.project.as_ref()?.get_character(name))
+                                    .map(|c| c.outfits.iter().map(|o| o.name.clone()).collect())
+                                    .unwrap_or_default();
+                                if !outfit_names.is_empty() {
+                                    ui.label("Outfit:");
+                                    let previous = state.preview_outfit.clone();
+                                    egui::ComboBox::from_id_salt("preview_outfit")
+                                        .selected_text(
+                                            state.preview_outfit.as_deref().unwrap_or("(none)"),
+                                        )
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(
+                                                &mut state.preview_outfit,
+                                                None,
+                                                "(none)",
+                                            );
+                                            for name in &outfit_names {
+                                                ui.selectable_value(
+                                                    &mut state.preview_outfit,
+                                                    Some(name.clone()),
+                                                    name,
+                                                );
+                                            }
+                                        });
+                                    if state.preview_outfit != previous {
+                                        state.frame_thumbnail_cache.clear();
+                                    }
+                                }
+
                                 ui.separator();
                                 ui.heading("Reference image");
                                 ui.separator();
@@ -1903,7 +2286,6 @@ fn render_welcome_screen(ui: &mut egui::Ui, state: &mut AppState) {
         ui.add_space(scaled_margin(20.0, ui_scale));
 
         let button_size = egui::vec2(scaled_margin(160.0, ui_scale), scaled_margin(40.0, ui_scale));
-        let mut open_project_path: Option<String> = None;
         let total_buttons_width = button_size.x * 2.0 + scaled_margin(10.0, ui_scale);
         let available = ui.available_width();
         ui.horizontal(|ui| {
@@ -1929,17 +2311,9 @@ fn render_welcome_screen(ui: &mut egui::Ui, state: &mut AppState) {
                 )
                 .clicked()
             {
-                if let Some(path) = pick_open_file() {
-                    open_project_path = Some(path.to_string_lossy().to_string());
-                }
+                state.open_file_browser(BrowsePurpose::OpenProject);
             }
         });
-        if let Some(path_str) = open_project_path {
-            match state.load_project(&path_str) {
-                Ok(()) => state.set_status(format!("Loaded {}", path_str)),
-                Err(e) => state.set_status(format!("Load failed: {}", e)),
-            }
-        }
 
         // Recent Projects
         let recent = state.config.recent_projects.clone();
@@ -1965,9 +2339,7 @@ fn render_welcome_screen(ui: &mut egui::Ui, state: &mut AppState) {
                     .map(format_relative_time)
                     .unwrap_or_else(|| "unknown".to_string());
 
-                let loaded_project = std::fs::read_to_string(&path_buf)
-                    .ok()
-                    .and_then(|json| Project::from_json(&json).ok());
+                let loaded_project = read_project_manifest(&path_buf).ok();
                 let project_name = loaded_project
                     .as_ref()
                     .map(|p| p.name.clone())
@@ -2082,13 +2454,10 @@ fn render_welcome_screen(ui: &mut egui::Ui, state: &mut AppState) {
             }
 
             if let Some(path) = project_to_open {
-                match state.load_project(&path) {
-                    Ok(()) => state.set_status(format!("Loaded {}", path)),
-                    Err(e) => {
-                        state.set_status(format!("Load failed: {}", e));
-                        if e.contains("Read error") {
-                            state.config.remove_recent(&path);
-                        }
+                if let Err(e) = state.load_project(&path) {
+                    state.set_status(format!("Load failed: {}", e));
+                    if e.contains("Read error") {
+                        state.config.remove_recent(&path);
                     }
                 }
             }